@@ -0,0 +1,107 @@
+//! ADC の測定値からバッテリー残量への換算
+
+/// バッテリー電圧の測定設定（`pins.json` の `battery` から生成）
+#[derive(Debug, Clone, Copy)]
+pub struct BatteryPinConfig {
+    /// ADC1 のピン番号
+    pub pin: u8,
+    /// 分圧比（バッテリー電圧 / ピン電圧）
+    pub divider: f32,
+    /// 0% とみなす電圧（mV）
+    pub empty_mv: u32,
+    /// 100% とみなす電圧（mV）
+    pub full_mv: u32,
+    /// この残量（%）以下でバッテリー低下を表示
+    pub low_percent: u8,
+}
+
+/// バッテリー低下表示を解除するまでに必要な回復幅（%）
+const LOW_HYSTERESIS_PERCENT: u8 = 5;
+
+/// ピン電圧（mV）を分圧前のバッテリー電圧（mV）に戻す
+pub fn battery_mv(pin_mv: u32, config: &BatteryPinConfig) -> u32 {
+    (pin_mv as f32 * config.divider) as u32
+}
+
+/// バッテリー電圧（mV）を残量（0-100%）に換算する（`empty_mv`〜`full_mv` を線形補間）
+pub fn percent(battery_mv: u32, config: &BatteryPinConfig) -> u8 {
+    if battery_mv <= config.empty_mv {
+        return 0;
+    }
+    if battery_mv >= config.full_mv {
+        return 100;
+    }
+    ((battery_mv - config.empty_mv) * 100 / (config.full_mv - config.empty_mv)) as u8
+}
+
+/// バッテリー低下か（測定のばらつきで表示が点滅しないよう、解除は少し回復してから）
+pub fn is_low(percent: u8, was_low: bool, config: &BatteryPinConfig) -> bool {
+    if was_low {
+        percent <= config.low_percent.saturating_add(LOW_HYSTERESIS_PERCENT)
+    } else {
+        percent <= config.low_percent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: BatteryPinConfig = BatteryPinConfig {
+        pin: 34,
+        divider: 2.0,
+        empty_mv: 3300,
+        full_mv: 4200,
+        low_percent: 15,
+    };
+
+    #[test]
+    fn pin_voltage_is_scaled_by_the_divider() {
+        assert_eq!(battery_mv(0, &CONFIG), 0);
+        assert_eq!(battery_mv(1850, &CONFIG), 3700);
+        let no_divider = BatteryPinConfig {
+            divider: 1.0,
+            ..CONFIG
+        };
+        assert_eq!(battery_mv(3100, &no_divider), 3100);
+    }
+
+    #[test]
+    fn percent_is_interpolated_between_empty_and_full() {
+        assert_eq!(percent(3300, &CONFIG), 0);
+        assert_eq!(percent(3750, &CONFIG), 50);
+        assert_eq!(percent(4199, &CONFIG), 99);
+        assert_eq!(percent(4200, &CONFIG), 100);
+    }
+
+    #[test]
+    fn percent_is_clamped_outside_the_range() {
+        assert_eq!(percent(0, &CONFIG), 0);
+        assert_eq!(percent(3000, &CONFIG), 0);
+        assert_eq!(percent(5000, &CONFIG), 100);
+    }
+
+    #[test]
+    fn low_is_set_at_the_threshold() {
+        assert!(!is_low(16, false, &CONFIG));
+        assert!(is_low(15, false, &CONFIG));
+        assert!(is_low(0, false, &CONFIG));
+    }
+
+    #[test]
+    fn low_is_cleared_only_after_recovering_by_the_hysteresis() {
+        assert!(is_low(16, true, &CONFIG));
+        assert!(is_low(20, true, &CONFIG));
+        assert!(!is_low(21, true, &CONFIG));
+    }
+
+    #[test]
+    fn low_threshold_near_100_does_not_overflow() {
+        let config = BatteryPinConfig {
+            low_percent: 100,
+            ..CONFIG
+        };
+        assert!(is_low(100, false, &config));
+        assert!(is_low(100, true, &config));
+    }
+}
@@ -0,0 +1,193 @@
+//! アドバタイズのメーカー固有データに載せる現在の状態
+//!
+//! 接続しなくてもスキャンだけで複数台を監視できるよう、次の形式で載せる（10バイト）。
+//!
+//! | オフセット | 長さ | 内容 |
+//! |-----------|------|------|
+//! | 0 | 2 | Company ID（リトルエンディアン） |
+//! | 2 | 1 | 形式のバージョン（`STATUS_FORMAT_VERSION`） |
+//! | 3 | 1 | フラグ（bit0: エラー、bit1: 接続中、bit2: バッテリー低下） |
+//! | 4 | 1 | バッテリー残量（%、バッテリー無し・未測定は `0xFF`） |
+//! | 5 | 2 | ボタンを押した回数（リトルエンディアン、起動からの累計で一周する） |
+//! | 7 | 3 | ファームウェアバージョン（major, minor, patch） |
+
+use crate::button::event::{ButtonEvent, ButtonGesture, ChordGesture};
+
+/// 形式のバージョン（レイアウトを変えたら上げる）
+pub const STATUS_FORMAT_VERSION: u8 = 1;
+/// Company ID を含むデータ長（build.rs のサイズ検証でも使う）
+pub const STATUS_DATA_LEN: usize = 10;
+
+const FLAG_ERROR: u8 = 1 << 0;
+const FLAG_CONNECTED: u8 = 1 << 1;
+const FLAG_BATTERY_LOW: u8 = 1 << 2;
+
+/// アドバタイズする状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdvStatus {
+    pub error: bool,
+    pub connected: bool,
+    /// バッテリー残量（%）、バッテリー無し・未測定なら None
+    pub battery_percent: Option<u8>,
+    pub battery_low: bool,
+    pub button_presses: u16,
+}
+
+impl AdvStatus {
+    /// `firmware_version` は [`firmware_version`] で作った (major, minor, patch)
+    pub fn encode(&self, company_id: u16, firmware_version: [u8; 3]) -> [u8; STATUS_DATA_LEN] {
+        let mut flags = 0;
+        if self.error {
+            flags |= FLAG_ERROR;
+        }
+        if self.connected {
+            flags |= FLAG_CONNECTED;
+        }
+        if self.battery_low {
+            flags |= FLAG_BATTERY_LOW;
+        }
+        let [company_lo, company_hi] = company_id.to_le_bytes();
+        let [presses_lo, presses_hi] = self.button_presses.to_le_bytes();
+        let [major, minor, patch] = firmware_version;
+        [
+            company_lo,
+            company_hi,
+            STATUS_FORMAT_VERSION,
+            flags,
+            self.battery_percent.unwrap_or(0xFF),
+            presses_lo,
+            presses_hi,
+            major,
+            minor,
+            patch,
+        ]
+    }
+}
+
+/// ボタンを1回押したことを表すイベントか（1回の押下で必ず1つ発行されるリリースで数える）
+pub fn is_press(event: &ButtonEvent) -> bool {
+    matches!(
+        event,
+        ButtonEvent::Gesture {
+            gesture: ButtonGesture::Released { .. },
+            ..
+        } | ButtonEvent::Chord {
+            gesture: ChordGesture::Released { .. },
+            ..
+        }
+    )
+}
+
+/// バージョンの各要素（ファームウェアの `CARGO_PKG_VERSION_*`）を1バイトずつにする
+///
+/// 255 を超える要素は 255、数値でない要素は 0 とする。
+pub fn firmware_version(major: &str, minor: &str, patch: &str) -> [u8; 3] {
+    let part = |s: &str| s.parse::<u32>().map_or(0, |v| v.min(u8::MAX as u32) as u8);
+    [part(major), part(minor), part(patch)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::button::event::{ButtonId, ButtonMask};
+
+    const STATUS: AdvStatus = AdvStatus {
+        error: false,
+        connected: false,
+        battery_percent: None,
+        battery_low: false,
+        button_presses: 0,
+    };
+
+    #[test]
+    fn layout_matches_the_documented_format() {
+        let status = AdvStatus {
+            error: true,
+            connected: true,
+            battery_percent: Some(87),
+            battery_low: true,
+            button_presses: 0x1234,
+        };
+        assert_eq!(
+            status.encode(0xFFFF, [1, 2, 3]),
+            [
+                0xFF,
+                0xFF,
+                STATUS_FORMAT_VERSION,
+                0b111,
+                87,
+                0x34,
+                0x12,
+                1,
+                2,
+                3
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_are_independent() {
+        let flags = |status: AdvStatus| status.encode(0, [0; 3])[3];
+        assert_eq!(flags(STATUS), 0);
+        assert_eq!(
+            flags(AdvStatus {
+                error: true,
+                ..STATUS
+            }),
+            FLAG_ERROR
+        );
+        assert_eq!(
+            flags(AdvStatus {
+                connected: true,
+                ..STATUS
+            }),
+            FLAG_CONNECTED
+        );
+        assert_eq!(
+            flags(AdvStatus {
+                battery_low: true,
+                ..STATUS
+            }),
+            FLAG_BATTERY_LOW
+        );
+    }
+
+    #[test]
+    fn company_id_is_little_endian() {
+        let data = STATUS.encode(0x02E5, [0; 3]);
+        assert_eq!(&data[..2], &[0xE5, 0x02]);
+    }
+
+    #[test]
+    fn unknown_battery_is_encoded_as_ff() {
+        assert_eq!(STATUS.encode(0, [0; 3])[4], 0xFF);
+        let empty = AdvStatus {
+            battery_percent: Some(0),
+            ..STATUS
+        };
+        assert_eq!(empty.encode(0, [0; 3])[4], 0);
+    }
+
+    #[test]
+    fn version_parts_are_clamped() {
+        assert_eq!(firmware_version("0", "1", "0"), [0, 1, 0]);
+        assert_eq!(firmware_version("1", "300", "255"), [1, 255, 255]);
+        assert_eq!(firmware_version("1", "2", "x"), [1, 2, 0]);
+    }
+
+    #[test]
+    fn only_releases_count_as_presses() {
+        let button = ButtonId(0);
+        let gesture = |gesture| ButtonEvent::Gesture { button, gesture };
+        assert!(is_press(&gesture(ButtonGesture::Released { held_ms: 50 })));
+        assert!(!is_press(&gesture(ButtonGesture::ShortPress)));
+        assert!(!is_press(&gesture(ButtonGesture::LongPress)));
+
+        let chord = |gesture| ButtonEvent::Chord {
+            buttons: ButtonMask(0b11),
+            gesture,
+        };
+        assert!(is_press(&chord(ChordGesture::Released { held_ms: 500 })));
+        assert!(!is_press(&chord(ChordGesture::Pressed)));
+    }
+}
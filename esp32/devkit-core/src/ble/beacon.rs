@@ -0,0 +1,415 @@
+//! iBeacon / Eddystone のビーコンの設定とフレーム
+//!
+//! - iBeacon: メーカー固有データ（Company ID 0x004C）
+//! - Eddystone: サービス UUID 0xFEAA のサービスデータ（UID / URL / TLM）
+
+use core::fmt;
+
+/// ビーコンとして送信する形式（`ble.json` の `beacon.mode`、実行時は `BleCommand::SetBeacon` で切り替え）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeaconMode {
+    Off,
+    IBeacon,
+    /// `beacon.eddystone.frames` の frame を順に送信
+    Eddystone,
+}
+
+/// Eddystone の frame の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EddystoneFrame {
+    Uid,
+    Url,
+    /// バッテリー電圧・送信回数・稼働時間
+    Tlm,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IBeaconConfig {
+    pub uuid: [u8; 16],
+    pub major: u16,
+    pub minor: u16,
+    /// 1m での RSSI（dBm）
+    pub measured_power: i8,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EddystoneConfig {
+    pub frames: &'static [EddystoneFrame],
+    /// 0m での RSSI（dBm）
+    pub tx_power: i8,
+    pub namespace: [u8; 10],
+    pub instance: [u8; 6],
+    /// 圧縮済みの URL（先頭はスキーム、`encode_eddystone_url` の結果）
+    pub url: &'static [u8],
+}
+
+/// ビーコン設定（`ble.json` の `beacon` から生成、frame の長さはビルド時に検証済み）
+#[derive(Debug, Clone, Copy)]
+pub struct BeaconConfig {
+    /// 起動時のモード
+    pub mode: BeaconMode,
+    /// ペアリング用のアドバタイズ中もビーコンと交互に送信するか
+    pub interleave: bool,
+    /// 送信内容を切り替える間隔
+    pub slot_ms: u32,
+    pub interval_ms: u32,
+    pub ibeacon: Option<IBeaconConfig>,
+    pub eddystone: Option<EddystoneConfig>,
+}
+
+/// Eddystone のサービス UUID
+pub const EDDYSTONE_SERVICE_UUID: u16 = 0xFEAA;
+const APPLE_COMPANY_ID: u16 = 0x004C;
+/// iBeacon の種類（0x02）と残りの長さ（0x15）
+const IBEACON_PREFIX: [u8; 2] = [0x02, 0x15];
+
+const EDDYSTONE_UID: u8 = 0x00;
+const EDDYSTONE_URL: u8 = 0x10;
+const EDDYSTONE_TLM: u8 = 0x20;
+/// TLM で温度を測定していないことを表す値（8.8 固定小数点の -128℃）
+const TLM_NO_TEMPERATURE: u16 = 0x8000;
+
+/// 送信する frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeaconFrame {
+    IBeacon,
+    Eddystone(EddystoneFrame),
+}
+
+/// アドバタイズに載せる内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeaconPayload {
+    /// メーカー固有データ（Company ID を含む）
+    ManufacturerData(Vec<u8>),
+    /// `EDDYSTONE_SERVICE_UUID` のサービスデータ
+    Eddystone(Vec<u8>),
+}
+
+/// Eddystone-TLM に載せる値
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Telemetry {
+    /// バッテリー電圧（mV、不明なら 0）
+    pub battery_mv: u16,
+    /// ビーコンを送信した回数（起動から）
+    pub adv_count: u32,
+    /// 起動からの時間（0.1秒単位）
+    pub uptime_ds: u32,
+}
+
+/// モードで送信する frame の列（設定が無いモードは空）
+pub fn frames(mode: BeaconMode, config: &BeaconConfig) -> Vec<BeaconFrame> {
+    match mode {
+        BeaconMode::Off => Vec::new(),
+        BeaconMode::IBeacon => config
+            .ibeacon
+            .map(|_| vec![BeaconFrame::IBeacon])
+            .unwrap_or_default(),
+        BeaconMode::Eddystone => config
+            .eddystone
+            .map(|eddystone| {
+                eddystone
+                    .frames
+                    .iter()
+                    .map(|frame| BeaconFrame::Eddystone(*frame))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// frame の内容（設定が無い frame は None）
+pub fn payload(
+    frame: BeaconFrame,
+    config: &BeaconConfig,
+    telemetry: &Telemetry,
+) -> Option<BeaconPayload> {
+    match frame {
+        BeaconFrame::IBeacon => {
+            let ibeacon = config.ibeacon?;
+            let mut data = APPLE_COMPANY_ID.to_le_bytes().to_vec();
+            data.extend_from_slice(&IBEACON_PREFIX);
+            data.extend_from_slice(&ibeacon.uuid);
+            data.extend_from_slice(&ibeacon.major.to_be_bytes());
+            data.extend_from_slice(&ibeacon.minor.to_be_bytes());
+            data.push(ibeacon.measured_power as u8);
+            Some(BeaconPayload::ManufacturerData(data))
+        }
+        BeaconFrame::Eddystone(frame) => {
+            let eddystone = config.eddystone?;
+            let tx_power = eddystone.tx_power as u8;
+            let data = match frame {
+                EddystoneFrame::Uid => {
+                    let mut data = vec![EDDYSTONE_UID, tx_power];
+                    data.extend_from_slice(&eddystone.namespace);
+                    data.extend_from_slice(&eddystone.instance);
+                    // 予約（RFU）
+                    data.extend_from_slice(&[0, 0]);
+                    data
+                }
+                EddystoneFrame::Url => {
+                    let mut data = vec![EDDYSTONE_URL, tx_power];
+                    data.extend_from_slice(eddystone.url);
+                    data
+                }
+                EddystoneFrame::Tlm => {
+                    // バージョン 0（暗号化なし）
+                    let mut data = vec![EDDYSTONE_TLM, 0];
+                    data.extend_from_slice(&telemetry.battery_mv.to_be_bytes());
+                    data.extend_from_slice(&TLM_NO_TEMPERATURE.to_be_bytes());
+                    data.extend_from_slice(&telemetry.adv_count.to_be_bytes());
+                    data.extend_from_slice(&telemetry.uptime_ds.to_be_bytes());
+                    data
+                }
+            };
+            Some(BeaconPayload::Eddystone(data))
+        }
+    }
+}
+
+/// Eddystone-URL の URL 部分（スキームを除く）の最大長
+pub const MAX_URL_LEN: usize = 17;
+const URL_SCHEMES: [&str; 4] = ["http://www.", "https://www.", "http://", "https://"];
+const URL_EXPANSIONS: [&str; 14] = [
+    ".com/", ".org/", ".edu/", ".net/", ".info/", ".biz/", ".gov/", ".com", ".org", ".edu", ".net",
+    ".info", ".biz", ".gov",
+];
+
+/// Eddystone-URL の圧縮エラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlError {
+    UnsupportedScheme,
+    UnsupportedCharacter(char),
+    /// 圧縮後の長さ（スキームを除く）
+    TooLong(usize),
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlError::UnsupportedScheme => write!(f, "must start with http:// or https://"),
+            UrlError::UnsupportedCharacter(c) => write!(f, "has unsupported character {c:?}"),
+            UrlError::TooLong(len) => {
+                write!(f, "is {len} bytes after encoding (max {MAX_URL_LEN})")
+            }
+        }
+    }
+}
+
+/// Eddystone-URL の URL を圧縮する（スキーム・よく使う末尾を1バイトに置き換える、build.rs から使う）
+pub fn encode_eddystone_url(url: &str) -> Result<Vec<u8>, UrlError> {
+    let (scheme, mut rest) = URL_SCHEMES
+        .iter()
+        .enumerate()
+        .find_map(|(i, scheme)| url.strip_prefix(scheme).map(|rest| (i as u8, rest)))
+        .ok_or(UrlError::UnsupportedScheme)?;
+
+    let mut encoded = vec![scheme];
+    while let Some(c) = rest.chars().next() {
+        if let Some((code, expansion)) = URL_EXPANSIONS
+            .iter()
+            .enumerate()
+            .find(|(_, expansion)| rest.starts_with(*expansion))
+        {
+            encoded.push(code as u8);
+            rest = &rest[expansion.len()..];
+        } else if c.is_ascii_graphic() {
+            encoded.push(c as u8);
+            rest = &rest[1..];
+        } else {
+            return Err(UrlError::UnsupportedCharacter(c));
+        }
+    }
+    if encoded.len() - 1 > MAX_URL_LEN {
+        return Err(UrlError::TooLong(encoded.len() - 1));
+    }
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IBEACON: IBeaconConfig = IBeaconConfig {
+        uuid: [0x11; 16],
+        major: 0x0102,
+        minor: 0x0304,
+        measured_power: -59,
+    };
+
+    const EDDYSTONE: EddystoneConfig = EddystoneConfig {
+        frames: &[
+            EddystoneFrame::Uid,
+            EddystoneFrame::Url,
+            EddystoneFrame::Tlm,
+        ],
+        tx_power: -20,
+        namespace: [0xAA; 10],
+        instance: [0xBB; 6],
+        url: &[0x03, b'e', b'x', 0x07],
+    };
+
+    fn config(ibeacon: Option<IBeaconConfig>, eddystone: Option<EddystoneConfig>) -> BeaconConfig {
+        BeaconConfig {
+            mode: BeaconMode::Off,
+            interleave: false,
+            slot_ms: 1000,
+            interval_ms: 100,
+            ibeacon,
+            eddystone,
+        }
+    }
+
+    #[test]
+    fn frames_follow_mode_and_config() {
+        let full = config(Some(IBEACON), Some(EDDYSTONE));
+        assert!(frames(BeaconMode::Off, &full).is_empty());
+        assert_eq!(
+            frames(BeaconMode::IBeacon, &full),
+            vec![BeaconFrame::IBeacon]
+        );
+        assert_eq!(
+            frames(BeaconMode::Eddystone, &full),
+            vec![
+                BeaconFrame::Eddystone(EddystoneFrame::Uid),
+                BeaconFrame::Eddystone(EddystoneFrame::Url),
+                BeaconFrame::Eddystone(EddystoneFrame::Tlm),
+            ]
+        );
+
+        let empty = config(None, None);
+        assert!(frames(BeaconMode::IBeacon, &empty).is_empty());
+        assert!(frames(BeaconMode::Eddystone, &empty).is_empty());
+        assert_eq!(
+            payload(BeaconFrame::IBeacon, &empty, &Telemetry::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn ibeacon_payload() {
+        let data = match payload(
+            BeaconFrame::IBeacon,
+            &config(Some(IBEACON), None),
+            &Telemetry::default(),
+        ) {
+            Some(BeaconPayload::ManufacturerData(data)) => data,
+            other => panic!("unexpected payload: {other:?}"),
+        };
+        // Company ID(2) + 種類・長さ(2) + UUID(16) + major(2) + minor(2) + 電波強度(1)
+        assert_eq!(data.len(), 25);
+        assert_eq!(&data[..4], &[0x4C, 0x00, 0x02, 0x15]);
+        assert_eq!(data[3] as usize, data.len() - 4);
+        assert_eq!(&data[4..20], &[0x11; 16]);
+        assert_eq!(&data[20..24], &[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(data[24], (-59i8) as u8);
+    }
+
+    fn eddystone_payload(frame: EddystoneFrame, telemetry: &Telemetry) -> Vec<u8> {
+        match payload(
+            BeaconFrame::Eddystone(frame),
+            &config(None, Some(EDDYSTONE)),
+            telemetry,
+        ) {
+            Some(BeaconPayload::Eddystone(data)) => data,
+            other => panic!("unexpected payload: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn eddystone_uid_payload() {
+        let data = eddystone_payload(EddystoneFrame::Uid, &Telemetry::default());
+        assert_eq!(data.len(), 20);
+        assert_eq!(&data[..2], &[0x00, (-20i8) as u8]);
+        assert_eq!(&data[2..12], &[0xAA; 10]);
+        assert_eq!(&data[12..18], &[0xBB; 6]);
+        assert_eq!(&data[18..], &[0, 0]);
+    }
+
+    #[test]
+    fn eddystone_url_payload() {
+        let data = eddystone_payload(EddystoneFrame::Url, &Telemetry::default());
+        assert_eq!(data, vec![0x10, (-20i8) as u8, 0x03, b'e', b'x', 0x07]);
+    }
+
+    #[test]
+    fn eddystone_tlm_payload() {
+        let telemetry = Telemetry {
+            battery_mv: 3700,
+            adv_count: 0x0102_0304,
+            uptime_ds: 0x0A0B_0C0D,
+        };
+        let data = eddystone_payload(EddystoneFrame::Tlm, &telemetry);
+        assert_eq!(data.len(), 14);
+        assert_eq!(&data[..2], &[0x20, 0x00]);
+        assert_eq!(&data[2..4], &3700u16.to_be_bytes());
+        assert_eq!(&data[4..6], &[0x80, 0x00]);
+        assert_eq!(&data[6..10], &[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(&data[10..], &[0x0A, 0x0B, 0x0C, 0x0D]);
+    }
+
+    #[test]
+    fn url_schemes() {
+        assert_eq!(encode_eddystone_url("http://www.a"), Ok(vec![0x00, b'a']));
+        assert_eq!(encode_eddystone_url("https://www.a"), Ok(vec![0x01, b'a']));
+        assert_eq!(encode_eddystone_url("http://a"), Ok(vec![0x02, b'a']));
+        assert_eq!(encode_eddystone_url("https://a"), Ok(vec![0x03, b'a']));
+        assert_eq!(
+            encode_eddystone_url("ftp://a"),
+            Err(UrlError::UnsupportedScheme)
+        );
+        assert_eq!(
+            encode_eddystone_url("a.com"),
+            Err(UrlError::UnsupportedScheme)
+        );
+    }
+
+    #[test]
+    fn url_expansions() {
+        assert_eq!(
+            encode_eddystone_url("https://example.com/"),
+            Ok(vec![0x03, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0x00])
+        );
+        // 末尾のスラッシュ無しは別のコード
+        assert_eq!(
+            encode_eddystone_url("https://a.com"),
+            Ok(vec![0x03, b'a', 0x07])
+        );
+        assert_eq!(
+            encode_eddystone_url("http://www.a.org/b.gov"),
+            Ok(vec![0x00, b'a', 0x01, b'b', 0x0D])
+        );
+        assert_eq!(
+            encode_eddystone_url("https://a.info/x.biz/"),
+            Ok(vec![0x03, b'a', 0x04, b'x', 0x05])
+        );
+    }
+
+    #[test]
+    fn url_rejects_unsupported_characters() {
+        assert_eq!(
+            encode_eddystone_url("https://a b"),
+            Err(UrlError::UnsupportedCharacter(' '))
+        );
+        assert_eq!(
+            encode_eddystone_url("https://ä"),
+            Err(UrlError::UnsupportedCharacter('ä'))
+        );
+    }
+
+    #[test]
+    fn url_length_limit() {
+        let max = format!("https://{}", "a".repeat(MAX_URL_LEN));
+        assert_eq!(
+            encode_eddystone_url(&max).map(|url| url.len()),
+            Ok(MAX_URL_LEN + 1)
+        );
+        let long = format!("https://{}", "a".repeat(MAX_URL_LEN + 1));
+        assert_eq!(
+            encode_eddystone_url(&long),
+            Err(UrlError::TooLong(MAX_URL_LEN + 1))
+        );
+        // 圧縮後の長さで判定する
+        let compressed = format!("https://{}.com/", "a".repeat(MAX_URL_LEN - 1));
+        assert!(encode_eddystone_url(&compressed).is_ok());
+    }
+}
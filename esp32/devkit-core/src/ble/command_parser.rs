@@ -0,0 +1,879 @@
+//! コマンドキャラクタリスティックに書き込まれたテキストの解析
+//!
+//! 書式（空白区切り、コマンド名は大文字小文字を区別しない）:
+//!
+//! - `led <name> on|off|release`
+//! - `led <name> level <0-255> [#rrggbb]`
+//! - `led <name> blink [on_ms] [off_ms] [#rrggbb]`
+//! - `led <name> fade <0-255> <duration_ms> [#rrggbb]`
+//! - `led <name> breathe [period_ms] [#rrggbb]`
+//! - `led <name> pattern <preset> [#rrggbb]`
+//! - `adv stop`
+//! - `state`
+//! - `conns`
+//! - `reboot`
+//! - `log <off|error|warn|info|debug|trace>`
+//! - `tasks`
+//! - `scan [duration_ms] [name=<prefix>] [uuid=<uuid>] [rssi=<dBm>]`
+//! - `gatt connect <address> [random]` / `gatt disconnect <address>`
+//! - `gatt read|sub <address> <service> <characteristic>`
+//! - `gatt write <address> <service> <characteristic> <hex> [noresp]`
+//! - `beacon off|ibeacon|eddystone`
+//! - `passkey <000000-999999>`
+//! - `help`
+
+use core::fmt;
+
+use crate::ble::beacon::BeaconMode;
+use crate::ble::gatt_client::{self, GattRequest, RemoteCharacteristic};
+use crate::ble::scan::{ScanFilter, DEFAULT_SCAN_DURATION_MS, MAX_SCAN_DURATION_MS};
+use crate::led::color::Rgb;
+
+/// 1回の書き込みで受け付ける最大長（バイト）
+pub const MAX_COMMAND_LEN: usize = 128;
+
+/// リモートから指定するLEDの表示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteLedMode {
+    On,
+    Off,
+    /// 指定した輝度で点灯
+    Level(u8),
+    /// `count` 回で終了して消灯（`None` なら無限、テキストコマンドでは常に `None`）
+    Blink {
+        on_ms: u32,
+        off_ms: u32,
+        count: Option<u16>,
+    },
+    /// 現在の輝度から `level` まで `duration_ms` かけて変化
+    Fade {
+        level: u8,
+        duration_ms: u32,
+    },
+    Breathe {
+        period_ms: u32,
+    },
+    /// `config/led_patterns.json` のプリセット名
+    Preset(String),
+    /// リモートからの表示要求を解除
+    Release,
+}
+
+/// リモートから受け付けるアプリケーションコマンド
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteCommand {
+    /// `led` は `pins.json` の LED 名（存在確認は実行側で行う）
+    SetLed {
+        led: String,
+        mode: RemoteLedMode,
+        color: Option<Rgb>,
+    },
+    StopAdvertise,
+    QueryState,
+    /// 接続中のセントラルの一覧
+    QueryConnections,
+    Reboot,
+    /// ログ出力レベルの変更
+    SetLogLevel(log::LevelFilter),
+    /// タスクの稼働状況とヒープ残量の問い合わせ
+    QueryTasks,
+    /// 周辺デバイスのスキャン
+    Scan {
+        duration_ms: u32,
+        filter: ScanFilter,
+    },
+    /// GATT クライアントとしてリモートのペリフェラルを操作
+    Gatt(GattRequest),
+    /// ビーコンのモード切り替え
+    SetBeacon(BeaconMode),
+    /// ペアリング中の相手が表示したパスキー（`passkey_entry` のみ）
+    EnterPasskey(u32),
+    /// コマンド一覧
+    Help,
+}
+
+/// `help` で返すコマンド一覧
+pub const HELP: &str =
+    "led <name> on|off|release|level N|blink [on] [off]|fade N ms|breathe [ms]|pattern <preset> [#rrggbb]; \
+adv stop; state; conns; reboot; log <off|error|warn|info|debug|trace>; tasks; \
+scan [ms] [name=<prefix>] [uuid=<uuid>] [rssi=<dBm>]; \
+gatt connect <addr> [random]|disconnect <addr>|read|sub <addr> <svc> <chr>|write <addr> <svc> <chr> <hex> [noresp]; \
+beacon off|ibeacon|eddystone; passkey <6 digits>; help";
+
+/// コマンドの解析エラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    TooLong(usize),
+    NotUtf8,
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    InvalidArgument { name: &'static str, value: String },
+    UnexpectedArgument(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty command"),
+            ParseError::TooLong(len) => {
+                write!(f, "command too long ({len} > {MAX_COMMAND_LEN} bytes)")
+            }
+            ParseError::NotUtf8 => write!(f, "command is not valid UTF-8"),
+            ParseError::UnknownCommand(cmd) => write!(f, "unknown command: {cmd}"),
+            ParseError::MissingArgument(name) => write!(f, "missing argument: {name}"),
+            ParseError::InvalidArgument { name, value } => {
+                write!(f, "invalid {name}: {value}")
+            }
+            ParseError::UnexpectedArgument(arg) => write!(f, "unexpected argument: {arg}"),
+        }
+    }
+}
+
+/// 書き込まれたバイト列をコマンドに変換する
+pub fn parse(payload: &[u8]) -> Result<RemoteCommand, ParseError> {
+    if payload.len() > MAX_COMMAND_LEN {
+        return Err(ParseError::TooLong(payload.len()));
+    }
+    let text = core::str::from_utf8(payload).map_err(|_| ParseError::NotUtf8)?;
+
+    let mut args = text.split_whitespace();
+    let command = args.next().ok_or(ParseError::Empty)?.to_ascii_lowercase();
+
+    let parsed = match command.as_str() {
+        "led" => parse_led(&mut args)?,
+        "adv" => match args.next().map(str::to_ascii_lowercase).as_deref() {
+            Some("stop") => RemoteCommand::StopAdvertise,
+            Some(other) => {
+                return Err(ParseError::InvalidArgument {
+                    name: "adv action",
+                    value: other.to_string(),
+                })
+            }
+            None => return Err(ParseError::MissingArgument("adv action")),
+        },
+        "state" => RemoteCommand::QueryState,
+        "conns" => RemoteCommand::QueryConnections,
+        "reboot" => RemoteCommand::Reboot,
+        "log" => {
+            let value = args
+                .next()
+                .ok_or(ParseError::MissingArgument("log level"))?;
+            RemoteCommand::SetLogLevel(value.parse().map_err(|_| ParseError::InvalidArgument {
+                name: "log level",
+                value: value.to_string(),
+            })?)
+        }
+        "tasks" => RemoteCommand::QueryTasks,
+        "scan" => parse_scan(&mut args)?,
+        "gatt" => RemoteCommand::Gatt(parse_gatt(&mut args)?),
+        "beacon" => {
+            RemoteCommand::SetBeacon(match args.next().map(str::to_ascii_lowercase).as_deref() {
+                Some("off") => BeaconMode::Off,
+                Some("ibeacon") => BeaconMode::IBeacon,
+                Some("eddystone") => BeaconMode::Eddystone,
+                Some(other) => {
+                    return Err(ParseError::InvalidArgument {
+                        name: "beacon mode",
+                        value: other.to_string(),
+                    })
+                }
+                None => return Err(ParseError::MissingArgument("beacon mode")),
+            })
+        }
+        "passkey" => {
+            let value = args.next().ok_or(ParseError::MissingArgument("passkey"))?;
+            // 6桁の数字（先頭の 0 も含めて入力する）
+            if value.len() != 6 || !value.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseError::InvalidArgument {
+                    name: "passkey",
+                    value: value.to_string(),
+                });
+            }
+            RemoteCommand::EnterPasskey(parse_number("passkey", value)?)
+        }
+        "help" => RemoteCommand::Help,
+        _ => return Err(ParseError::UnknownCommand(command)),
+    };
+
+    match args.next() {
+        Some(extra) => Err(ParseError::UnexpectedArgument(extra.to_string())),
+        None => Ok(parsed),
+    }
+}
+
+fn parse_led<'a>(args: &mut impl Iterator<Item = &'a str>) -> Result<RemoteCommand, ParseError> {
+    let led = args
+        .next()
+        .ok_or(ParseError::MissingArgument("LED name"))?
+        .to_string();
+    let mode = args
+        .next()
+        .ok_or(ParseError::MissingArgument("LED mode"))?
+        .to_ascii_lowercase();
+
+    // 数値引数の後ろに色指定（#rrggbb）を置けるよう、残りを先に分けておく
+    let mut rest: Vec<&str> = args.collect();
+    let color = match rest.last() {
+        Some(arg) if arg.starts_with('#') => {
+            let color = parse_color(arg)?;
+            rest.pop();
+            Some(color)
+        }
+        _ => None,
+    };
+    let mut rest = rest.into_iter();
+
+    let mode = match mode.as_str() {
+        "on" => RemoteLedMode::On,
+        "off" => RemoteLedMode::Off,
+        "release" => RemoteLedMode::Release,
+        "level" => {
+            let value = rest.next().ok_or(ParseError::MissingArgument("level"))?;
+            RemoteLedMode::Level(parse_number("level", value)?)
+        }
+        "blink" => {
+            let on_ms = rest
+                .next()
+                .map(|v| parse_number("on_ms", v))
+                .transpose()?
+                .unwrap_or(500);
+            let off_ms = rest
+                .next()
+                .map(|v| parse_number("off_ms", v))
+                .transpose()?
+                .unwrap_or(on_ms);
+            RemoteLedMode::Blink {
+                on_ms,
+                off_ms,
+                count: None,
+            }
+        }
+        "fade" => {
+            let level = rest.next().ok_or(ParseError::MissingArgument("level"))?;
+            let duration_ms = rest
+                .next()
+                .ok_or(ParseError::MissingArgument("duration_ms"))?;
+            RemoteLedMode::Fade {
+                level: parse_number("level", level)?,
+                duration_ms: parse_number("duration_ms", duration_ms)?,
+            }
+        }
+        "breathe" => {
+            let period_ms = rest
+                .next()
+                .map(|v| parse_number("period_ms", v))
+                .transpose()?
+                .unwrap_or(3000);
+            RemoteLedMode::Breathe { period_ms }
+        }
+        "pattern" => {
+            let name = rest.next().ok_or(ParseError::MissingArgument("pattern"))?;
+            RemoteLedMode::Preset(name.to_string())
+        }
+        _ => {
+            return Err(ParseError::InvalidArgument {
+                name: "LED mode",
+                value: mode,
+            })
+        }
+    };
+
+    if let Some(extra) = rest.next() {
+        return Err(ParseError::UnexpectedArgument(extra.to_string()));
+    }
+    // 消灯・解除に色は意味を持たない
+    if color.is_some() && matches!(mode, RemoteLedMode::Off | RemoteLedMode::Release) {
+        return Err(ParseError::UnexpectedArgument("color".to_string()));
+    }
+
+    Ok(RemoteCommand::SetLed { led, mode, color })
+}
+
+fn parse_scan<'a>(args: &mut impl Iterator<Item = &'a str>) -> Result<RemoteCommand, ParseError> {
+    let mut duration_ms = DEFAULT_SCAN_DURATION_MS;
+    let mut filter = ScanFilter::default();
+    for (i, arg) in args.enumerate() {
+        match arg.split_once('=') {
+            Some(("name", value)) => filter.name_prefix = Some(value.to_string()),
+            Some(("uuid", value)) => filter.service_uuid = Some(value.to_ascii_lowercase()),
+            Some(("rssi", value)) => filter.min_rssi = Some(parse_number("rssi", value)?),
+            None if i == 0 => {
+                duration_ms = parse_number("duration_ms", arg)?;
+                if duration_ms == 0 || duration_ms > MAX_SCAN_DURATION_MS {
+                    return Err(ParseError::InvalidArgument {
+                        name: "duration_ms",
+                        value: arg.to_string(),
+                    });
+                }
+            }
+            _ => return Err(ParseError::UnexpectedArgument(arg.to_string())),
+        }
+    }
+    Ok(RemoteCommand::Scan {
+        duration_ms,
+        filter,
+    })
+}
+
+fn parse_gatt<'a>(args: &mut impl Iterator<Item = &'a str>) -> Result<GattRequest, ParseError> {
+    let action = args
+        .next()
+        .ok_or(ParseError::MissingArgument("gatt action"))?
+        .to_ascii_lowercase();
+    let address = parse_checked(args, "address", gatt_client::is_address)?;
+
+    let request = match action.as_str() {
+        "connect" => {
+            let random = match args.next() {
+                Some(arg) if arg.eq_ignore_ascii_case("random") => true,
+                Some(other) => return Err(ParseError::UnexpectedArgument(other.to_string())),
+                None => false,
+            };
+            GattRequest::Connect { address, random }
+        }
+        "disconnect" => GattRequest::Disconnect { address },
+        "read" | "sub" | "write" => {
+            let target = RemoteCharacteristic {
+                service: parse_checked(args, "service", gatt_client::is_uuid)?,
+                characteristic: parse_checked(args, "characteristic", gatt_client::is_uuid)?,
+            };
+            match action.as_str() {
+                "read" => GattRequest::Read { address, target },
+                "sub" => GattRequest::Subscribe { address, target },
+                _ => {
+                    let hex = args.next().ok_or(ParseError::MissingArgument("value"))?;
+                    let value =
+                        gatt_client::from_hex(hex).ok_or_else(|| ParseError::InvalidArgument {
+                            name: "value",
+                            value: hex.to_string(),
+                        })?;
+                    let with_response = match args.next() {
+                        Some(arg) if arg.eq_ignore_ascii_case("noresp") => false,
+                        Some(other) => {
+                            return Err(ParseError::UnexpectedArgument(other.to_string()))
+                        }
+                        None => true,
+                    };
+                    GattRequest::Write {
+                        address,
+                        target,
+                        value,
+                        with_response,
+                    }
+                }
+            }
+        }
+        _ => {
+            return Err(ParseError::InvalidArgument {
+                name: "gatt action",
+                value: action,
+            })
+        }
+    };
+    Ok(request)
+}
+
+/// 次の引数を取り出して書式を確認する（小文字にそろえる）
+fn parse_checked<'a>(
+    args: &mut impl Iterator<Item = &'a str>,
+    name: &'static str,
+    is_valid: fn(&str) -> bool,
+) -> Result<String, ParseError> {
+    let value = args.next().ok_or(ParseError::MissingArgument(name))?;
+    if !is_valid(value) {
+        return Err(ParseError::InvalidArgument {
+            name,
+            value: value.to_string(),
+        });
+    }
+    Ok(value.to_ascii_lowercase())
+}
+
+fn parse_number<T: core::str::FromStr>(name: &'static str, value: &str) -> Result<T, ParseError> {
+    value.parse().map_err(|_| ParseError::InvalidArgument {
+        name,
+        value: value.to_string(),
+    })
+}
+
+/// `#rrggbb` 形式の色
+fn parse_color(value: &str) -> Result<Rgb, ParseError> {
+    let invalid = || ParseError::InvalidArgument {
+        name: "color",
+        value: value.to_string(),
+    };
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let rgb = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+    Ok(Rgb::new((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(text: &str) -> Result<RemoteCommand, ParseError> {
+        parse(text.as_bytes())
+    }
+
+    fn led(mode: RemoteLedMode, color: Option<Rgb>) -> RemoteCommand {
+        RemoteCommand::SetLed {
+            led: "status".to_string(),
+            mode,
+            color,
+        }
+    }
+
+    fn invalid(name: &'static str, value: &str) -> ParseError {
+        ParseError::InvalidArgument {
+            name,
+            value: value.to_string(),
+        }
+    }
+
+    fn unexpected(arg: &str) -> ParseError {
+        ParseError::UnexpectedArgument(arg.to_string())
+    }
+
+    const ADDRESS: &str = "aa:bb:cc:dd:ee:ff";
+
+    fn characteristic() -> RemoteCharacteristic {
+        RemoteCharacteristic {
+            service: "180f".to_string(),
+            characteristic: "2a19".to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_every_verb() {
+        let cases = [
+            ("led status on", led(RemoteLedMode::On, None)),
+            ("led status off", led(RemoteLedMode::Off, None)),
+            ("led status release", led(RemoteLedMode::Release, None)),
+            ("led status level 128", led(RemoteLedMode::Level(128), None)),
+            (
+                "led status blink",
+                led(
+                    RemoteLedMode::Blink {
+                        on_ms: 500,
+                        off_ms: 500,
+                        count: None,
+                    },
+                    None,
+                ),
+            ),
+            (
+                "led status blink 100",
+                led(
+                    RemoteLedMode::Blink {
+                        on_ms: 100,
+                        off_ms: 100,
+                        count: None,
+                    },
+                    None,
+                ),
+            ),
+            (
+                "led status blink 100 900",
+                led(
+                    RemoteLedMode::Blink {
+                        on_ms: 100,
+                        off_ms: 900,
+                        count: None,
+                    },
+                    None,
+                ),
+            ),
+            (
+                "led status fade 200 1500",
+                led(
+                    RemoteLedMode::Fade {
+                        level: 200,
+                        duration_ms: 1500,
+                    },
+                    None,
+                ),
+            ),
+            (
+                "led status breathe",
+                led(RemoteLedMode::Breathe { period_ms: 3000 }, None),
+            ),
+            (
+                "led status breathe 1500",
+                led(RemoteLedMode::Breathe { period_ms: 1500 }, None),
+            ),
+            (
+                "led status pattern sos",
+                led(RemoteLedMode::Preset("sos".to_string()), None),
+            ),
+            ("adv stop", RemoteCommand::StopAdvertise),
+            ("state", RemoteCommand::QueryState),
+            ("conns", RemoteCommand::QueryConnections),
+            ("reboot", RemoteCommand::Reboot),
+            ("tasks", RemoteCommand::QueryTasks),
+            (
+                "scan",
+                RemoteCommand::Scan {
+                    duration_ms: DEFAULT_SCAN_DURATION_MS,
+                    filter: ScanFilter::default(),
+                },
+            ),
+            (
+                "scan 1000 name=Dev uuid=180F rssi=-70",
+                RemoteCommand::Scan {
+                    duration_ms: 1000,
+                    filter: ScanFilter {
+                        name_prefix: Some("Dev".to_string()),
+                        service_uuid: Some("180f".to_string()),
+                        min_rssi: Some(-70),
+                    },
+                },
+            ),
+            (
+                "scan rssi=-50",
+                RemoteCommand::Scan {
+                    duration_ms: DEFAULT_SCAN_DURATION_MS,
+                    filter: ScanFilter {
+                        min_rssi: Some(-50),
+                        ..ScanFilter::default()
+                    },
+                },
+            ),
+            (
+                "gatt connect AA:BB:CC:DD:EE:FF",
+                RemoteCommand::Gatt(GattRequest::Connect {
+                    address: ADDRESS.to_string(),
+                    random: false,
+                }),
+            ),
+            (
+                "gatt connect aa:bb:cc:dd:ee:ff random",
+                RemoteCommand::Gatt(GattRequest::Connect {
+                    address: ADDRESS.to_string(),
+                    random: true,
+                }),
+            ),
+            (
+                "gatt disconnect aa:bb:cc:dd:ee:ff",
+                RemoteCommand::Gatt(GattRequest::Disconnect {
+                    address: ADDRESS.to_string(),
+                }),
+            ),
+            (
+                "gatt read aa:bb:cc:dd:ee:ff 180F 2A19",
+                RemoteCommand::Gatt(GattRequest::Read {
+                    address: ADDRESS.to_string(),
+                    target: characteristic(),
+                }),
+            ),
+            (
+                "gatt sub aa:bb:cc:dd:ee:ff 180f 2a19",
+                RemoteCommand::Gatt(GattRequest::Subscribe {
+                    address: ADDRESS.to_string(),
+                    target: characteristic(),
+                }),
+            ),
+            (
+                "gatt write aa:bb:cc:dd:ee:ff 180f 2a19 01ff",
+                RemoteCommand::Gatt(GattRequest::Write {
+                    address: ADDRESS.to_string(),
+                    target: characteristic(),
+                    value: vec![0x01, 0xff],
+                    with_response: true,
+                }),
+            ),
+            (
+                "gatt write aa:bb:cc:dd:ee:ff 180f 2a19 01FF noresp",
+                RemoteCommand::Gatt(GattRequest::Write {
+                    address: ADDRESS.to_string(),
+                    target: characteristic(),
+                    value: vec![0x01, 0xff],
+                    with_response: false,
+                }),
+            ),
+            ("beacon off", RemoteCommand::SetBeacon(BeaconMode::Off)),
+            (
+                "beacon ibeacon",
+                RemoteCommand::SetBeacon(BeaconMode::IBeacon),
+            ),
+            (
+                "beacon eddystone",
+                RemoteCommand::SetBeacon(BeaconMode::Eddystone),
+            ),
+            ("passkey 012345", RemoteCommand::EnterPasskey(12345)),
+            ("passkey 999999", RemoteCommand::EnterPasskey(999_999)),
+            ("help", RemoteCommand::Help),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_str(text), Ok(expected), "{text}");
+        }
+    }
+
+    #[test]
+    fn verbs_are_case_insensitive_and_whitespace_tolerant() {
+        assert_eq!(parse_str("  STATE \r\n"), Ok(RemoteCommand::QueryState));
+        assert_eq!(parse_str("Adv STOP"), Ok(RemoteCommand::StopAdvertise));
+        assert_eq!(parse_str("LED status ON"), Ok(led(RemoteLedMode::On, None)));
+        assert_eq!(
+            parse_str("beacon IBeacon"),
+            Ok(RemoteCommand::SetBeacon(BeaconMode::IBeacon))
+        );
+    }
+
+    #[test]
+    fn led_name_keeps_its_case() {
+        assert_eq!(
+            parse_str("led Status on"),
+            Ok(RemoteCommand::SetLed {
+                led: "Status".to_string(),
+                mode: RemoteLedMode::On,
+                color: None,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_missing_arguments() {
+        let cases = [
+            ("led", "LED name"),
+            ("led status", "LED mode"),
+            ("led status level", "level"),
+            ("led status level #ff0000", "level"),
+            ("led status pattern", "pattern"),
+            ("led status fade", "level"),
+            ("led status fade 100", "duration_ms"),
+            ("led status fade 100 #ff0000", "duration_ms"),
+            ("adv", "adv action"),
+            ("log", "log level"),
+            ("beacon", "beacon mode"),
+            ("passkey", "passkey"),
+            ("gatt", "gatt action"),
+            ("gatt connect", "address"),
+            ("gatt read aa:bb:cc:dd:ee:ff", "service"),
+            ("gatt read aa:bb:cc:dd:ee:ff 180f", "characteristic"),
+            ("gatt write aa:bb:cc:dd:ee:ff 180f 2a19", "value"),
+        ];
+        for (text, name) in cases {
+            assert_eq!(
+                parse_str(text),
+                Err(ParseError::MissingArgument(name)),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_extra_arguments() {
+        let cases = [
+            ("state now", "now"),
+            ("conns all", "all"),
+            ("reboot 1", "1"),
+            ("tasks x", "x"),
+            ("help me", "me"),
+            ("adv stop now", "now"),
+            ("log info debug", "debug"),
+            ("beacon off on", "on"),
+            ("passkey 123456 7", "7"),
+            ("led status on 1", "1"),
+            ("led status level 1 2", "2"),
+            ("led status blink 1 2 3", "3"),
+            ("led status breathe 1 2", "2"),
+            ("led status fade 1 2 3", "3"),
+            ("led status pattern a b", "b"),
+            ("scan 1000 2000", "2000"),
+            ("scan color=red", "color=red"),
+            ("gatt connect aa:bb:cc:dd:ee:ff public", "public"),
+            ("gatt disconnect aa:bb:cc:dd:ee:ff now", "now"),
+            ("gatt read aa:bb:cc:dd:ee:ff 180f 2a19 x", "x"),
+            ("gatt write aa:bb:cc:dd:ee:ff 180f 2a19 01 resp", "resp"),
+            ("gatt write aa:bb:cc:dd:ee:ff 180f 2a19 01 noresp x", "x"),
+        ];
+        for (text, arg) in cases {
+            assert_eq!(parse_str(text), Err(unexpected(arg)), "{text}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        let cases = [
+            ("adv start", invalid("adv action", "start")),
+            ("led status dim", invalid("LED mode", "dim")),
+            ("led status level 256", invalid("level", "256")),
+            ("led status level -1", invalid("level", "-1")),
+            ("led status blink fast", invalid("on_ms", "fast")),
+            ("led status blink 100 slow", invalid("off_ms", "slow")),
+            ("led status breathe -5", invalid("period_ms", "-5")),
+            ("led status fade 256 100", invalid("level", "256")),
+            ("led status fade 10 soon", invalid("duration_ms", "soon")),
+            ("beacon altbeacon", invalid("beacon mode", "altbeacon")),
+            ("passkey 12345", invalid("passkey", "12345")),
+            ("passkey 1234567", invalid("passkey", "1234567")),
+            ("passkey +12345", invalid("passkey", "+12345")),
+            ("passkey abcdef", invalid("passkey", "abcdef")),
+            ("scan 0", invalid("duration_ms", "0")),
+            ("scan 30001", invalid("duration_ms", "30001")),
+            ("scan soon", invalid("duration_ms", "soon")),
+            ("scan rssi=strong", invalid("rssi", "strong")),
+            (
+                "gatt pair aa:bb:cc:dd:ee:ff",
+                invalid("gatt action", "pair"),
+            ),
+            (
+                "gatt connect aa:bb:cc:dd:ee",
+                invalid("address", "aa:bb:cc:dd:ee"),
+            ),
+            (
+                "gatt connect aa:bb:cc:dd:ee:gg",
+                invalid("address", "aa:bb:cc:dd:ee:gg"),
+            ),
+            (
+                "gatt read aa:bb:cc:dd:ee:ff 180 2a19",
+                invalid("service", "180"),
+            ),
+            (
+                "gatt read aa:bb:cc:dd:ee:ff 180f 0000-1111",
+                invalid("characteristic", "0000-1111"),
+            ),
+        ];
+        for (text, error) in cases {
+            assert_eq!(parse_str(text), Err(error), "{text}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_hex_values() {
+        for hex in ["0", "abc", "zz", "0x01", "ｆｆ"] {
+            let text = format!("gatt write aa:bb:cc:dd:ee:ff 180f 2a19 {hex}");
+            assert_eq!(parse_str(&text), Err(invalid("value", hex)), "{text}");
+        }
+    }
+
+    #[test]
+    fn parses_colors() {
+        let cases = [
+            (
+                "led status on #ff8000",
+                RemoteLedMode::On,
+                Rgb::new(255, 128, 0),
+            ),
+            ("led status on #FFFFFF", RemoteLedMode::On, Rgb::WHITE),
+            (
+                "led status level 10 #000001",
+                RemoteLedMode::Level(10),
+                Rgb::new(0, 0, 1),
+            ),
+            (
+                "led status blink 100 #00ff00",
+                RemoteLedMode::Blink {
+                    on_ms: 100,
+                    off_ms: 100,
+                    count: None,
+                },
+                Rgb::new(0, 255, 0),
+            ),
+            (
+                "led status fade 0 500 #00ffff",
+                RemoteLedMode::Fade {
+                    level: 0,
+                    duration_ms: 500,
+                },
+                Rgb::new(0, 255, 255),
+            ),
+            (
+                "led status breathe #0000ff",
+                RemoteLedMode::Breathe { period_ms: 3000 },
+                Rgb::new(0, 0, 255),
+            ),
+            (
+                "led status pattern sos #ff0000",
+                RemoteLedMode::Preset("sos".to_string()),
+                Rgb::new(255, 0, 0),
+            ),
+        ];
+        for (text, mode, color) in cases {
+            assert_eq!(parse_str(text), Ok(led(mode, Some(color))), "{text}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_colors() {
+        for color in ["#fff", "#ff00001", "#gg0000", "#", "#+12345"] {
+            let text = format!("led status on {color}");
+            assert_eq!(parse_str(&text), Err(invalid("color", color)), "{text}");
+        }
+        // 消灯・解除に色は指定できない
+        for mode in ["off", "release"] {
+            let text = format!("led status {mode} #ff0000");
+            assert_eq!(parse_str(&text), Err(unexpected("color")), "{text}");
+        }
+    }
+
+    #[test]
+    fn parses_log_levels() {
+        let cases = [
+            ("off", log::LevelFilter::Off),
+            ("error", log::LevelFilter::Error),
+            ("warn", log::LevelFilter::Warn),
+            ("info", log::LevelFilter::Info),
+            ("DEBUG", log::LevelFilter::Debug),
+            ("trace", log::LevelFilter::Trace),
+        ];
+        for (level, expected) in cases {
+            assert_eq!(
+                parse_str(&format!("log {level}")),
+                Ok(RemoteCommand::SetLogLevel(expected)),
+                "{level}"
+            );
+        }
+        assert_eq!(
+            parse_str("log verbose"),
+            Err(invalid("log level", "verbose"))
+        );
+    }
+
+    #[test]
+    fn rejects_empty_long_and_non_utf8_input() {
+        assert_eq!(parse_str(""), Err(ParseError::Empty));
+        assert_eq!(parse_str(" \r\n"), Err(ParseError::Empty));
+        assert_eq!(parse(&[b'l', 0xff]), Err(ParseError::NotUtf8));
+        let long = format!("led {} on", "x".repeat(MAX_COMMAND_LEN));
+        assert_eq!(parse_str(&long), Err(ParseError::TooLong(long.len())));
+        let max = format!("led {} on", "x".repeat(MAX_COMMAND_LEN - 7));
+        assert_eq!(max.len(), MAX_COMMAND_LEN);
+        assert!(parse_str(&max).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_commands() {
+        assert_eq!(
+            parse_str("Blink status"),
+            Err(ParseError::UnknownCommand("blink".to_string()))
+        );
+    }
+
+    #[test]
+    fn help_lists_every_verb() {
+        for verb in [
+            "led", "adv", "state", "conns", "reboot", "log", "tasks", "scan", "gatt", "beacon",
+            "passkey", "help",
+        ] {
+            assert!(
+                HELP.split(';')
+                    .any(|entry| entry.trim_start().starts_with(verb)),
+                "{verb}"
+            );
+        }
+    }
+
+    #[test]
+    fn errors_are_readable() {
+        assert_eq!(invalid("level", "256").to_string(), "invalid level: 256");
+        assert_eq!(
+            ParseError::TooLong(200).to_string(),
+            "command too long (200 > 128 bytes)"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! 接続中のセントラルの一覧
+
+/// 接続中のセントラル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    pub conn_handle: u16,
+    /// 相手のアドレス（ボンド済みなら ID アドレス）
+    pub address: String,
+    /// ATT MTU
+    pub mtu: u16,
+    /// 最後に測定した RSSI（dBm、未測定なら None）
+    pub rssi: Option<i8>,
+    /// ボンド済みか（ペアリング完了時に更新）
+    pub bonded: bool,
+}
+
+/// 接続の一覧（上限は `ble.json` の `connections.max`）
+#[derive(Debug)]
+pub struct ConnectionTable {
+    connections: Vec<Connection>,
+    max: usize,
+}
+
+impl ConnectionTable {
+    pub fn new(max: usize) -> Self {
+        Self {
+            connections: Vec::with_capacity(max),
+            max,
+        }
+    }
+
+    /// 接続を追加する（上限を超える場合は追加せず false）
+    pub fn insert(&mut self, connection: Connection) -> bool {
+        self.remove(connection.conn_handle);
+        if self.is_full() {
+            return false;
+        }
+        self.connections.push(connection);
+        true
+    }
+
+    pub fn remove(&mut self, conn_handle: u16) -> Option<Connection> {
+        let index = self
+            .connections
+            .iter()
+            .position(|c| c.conn_handle == conn_handle)?;
+        Some(self.connections.remove(index))
+    }
+
+    pub fn get_mut(&mut self, conn_handle: u16) -> Option<&mut Connection> {
+        self.connections
+            .iter_mut()
+            .find(|c| c.conn_handle == conn_handle)
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.connections.len() >= self.max
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Connection> {
+        self.connections.iter_mut()
+    }
+
+    pub fn to_vec(&self) -> Vec<Connection> {
+        self.connections.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(conn_handle: u16) -> Connection {
+        Connection {
+            conn_handle,
+            address: format!("aa:bb:cc:dd:ee:{conn_handle:02x}"),
+            mtu: 23,
+            rssi: None,
+            bonded: false,
+        }
+    }
+
+    #[test]
+    fn insert_up_to_max() {
+        let mut table = ConnectionTable::new(2);
+        assert!(table.is_empty());
+        assert!(table.insert(connection(1)));
+        assert!(!table.is_full());
+        assert!(table.insert(connection(2)));
+        assert!(table.is_full());
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn insert_at_capacity_is_rejected() {
+        let mut table = ConnectionTable::new(2);
+        table.insert(connection(1));
+        table.insert(connection(2));
+        assert!(!table.insert(connection(3)));
+        assert_eq!(table.len(), 2);
+        assert!(table.get_mut(3).is_none());
+
+        // 空きができれば追加できる
+        assert_eq!(table.remove(1).map(|c| c.conn_handle), Some(1));
+        assert!(table.insert(connection(3)));
+        let handles: Vec<u16> = table.to_vec().iter().map(|c| c.conn_handle).collect();
+        assert_eq!(handles, vec![2, 3]);
+    }
+
+    #[test]
+    fn reinsert_replaces_existing_entry_even_when_full() {
+        let mut table = ConnectionTable::new(1);
+        table.insert(connection(1));
+        let updated = Connection {
+            mtu: 247,
+            bonded: true,
+            ..connection(1)
+        };
+        assert!(table.insert(updated.clone()));
+        assert_eq!(table.to_vec(), vec![updated]);
+    }
+
+    #[test]
+    fn remove_and_update() {
+        let mut table = ConnectionTable::new(3);
+        table.insert(connection(1));
+        table.insert(connection(2));
+        assert_eq!(table.remove(9), None);
+        if let Some(c) = table.get_mut(2) {
+            c.rssi = Some(-60);
+        }
+        for c in table.iter_mut() {
+            c.bonded = true;
+        }
+        assert_eq!(table.remove(2).and_then(|c| c.rssi), Some(-60));
+        assert!(table.to_vec().iter().all(|c| c.bonded));
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.max(), 3);
+    }
+}
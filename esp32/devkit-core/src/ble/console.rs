@@ -0,0 +1,109 @@
+//! Nordic UART Service（NUS）の行単位コンソール
+//!
+//! 端末アプリは1行を複数の書き込みに分けたり、複数行をまとめて送ったりするため、
+//! 受信したバイト列を改行（`\n`、`\r` は無視）で区切ってコマンド行にする。
+
+use crate::ble::command_parser::MAX_COMMAND_LEN;
+use crate::ble::wire::DEFAULT_ATT_MTU;
+
+/// 受信途中の行を保持するバッファ
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    line: Vec<u8>,
+    /// 上限を超えた行を読み捨て中なら、その行の長さ
+    overflow: Option<usize>,
+}
+
+/// 区切りまで受信した1行
+#[derive(Debug, PartialEq, Eq)]
+pub enum Line {
+    Command(Vec<u8>),
+    /// `MAX_COMMAND_LEN` を超えたため破棄した行（長さ）
+    TooLong(usize),
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 受信データを取り込み、完成した行を返す（空行は返さない）
+    pub fn push(&mut self, data: &[u8]) -> Vec<Line> {
+        let mut lines = Vec::new();
+        for &byte in data {
+            match byte {
+                b'\n' => match self.overflow.take() {
+                    Some(len) => lines.push(Line::TooLong(len)),
+                    None if !self.line.is_empty() => {
+                        lines.push(Line::Command(std::mem::take(&mut self.line)))
+                    }
+                    None => {}
+                },
+                b'\r' => {}
+                _ if self.overflow.is_some() => {
+                    self.overflow = self.overflow.map(|len| len + 1);
+                }
+                _ if self.line.len() >= MAX_COMMAND_LEN => {
+                    self.overflow = Some(self.line.len() + 1);
+                    self.line.clear();
+                }
+                _ => self.line.push(byte),
+            }
+        }
+        lines
+    }
+}
+
+/// 応答を1行（CRLF 終端）にして、Notify で送れる長さ（ATT MTU - 3）ごとに分ける
+pub fn to_chunks(message: &str, mtu: u16) -> Vec<Vec<u8>> {
+    let chunk_len = (mtu.max(DEFAULT_ATT_MTU) - 3) as usize;
+    let mut text = message.as_bytes().to_vec();
+    text.extend_from_slice(b"\r\n");
+    text.chunks(chunk_len).map(<[u8]>::to_vec).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(text: &str) -> Line {
+        Line::Command(text.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn splits_lines_across_writes() {
+        let mut buffer = LineBuffer::new();
+        assert_eq!(buffer.push(b"sta"), vec![]);
+        assert_eq!(buffer.push(b"te\r\nreb"), vec![command("state")]);
+        assert_eq!(
+            buffer.push(b"oot\n\n\r\nhelp\n"),
+            vec![command("reboot"), command("help")]
+        );
+    }
+
+    #[test]
+    fn drops_overlong_lines() {
+        let mut buffer = LineBuffer::new();
+        let long = vec![b'x'; MAX_COMMAND_LEN + 10];
+        assert_eq!(buffer.push(&long), vec![]);
+        assert_eq!(
+            buffer.push(b"\nstate\n"),
+            vec![Line::TooLong(MAX_COMMAND_LEN + 10), command("state")]
+        );
+
+        let max = vec![b'x'; MAX_COMMAND_LEN];
+        assert_eq!(buffer.push(&max), vec![]);
+        assert_eq!(buffer.push(b"\n"), vec![Line::Command(max)]);
+    }
+
+    #[test]
+    fn chunks_responses_by_mtu() {
+        assert_eq!(to_chunks("ok", 23), vec![b"ok\r\n".to_vec()]);
+        let message = "x".repeat(40);
+        let chunks = to_chunks(&message, 23);
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [20, 20, 2]);
+        assert_eq!(chunks.concat(), format!("{message}\r\n").into_bytes());
+        // MTU 交換前の値より小さい MTU は既定値として扱う
+        assert_eq!(to_chunks(&message, 0), chunks);
+    }
+}
@@ -0,0 +1,259 @@
+//! GATT クライアント（セントラル）の要求と結果
+//!
+//! リモートのペリフェラル（センサーなど）はアドレスで指定し、サービス・キャラクタリスティックは
+//! 16ビット（`2a19`）または128ビット（`xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`）の UUID で指定する。
+
+/// 同時に接続するリモートペリフェラルの上限（NimBLE の接続数はペリフェラル側と共有）
+pub const MAX_REMOTE_PEERS: usize = 2;
+
+/// リモートのキャラクタリスティック
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteCharacteristic {
+    pub service: String,
+    pub characteristic: String,
+}
+
+/// GATT クライアントとしての操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GattRequest {
+    /// 接続してサービスを探索する（`random` ならランダムアドレス）
+    Connect {
+        address: String,
+        random: bool,
+    },
+    Disconnect {
+        address: String,
+    },
+    Read {
+        address: String,
+        target: RemoteCharacteristic,
+    },
+    Write {
+        address: String,
+        target: RemoteCharacteristic,
+        value: Vec<u8>,
+        /// Write Request（応答あり）で書き込むか
+        with_response: bool,
+    },
+    /// 通知を購読し、以降の値を `GattEvent::Notification` で返す
+    Subscribe {
+        address: String,
+        target: RemoteCharacteristic,
+    },
+}
+
+impl GattRequest {
+    pub fn address(&self) -> &str {
+        match self {
+            GattRequest::Connect { address, .. }
+            | GattRequest::Disconnect { address }
+            | GattRequest::Read { address, .. }
+            | GattRequest::Write { address, .. }
+            | GattRequest::Subscribe { address, .. } => address,
+        }
+    }
+}
+
+/// GATT クライアントの操作結果とリモートからの通知
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GattEvent {
+    /// 接続完了（見つかったサービスの UUID）
+    Connected {
+        address: String,
+        services: Vec<String>,
+    },
+    /// 切断（要求による切断、相手からの切断とも）
+    Disconnected { address: String },
+    /// 読み出した値
+    Value {
+        address: String,
+        characteristic: String,
+        value: Vec<u8>,
+    },
+    Written {
+        address: String,
+        characteristic: String,
+    },
+    Subscribed {
+        address: String,
+        characteristic: String,
+    },
+    /// 購読中のキャラクタリスティックからの通知
+    Notification {
+        address: String,
+        characteristic: String,
+        value: Vec<u8>,
+    },
+    /// 要求の失敗
+    Failed { address: String, reason: String },
+}
+
+impl GattEvent {
+    pub fn address(&self) -> &str {
+        match self {
+            GattEvent::Connected { address, .. }
+            | GattEvent::Disconnected { address }
+            | GattEvent::Value { address, .. }
+            | GattEvent::Written { address, .. }
+            | GattEvent::Subscribed { address, .. }
+            | GattEvent::Notification { address, .. }
+            | GattEvent::Failed { address, .. } => address,
+        }
+    }
+
+    /// リモートコマンドへの応答（`ok ...` / `err ...`）
+    pub fn to_response(&self) -> String {
+        match self {
+            GattEvent::Connected { address, services } => {
+                format!(
+                    "ok gatt connected {address} services={}",
+                    services.join(",")
+                )
+            }
+            GattEvent::Disconnected { address } => format!("ok gatt disconnected {address}"),
+            GattEvent::Value {
+                characteristic,
+                value,
+                ..
+            } => format!("ok {characteristic}={}", to_hex(value)),
+            GattEvent::Written { characteristic, .. } => format!("ok written {characteristic}"),
+            GattEvent::Subscribed { characteristic, .. } => {
+                format!("ok subscribed {characteristic}")
+            }
+            GattEvent::Notification {
+                characteristic,
+                value,
+                ..
+            } => format!("ok {characteristic}={}", to_hex(value)),
+            GattEvent::Failed { reason, .. } => format!("err {reason}"),
+        }
+    }
+}
+
+/// `aa:bb:cc:dd:ee:ff` 形式の BLE アドレスか
+pub fn is_address(value: &str) -> bool {
+    let parts: Vec<&str> = value.split(':').collect();
+    parts.len() == 6
+        && parts
+            .iter()
+            .all(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// 16ビット（4桁）または128ビット（ハイフン区切り）の UUID か
+pub fn is_uuid(value: &str) -> bool {
+    let hex_len =
+        |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_hexdigit());
+    if value.len() == 4 {
+        return hex_len(value, 4);
+    }
+    let parts: Vec<&str> = value.split('-').collect();
+    parts.len() == 5
+        && parts
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(part, len)| hex_len(part, len))
+}
+
+/// 値を16進文字列にする（応答・通知用）
+pub fn to_hex(value: &[u8]) -> String {
+    value.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 16進文字列を値にする
+pub fn from_hex(value: &str) -> Option<Vec<u8>> {
+    // from_str_radix は先頭の '+' を受け付けるため、16進数字だけか先に確認する
+    if value.len() % 2 != 0 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_requires_six_hex_pairs() {
+        assert!(is_address("aa:bb:cc:dd:ee:ff"));
+        assert!(is_address("AA:BB:CC:00:11:22"));
+        assert!(!is_address("aa:bb:cc:dd:ee"));
+        assert!(!is_address("aa:bb:cc:dd:ee:ff:00"));
+        assert!(!is_address("aa:bb:cc:dd:ee:f"));
+        assert!(!is_address("aa:bb:cc:dd:ee:fff"));
+        assert!(!is_address("aa-bb-cc-dd-ee-ff"));
+        assert!(!is_address("gg:bb:cc:dd:ee:ff"));
+        assert!(!is_address(""));
+    }
+
+    #[test]
+    fn uuid16_requires_four_hex_digits() {
+        assert!(is_uuid("2a19"));
+        assert!(is_uuid("180F"));
+        assert!(!is_uuid("2a1"));
+        assert!(!is_uuid("2a190"));
+        assert!(!is_uuid("2g19"));
+        assert!(!is_uuid(""));
+    }
+
+    #[test]
+    fn uuid128_requires_hyphenated_groups() {
+        assert!(is_uuid("9b574847-f706-436c-bed7-fc01eb0965c1"));
+        assert!(is_uuid("9B574847-F706-436C-BED7-FC01EB0965C1"));
+        assert!(!is_uuid("9b574847f706436cbed7fc01eb0965c1"));
+        assert!(!is_uuid("9b574847-f706-436c-bed7-fc01eb0965c"));
+        assert!(!is_uuid("9b57484-7f706-436c-bed7-fc01eb0965c1"));
+        assert!(!is_uuid("9b574847-f706-436c-bed7-fc01eb0965c1-00"));
+        assert!(!is_uuid("zb574847-f706-436c-bed7-fc01eb0965c1"));
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(from_hex(""), Some(Vec::new()));
+        assert_eq!(from_hex("00ff10"), Some(vec![0x00, 0xFF, 0x10]));
+        assert_eq!(from_hex("ABcd"), Some(vec![0xAB, 0xCD]));
+        let value = [0x01, 0x23, 0xAB, 0xFF];
+        assert_eq!(from_hex(&to_hex(&value)), Some(value.to_vec()));
+        assert_eq!(to_hex(&value), "0123abff");
+    }
+
+    #[test]
+    fn invalid_hex_is_rejected() {
+        assert_eq!(from_hex("abc"), None);
+        assert_eq!(from_hex("zz"), None);
+        assert_eq!(from_hex("+1"), None);
+        assert_eq!(from_hex("ａｂ"), None);
+    }
+
+    #[test]
+    fn responses() {
+        let address = "aa:bb:cc:dd:ee:ff".to_string();
+        assert_eq!(
+            GattEvent::Connected {
+                address: address.clone(),
+                services: vec!["180f".to_string(), "180a".to_string()],
+            }
+            .to_response(),
+            "ok gatt connected aa:bb:cc:dd:ee:ff services=180f,180a"
+        );
+        assert_eq!(
+            GattEvent::Value {
+                address: address.clone(),
+                characteristic: "2a19".to_string(),
+                value: vec![0x64],
+            }
+            .to_response(),
+            "ok 2a19=64"
+        );
+        assert_eq!(
+            GattEvent::Failed {
+                address,
+                reason: "not connected".to_string(),
+            }
+            .to_response(),
+            "err not connected"
+        );
+    }
+}
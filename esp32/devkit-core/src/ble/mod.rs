@@ -0,0 +1,9 @@
+pub mod adv_status;
+pub mod beacon;
+pub mod command_parser;
+pub mod connections;
+pub mod console;
+pub mod gatt_client;
+pub mod reconnect;
+pub mod scan;
+pub mod wire;
@@ -0,0 +1,277 @@
+//! 切断後の再アドバタイズ
+//!
+//! BleTask が切断・窓の終了を伝え、返された窓でアドバタイズを開始する。
+
+use std::time::{Duration, Instant};
+
+/// アドバタイズで接続を受け付ける相手
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdvertiseMode {
+    /// ボンド済みの相手のみ（フィルタ許可リスト）
+    BondedOnly,
+    /// 誰でも接続・ペアリングできる（ペアリング受付期間）
+    OpenPairing,
+}
+
+/// 切断後の再アドバタイズの方針
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectPolicy {
+    /// 再アドバタイズしない
+    Never,
+    /// 直前のモードで `window_ms` だけ再アドバタイズ
+    Window,
+    /// ボンド済みの相手だけに `window_ms` だけ再アドバタイズ
+    BondedOnly,
+    /// 直前のモードで `window_ms` の再アドバタイズを、間隔を倍にしながら繰り返す
+    Backoff,
+}
+
+/// 再接続の設定（`ble.json` の `reconnect` から生成）
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectConfig {
+    pub policy: ReconnectPolicy,
+    /// 1回の再アドバタイズの長さ
+    pub window_ms: u32,
+    /// Backoff: 最初の待ち時間
+    pub backoff_initial_ms: u32,
+    /// Backoff: 待ち時間の上限
+    pub backoff_max_ms: u32,
+    /// Backoff: 窓の回数の上限（0 なら無制限）
+    pub max_attempts: u32,
+}
+
+/// 再アドバタイズの窓
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub mode: AdvertiseMode,
+    pub duration_ms: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Idle,
+    /// 再アドバタイズ中
+    Advertising,
+    /// 次の窓を待っている（Backoff）
+    Waiting {
+        until: Instant,
+    },
+}
+
+#[derive(Debug)]
+pub struct Reconnect {
+    config: ReconnectConfig,
+    mode: AdvertiseMode,
+    phase: Phase,
+    /// 今回の切断から開始した窓の数
+    attempts: u32,
+}
+
+impl Reconnect {
+    pub fn new(config: ReconnectConfig) -> Self {
+        Self {
+            config,
+            mode: AdvertiseMode::OpenPairing,
+            phase: Phase::Idle,
+            attempts: 0,
+        }
+    }
+
+    /// 切断時に呼ぶ（`last_mode` は直前のアドバタイズのモード）。再アドバタイズするなら最初の窓を返す
+    pub fn on_disconnected(&mut self, last_mode: AdvertiseMode) -> Option<Window> {
+        self.cancel();
+        self.mode = match self.config.policy {
+            ReconnectPolicy::Never => return None,
+            ReconnectPolicy::BondedOnly => AdvertiseMode::BondedOnly,
+            ReconnectPolicy::Window | ReconnectPolicy::Backoff => last_mode,
+        };
+        Some(self.start_window())
+    }
+
+    /// 再アドバタイズの窓が終わった時に呼ぶ。次の窓を待つなら待ち時間を返す
+    pub fn on_window_expired(&mut self, now: Instant) -> Option<Duration> {
+        if self.phase != Phase::Advertising {
+            return None;
+        }
+        let limit_reached =
+            self.config.max_attempts != 0 && self.attempts >= self.config.max_attempts;
+        if self.config.policy != ReconnectPolicy::Backoff || limit_reached {
+            self.cancel();
+            return None;
+        }
+        let delay = Duration::from_millis(self.backoff_delay_ms() as u64);
+        self.phase = Phase::Waiting { until: now + delay };
+        Some(delay)
+    }
+
+    /// 待ち時間が過ぎていれば次の窓を返す
+    pub fn poll(&mut self, now: Instant) -> Option<Window> {
+        match self.phase {
+            Phase::Waiting { until } if now >= until => Some(self.start_window()),
+            _ => None,
+        }
+    }
+
+    /// 再接続・手動の操作で中断する
+    pub fn cancel(&mut self) {
+        self.phase = Phase::Idle;
+        self.attempts = 0;
+    }
+
+    /// 再アドバタイズ中または次の窓を待っているか
+    pub fn is_active(&self) -> bool {
+        self.phase != Phase::Idle
+    }
+
+    fn start_window(&mut self) -> Window {
+        self.attempts += 1;
+        self.phase = Phase::Advertising;
+        Window {
+            mode: self.mode,
+            duration_ms: self.config.window_ms,
+        }
+    }
+
+    /// n 回目の窓の後の待ち時間（初期値から倍々、上限あり）
+    fn backoff_delay_ms(&self) -> u32 {
+        let shift = self.attempts.saturating_sub(1).min(31);
+        self.config
+            .backoff_initial_ms
+            .saturating_mul(1 << shift)
+            .min(self.config.backoff_max_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(policy: ReconnectPolicy, max_attempts: u32) -> ReconnectConfig {
+        ReconnectConfig {
+            policy,
+            window_ms: 30_000,
+            backoff_initial_ms: 5_000,
+            backoff_max_ms: 40_000,
+            max_attempts,
+        }
+    }
+
+    /// 窓の終了と待ち時間の経過を繰り返し、各窓の後の待ち時間を返す
+    fn delays(reconnect: &mut Reconnect, windows: usize) -> Vec<Option<u64>> {
+        let mut now = Instant::now();
+        (0..windows)
+            .map(|_| {
+                let delay = reconnect.on_window_expired(now)?;
+                now += delay;
+                assert!(reconnect.poll(now).is_some());
+                Some(delay.as_millis() as u64)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn never_does_not_readvertise() {
+        let mut reconnect = Reconnect::new(config(ReconnectPolicy::Never, 0));
+        assert_eq!(reconnect.on_disconnected(AdvertiseMode::OpenPairing), None);
+        assert!(!reconnect.is_active());
+    }
+
+    #[test]
+    fn window_uses_last_mode_once() {
+        let mut reconnect = Reconnect::new(config(ReconnectPolicy::Window, 0));
+        assert_eq!(
+            reconnect.on_disconnected(AdvertiseMode::OpenPairing),
+            Some(Window {
+                mode: AdvertiseMode::OpenPairing,
+                duration_ms: 30_000
+            })
+        );
+        assert!(reconnect.is_active());
+        assert_eq!(reconnect.on_window_expired(Instant::now()), None);
+        assert!(!reconnect.is_active());
+    }
+
+    #[test]
+    fn bonded_only_overrides_last_mode() {
+        let mut reconnect = Reconnect::new(config(ReconnectPolicy::BondedOnly, 0));
+        let window = reconnect.on_disconnected(AdvertiseMode::OpenPairing);
+        assert_eq!(window.map(|w| w.mode), Some(AdvertiseMode::BondedOnly));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let mut reconnect = Reconnect::new(config(ReconnectPolicy::Backoff, 0));
+        reconnect.on_disconnected(AdvertiseMode::BondedOnly);
+        assert_eq!(
+            delays(&mut reconnect, 6),
+            vec![
+                Some(5_000),
+                Some(10_000),
+                Some(20_000),
+                Some(40_000),
+                Some(40_000),
+                Some(40_000)
+            ]
+        );
+        assert!(reconnect.is_active());
+    }
+
+    #[test]
+    fn backoff_stops_after_max_attempts() {
+        let mut reconnect = Reconnect::new(config(ReconnectPolicy::Backoff, 3));
+        reconnect.on_disconnected(AdvertiseMode::OpenPairing);
+        // 3 回目の窓が終わったら諦める
+        assert_eq!(
+            delays(&mut reconnect, 3),
+            vec![Some(5_000), Some(10_000), None]
+        );
+        assert!(!reconnect.is_active());
+    }
+
+    #[test]
+    fn backoff_delay_saturates() {
+        let mut reconnect = Reconnect::new(ReconnectConfig {
+            backoff_initial_ms: u32::MAX / 2,
+            backoff_max_ms: u32::MAX,
+            ..config(ReconnectPolicy::Backoff, 0)
+        });
+        reconnect.on_disconnected(AdvertiseMode::OpenPairing);
+        assert_eq!(
+            delays(&mut reconnect, 3),
+            vec![
+                Some((u32::MAX / 2) as u64),
+                Some((u32::MAX - 1) as u64),
+                Some(u32::MAX as u64)
+            ]
+        );
+    }
+
+    #[test]
+    fn poll_waits_for_the_delay() {
+        let mut reconnect = Reconnect::new(config(ReconnectPolicy::Backoff, 0));
+        reconnect.on_disconnected(AdvertiseMode::OpenPairing);
+        let now = Instant::now();
+        let delay = reconnect.on_window_expired(now).unwrap();
+        assert_eq!(reconnect.poll(now), None);
+        assert_eq!(reconnect.poll(now + delay - Duration::from_millis(1)), None);
+        assert_eq!(
+            reconnect.poll(now + delay).map(|w| w.mode),
+            Some(AdvertiseMode::OpenPairing)
+        );
+        // 窓の最中は次の窓を返さない
+        assert_eq!(reconnect.poll(now + delay * 2), None);
+    }
+
+    #[test]
+    fn cancel_resets_attempts() {
+        let mut reconnect = Reconnect::new(config(ReconnectPolicy::Backoff, 0));
+        reconnect.on_disconnected(AdvertiseMode::OpenPairing);
+        delays(&mut reconnect, 2);
+        reconnect.cancel();
+        assert!(!reconnect.is_active());
+        assert_eq!(reconnect.on_window_expired(Instant::now()), None);
+
+        reconnect.on_disconnected(AdvertiseMode::OpenPairing);
+        assert_eq!(delays(&mut reconnect, 1), vec![Some(5_000)]);
+    }
+}
@@ -0,0 +1,291 @@
+//! 周辺デバイスのスキャン結果の重複排除と絞り込み
+//!
+//! 同じデバイスからはアドバタイズとスキャンレスポンスが繰り返し届くため、
+//! アドレスごとに1件へまとめてから条件で絞り込む（名前はスキャンレスポンスにしか無いことが多い）。
+
+/// 既定のスキャン時間
+pub const DEFAULT_SCAN_DURATION_MS: u32 = 5_000;
+/// スキャン中は GATT クライアントの接続などができないため、長さを制限する
+pub const MAX_SCAN_DURATION_MS: u32 = 30_000;
+/// 結果として返す最大件数（RSSI の強い順）
+pub const MAX_SCAN_RESULTS: usize = 16;
+/// スキャン中に保持する最大デバイス数（超えた分の新しいアドレスは捨てる）
+const MAX_TRACKED_DEVICES: usize = 64;
+
+/// スキャン結果の絞り込み条件（すべて満たすものを返す）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanFilter {
+    /// デバイス名の先頭
+    pub name_prefix: Option<String>,
+    /// アドバタイズに含まれるサービス UUID（小文字）
+    pub service_uuid: Option<String>,
+    /// RSSI の下限（dBm）
+    pub min_rssi: Option<i32>,
+}
+
+/// 見つかったデバイス
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedDevice {
+    pub address: String,
+    /// 最後に受信した時の RSSI（dBm）
+    pub rssi: i32,
+    pub name: Option<String>,
+    /// サービス UUID（小文字）
+    pub service_uuids: Vec<String>,
+    /// メーカー固有データ（先頭2バイトは Company ID）
+    pub manufacturer_data: Vec<u8>,
+}
+
+impl ScanFilter {
+    pub fn matches(&self, device: &ScannedDevice) -> bool {
+        if let Some(prefix) = &self.name_prefix {
+            if !device
+                .name
+                .as_deref()
+                .is_some_and(|name| name.starts_with(prefix.as_str()))
+            {
+                return false;
+            }
+        }
+        if let Some(uuid) = &self.service_uuid {
+            if !device.service_uuids.contains(uuid) {
+                return false;
+            }
+        }
+        match self.min_rssi {
+            Some(min) => device.rssi >= min,
+            None => true,
+        }
+    }
+}
+
+/// 応答に載せるデバイス名（空白・引用符・制御文字を含んでも1項目として読めるよう、引用符で囲んでエスケープする）
+pub fn quote_name(name: &str) -> String {
+    let mut quoted = String::with_capacity(name.len() + 2);
+    quoted.push('"');
+    for c in name.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// スキャン中に受信したアドバタイズを集める
+#[derive(Debug)]
+pub struct ScanCollector {
+    filter: ScanFilter,
+    devices: Vec<ScannedDevice>,
+}
+
+impl ScanCollector {
+    pub fn new(filter: ScanFilter) -> Self {
+        Self {
+            filter,
+            devices: Vec::new(),
+        }
+    }
+
+    /// 受信したアドバタイズを取り込む（同じアドレスなら内容をまとめる）
+    pub fn push(&mut self, device: ScannedDevice) {
+        let Some(known) = self
+            .devices
+            .iter_mut()
+            .find(|known| known.address == device.address)
+        else {
+            if self.devices.len() < MAX_TRACKED_DEVICES {
+                self.devices.push(device);
+            }
+            return;
+        };
+
+        known.rssi = device.rssi;
+        if device.name.is_some() {
+            known.name = device.name;
+        }
+        for uuid in device.service_uuids {
+            if !known.service_uuids.contains(&uuid) {
+                known.service_uuids.push(uuid);
+            }
+        }
+        if !device.manufacturer_data.is_empty() {
+            known.manufacturer_data = device.manufacturer_data;
+        }
+    }
+
+    /// 条件に合うデバイスを RSSI の強い順に返す
+    pub fn finish(self) -> Vec<ScannedDevice> {
+        let filter = self.filter;
+        let mut devices: Vec<ScannedDevice> = self
+            .devices
+            .into_iter()
+            .filter(|device| filter.matches(device))
+            .collect();
+        devices.sort_by_key(|device| core::cmp::Reverse(device.rssi));
+        devices.truncate(MAX_SCAN_RESULTS);
+        devices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(address: &str, rssi: i32) -> ScannedDevice {
+        ScannedDevice {
+            address: address.to_string(),
+            rssi,
+            name: None,
+            service_uuids: Vec::new(),
+            manufacturer_data: Vec::new(),
+        }
+    }
+
+    fn named(address: &str, rssi: i32, name: &str) -> ScannedDevice {
+        ScannedDevice {
+            name: Some(name.to_string()),
+            ..device(address, rssi)
+        }
+    }
+
+    #[test]
+    fn same_address_is_merged() {
+        let mut collector = ScanCollector::new(ScanFilter::default());
+        collector.push(ScannedDevice {
+            service_uuids: vec!["180f".to_string()],
+            manufacturer_data: vec![0xE5, 0x02, 1],
+            ..device("aa:bb:cc:dd:ee:01", -70)
+        });
+        // スキャンレスポンス（名前のみ）
+        collector.push(named("aa:bb:cc:dd:ee:01", -60, "sensor"));
+        collector.push(ScannedDevice {
+            service_uuids: vec!["180f".to_string(), "180a".to_string()],
+            ..device("aa:bb:cc:dd:ee:01", -65)
+        });
+
+        let devices = collector.finish();
+        assert_eq!(devices.len(), 1);
+        let merged = &devices[0];
+        assert_eq!(merged.rssi, -65);
+        assert_eq!(merged.name.as_deref(), Some("sensor"));
+        assert_eq!(merged.service_uuids, ["180f", "180a"]);
+        assert_eq!(merged.manufacturer_data, [0xE5, 0x02, 1]);
+    }
+
+    #[test]
+    fn later_packet_without_name_keeps_the_name() {
+        let mut collector = ScanCollector::new(ScanFilter::default());
+        collector.push(named("aa:bb:cc:dd:ee:01", -60, "sensor"));
+        collector.push(device("aa:bb:cc:dd:ee:01", -61));
+        assert_eq!(collector.finish()[0].name.as_deref(), Some("sensor"));
+    }
+
+    #[test]
+    fn results_are_sorted_by_rssi_and_truncated() {
+        let mut collector = ScanCollector::new(ScanFilter::default());
+        for i in 0..(MAX_SCAN_RESULTS + 4) {
+            collector.push(device(&format!("aa:bb:cc:dd:ee:{i:02x}"), -90 + i as i32));
+        }
+        let devices = collector.finish();
+        assert_eq!(devices.len(), MAX_SCAN_RESULTS);
+        assert!(devices.windows(2).all(|w| w[0].rssi >= w[1].rssi));
+        assert_eq!(devices[0].rssi, -90 + (MAX_SCAN_RESULTS + 3) as i32);
+    }
+
+    #[test]
+    fn tracked_devices_are_bounded() {
+        let mut collector = ScanCollector::new(ScanFilter {
+            name_prefix: Some("late".to_string()),
+            ..ScanFilter::default()
+        });
+        for i in 0..MAX_TRACKED_DEVICES {
+            collector.push(device(
+                &format!("aa:bb:cc:dd:{:02x}:{:02x}", i / 256, i % 256),
+                -50,
+            ));
+        }
+        collector.push(named("ff:ff:ff:ff:ff:ff", -40, "late"));
+        assert!(collector.finish().is_empty());
+    }
+
+    #[test]
+    fn name_prefix_filter() {
+        let filter = ScanFilter {
+            name_prefix: Some("esp".to_string()),
+            ..ScanFilter::default()
+        };
+        assert!(filter.matches(&named("a", -50, "esp32-devkit")));
+        assert!(!filter.matches(&named("a", -50, "my-esp")));
+        // 名前が無いデバイスは除外
+        assert!(!filter.matches(&device("a", -50)));
+    }
+
+    #[test]
+    fn service_uuid_filter() {
+        let filter = ScanFilter {
+            service_uuid: Some("180f".to_string()),
+            ..ScanFilter::default()
+        };
+        let battery = ScannedDevice {
+            service_uuids: vec!["180a".to_string(), "180f".to_string()],
+            ..device("a", -50)
+        };
+        assert!(filter.matches(&battery));
+        assert!(!filter.matches(&device("a", -50)));
+    }
+
+    #[test]
+    fn min_rssi_filter_is_inclusive() {
+        let filter = ScanFilter {
+            min_rssi: Some(-70),
+            ..ScanFilter::default()
+        };
+        assert!(filter.matches(&device("a", -70)));
+        assert!(filter.matches(&device("a", -40)));
+        assert!(!filter.matches(&device("a", -71)));
+    }
+
+    #[test]
+    fn filters_are_combined() {
+        let filter = ScanFilter {
+            name_prefix: Some("esp".to_string()),
+            service_uuid: None,
+            min_rssi: Some(-60),
+        };
+        let mut collector = ScanCollector::new(filter);
+        collector.push(named("aa:bb:cc:dd:ee:01", -50, "esp-near"));
+        collector.push(named("aa:bb:cc:dd:ee:02", -80, "esp-far"));
+        collector.push(named("aa:bb:cc:dd:ee:03", -40, "other"));
+        let devices = collector.finish();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].address, "aa:bb:cc:dd:ee:01");
+    }
+
+    #[test]
+    fn filter_is_applied_to_the_merged_device() {
+        // 名前はスキャンレスポンスで後から届く
+        let mut collector = ScanCollector::new(ScanFilter {
+            name_prefix: Some("esp".to_string()),
+            ..ScanFilter::default()
+        });
+        collector.push(device("aa:bb:cc:dd:ee:01", -50));
+        collector.push(named("aa:bb:cc:dd:ee:01", -50, "esp32"));
+        assert_eq!(collector.finish().len(), 1);
+    }
+
+    #[test]
+    fn names_are_quoted_and_escaped() {
+        assert_eq!(quote_name("sensor"), "\"sensor\"");
+        assert_eq!(quote_name("my sensor"), "\"my sensor\"");
+        assert_eq!(quote_name("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(quote_name("a\nb\r"), "\"a\\x0ab\\x0d\"");
+        assert_eq!(quote_name(""), "\"\"");
+        assert_eq!(quote_name("センサー"), "\"センサー\"");
+    }
+}
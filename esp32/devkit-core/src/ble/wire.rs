@@ -0,0 +1,241 @@
+//! バイナリプロトコル（`devkit-protocol`）とテキストコマンドの相互変換
+//!
+//! コマンドキャラクタリスティックへの書き込みが `MAGIC` で始まる場合はバイナリフレームとして扱い、
+//! テキストコマンドと同じ `RemoteCommand` に変換する。応答も同じ形式で返す。
+
+use devkit_protocol::{Command, ErrorCode, Fragmenter, LedMode, Message};
+
+use crate::ble::command_parser::{RemoteCommand, RemoteLedMode};
+use crate::led::color::Rgb;
+
+/// 接続直後（MTU交換前）の ATT MTU
+pub const DEFAULT_ATT_MTU: u16 = 23;
+
+/// 再構成できるメッセージの最大長（バイト）
+pub const MAX_MESSAGE_LEN: usize = 512;
+
+/// バイナリフレームか（テキストコマンドは ASCII で始まるため `MAGIC` と衝突しない）
+pub fn is_frame(payload: &[u8]) -> bool {
+    payload.first() == Some(&devkit_protocol::MAGIC)
+}
+
+/// バイナリのコマンドをテキストコマンドと同じ形に変換する
+pub fn to_remote_command(command: &Command) -> Result<RemoteCommand, String> {
+    Ok(match command {
+        Command::SetLed { led, mode, color } => {
+            let mode = match mode {
+                LedMode::On => RemoteLedMode::On,
+                LedMode::Off => RemoteLedMode::Off,
+                LedMode::Level(level) => RemoteLedMode::Level(*level),
+                LedMode::Blink {
+                    on_ms,
+                    off_ms,
+                    count,
+                } => RemoteLedMode::Blink {
+                    on_ms: *on_ms,
+                    off_ms: *off_ms,
+                    count: (*count != 0).then_some(*count),
+                },
+                LedMode::Fade { level, duration_ms } => RemoteLedMode::Fade {
+                    level: *level,
+                    duration_ms: *duration_ms,
+                },
+                LedMode::Breathe { period_ms } => RemoteLedMode::Breathe {
+                    period_ms: *period_ms,
+                },
+                LedMode::Preset(name) => RemoteLedMode::Preset(name.to_string()),
+                LedMode::Release => RemoteLedMode::Release,
+                // ステップ列を運ばないため、コマンドではプリセット名を指定する
+                LedMode::Pattern => return Err("LED mode Pattern is event-only; use Preset".into()),
+            };
+            RemoteCommand::SetLed {
+                led: led.to_string(),
+                mode,
+                color: color.map(|[r, g, b]| Rgb::new(r, g, b)),
+            }
+        }
+        Command::StopAdvertise => RemoteCommand::StopAdvertise,
+        Command::QueryState => RemoteCommand::QueryState,
+        Command::Reboot => RemoteCommand::Reboot,
+    })
+}
+
+/// テキストの応答（`ok ...` / `err ...`）をバイナリの応答メッセージに変換する
+pub fn to_response(text: &str) -> Message<'_> {
+    match text.strip_prefix("err") {
+        Some(message) => Message::Error {
+            code: ErrorCode::InvalidArgument,
+            message: truncate(message.trim_start()),
+        },
+        None => Message::Ack {
+            message: truncate(text.strip_prefix("ok").unwrap_or(text).trim_start()),
+        },
+    }
+}
+
+/// メッセージを ATT MTU に収まるフレーム列にする
+pub fn encode_frames(
+    message: &Message,
+    msg_id: u8,
+    mtu: u16,
+) -> Result<Vec<Vec<u8>>, devkit_protocol::Error> {
+    let mut payload = [0u8; MAX_MESSAGE_LEN];
+    let len = message.encode(&mut payload)?;
+
+    // Notify で送れるのは ATT MTU - 3 バイト
+    let max_frame_len = (mtu.max(DEFAULT_ATT_MTU) - 3) as usize;
+    let mut fragmenter = Fragmenter::new(&payload[..len], msg_id, max_frame_len)?;
+    let mut frames = Vec::with_capacity(fragmenter.fragment_count() as usize);
+    let mut frame = vec![0u8; max_frame_len];
+    while let Some(frame_len) = fragmenter.next_frame(&mut frame) {
+        frames.push(frame[..frame_len?].to_vec());
+    }
+    Ok(frames)
+}
+
+/// 文字列の長さ上限（255バイト）に収める
+fn truncate(text: &str) -> &str {
+    let mut end = text.len().min(u8::MAX as usize);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use devkit_protocol::Reassembler;
+
+    #[test]
+    fn detects_binary_frames() {
+        assert!(is_frame(&[devkit_protocol::MAGIC, 1]));
+        assert!(!is_frame(b"state"));
+        assert!(!is_frame(&[]));
+    }
+
+    #[test]
+    fn converts_binary_commands() {
+        let set_led = |mode| Command::SetLed {
+            led: "status",
+            mode,
+            color: Some([1, 2, 3]),
+        };
+        let remote = |mode| RemoteCommand::SetLed {
+            led: "status".to_string(),
+            mode,
+            color: Some(Rgb::new(1, 2, 3)),
+        };
+        let cases = [
+            (set_led(LedMode::On), remote(RemoteLedMode::On)),
+            (set_led(LedMode::Off), remote(RemoteLedMode::Off)),
+            (set_led(LedMode::Level(9)), remote(RemoteLedMode::Level(9))),
+            (
+                set_led(LedMode::Blink {
+                    on_ms: 1,
+                    off_ms: 2,
+                    count: 0,
+                }),
+                remote(RemoteLedMode::Blink {
+                    on_ms: 1,
+                    off_ms: 2,
+                    count: None,
+                }),
+            ),
+            (
+                set_led(LedMode::Blink {
+                    on_ms: 100,
+                    off_ms: 200,
+                    count: 3,
+                }),
+                remote(RemoteLedMode::Blink {
+                    on_ms: 100,
+                    off_ms: 200,
+                    count: Some(3),
+                }),
+            ),
+            (
+                set_led(LedMode::Fade {
+                    level: 7,
+                    duration_ms: 800,
+                }),
+                remote(RemoteLedMode::Fade {
+                    level: 7,
+                    duration_ms: 800,
+                }),
+            ),
+            (
+                set_led(LedMode::Breathe { period_ms: 5 }),
+                remote(RemoteLedMode::Breathe { period_ms: 5 }),
+            ),
+            (
+                set_led(LedMode::Preset("sos")),
+                remote(RemoteLedMode::Preset("sos".to_string())),
+            ),
+            (set_led(LedMode::Release), remote(RemoteLedMode::Release)),
+            (Command::StopAdvertise, RemoteCommand::StopAdvertise),
+            (Command::QueryState, RemoteCommand::QueryState),
+            (Command::Reboot, RemoteCommand::Reboot),
+        ];
+        for (command, expected) in cases {
+            assert_eq!(to_remote_command(&command), Ok(expected), "{command:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_event_only_pattern() {
+        let command = Command::SetLed {
+            led: "status",
+            mode: LedMode::Pattern,
+            color: None,
+        };
+        assert_eq!(
+            to_remote_command(&command),
+            Err("LED mode Pattern is event-only; use Preset".to_string())
+        );
+    }
+
+    #[test]
+    fn converts_text_responses() {
+        assert_eq!(to_response("ok"), Message::Ack { message: "" });
+        assert_eq!(
+            to_response("ok connected=1"),
+            Message::Ack {
+                message: "connected=1"
+            }
+        );
+        assert_eq!(
+            to_response("err unknown LED: x"),
+            Message::Error {
+                code: ErrorCode::InvalidArgument,
+                message: "unknown LED: x"
+            }
+        );
+        let long = format!("ok {}", "あ".repeat(100));
+        let Message::Ack { message } = to_response(&long) else {
+            panic!("expected Ack");
+        };
+        // 255 バイト以下の文字境界で切る
+        assert_eq!(message.len(), 85 * 3);
+    }
+
+    #[test]
+    fn frames_fit_the_mtu_and_reassemble() {
+        let text = format!("ok {}", "x".repeat(200));
+        let message = to_response(&text);
+        for mtu in [0, 23, 185, 517] {
+            let frames = encode_frames(&message, 3, mtu).unwrap();
+            let max_frame_len = (mtu.max(DEFAULT_ATT_MTU) - 3) as usize;
+            assert!(frames.iter().all(|f| f.len() <= max_frame_len), "{mtu}");
+
+            let mut reassembler = Reassembler::<MAX_MESSAGE_LEN>::new();
+            let mut decoded = None;
+            for frame in &frames {
+                if let Some(payload) = reassembler.push(frame).unwrap() {
+                    decoded = Some(Message::decode(payload).unwrap() == message);
+                }
+            }
+            assert_eq!(decoded, Some(true), "{mtu}");
+        }
+    }
+}
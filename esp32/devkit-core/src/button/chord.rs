@@ -0,0 +1,176 @@
+use crate::button::event::{ButtonMask, ChordGesture};
+
+/// 複数ボタン同時押しの検出器
+///
+/// 2つ以上のボタンが同時に押された時点で同時押しを開始し、全ボタンが離されるまで継続する。
+/// 途中で加わったボタンも同じ同時押しに含める。
+pub struct ChordDetector {
+    long_press_ms: u32,
+    buttons: ButtonMask,
+    started_at: u32,
+    active: bool,
+    long_fired: bool,
+}
+
+impl ChordDetector {
+    pub fn new(long_press_ms: u32) -> Self {
+        Self {
+            long_press_ms,
+            buttons: ButtonMask::default(),
+            started_at: 0,
+            active: false,
+            long_fired: false,
+        }
+    }
+
+    /// 同時押し中（各ボタン単体のジェスチャを抑止すべき状態）か
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// 次に `update` を呼ぶ必要がある時刻までの残り時間（ms）
+    pub fn next_deadline_ms(&self, now_ms: u32) -> Option<u32> {
+        if self.active && !self.long_fired {
+            Some(
+                self.long_press_ms
+                    .saturating_sub(now_ms.wrapping_sub(self.started_at)),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// 現在押されているボタン集合を与える
+    ///
+    /// 戻り値は、この呼び出しの前後いずれかで同時押しが有効だったか（単体ジェスチャ抑止用）。
+    pub fn update(
+        &mut self,
+        pressed: ButtonMask,
+        now_ms: u32,
+        emit: &mut dyn FnMut(ButtonMask, ChordGesture),
+    ) -> bool {
+        let was_active = self.active;
+
+        if !self.active {
+            if pressed.count() >= 2 {
+                self.active = true;
+                self.long_fired = false;
+                self.buttons = pressed;
+                self.started_at = now_ms;
+                emit(self.buttons, ChordGesture::Pressed);
+            }
+            return self.active;
+        }
+
+        if pressed.is_empty() {
+            self.active = false;
+            let held_ms = now_ms.wrapping_sub(self.started_at);
+            emit(self.buttons, ChordGesture::Released { held_ms });
+            return was_active;
+        }
+
+        self.buttons = ButtonMask(self.buttons.0 | pressed.0);
+        if !self.long_fired && now_ms.wrapping_sub(self.started_at) >= self.long_press_ms {
+            self.long_fired = true;
+            emit(self.buttons, ChordGesture::LongPress);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::button::event::ButtonId;
+
+    const LONG_PRESS_MS: u32 = 1000;
+
+    fn mask(ids: &[u8]) -> ButtonMask {
+        ids.iter()
+            .fold(ButtonMask::default(), |m, &id| m.with(ButtonId(id)))
+    }
+
+    /// `update` を呼び、戻り値と発行されたジェスチャを返す
+    fn update(
+        detector: &mut ChordDetector,
+        pressed: ButtonMask,
+        now_ms: u32,
+    ) -> (bool, Vec<(ButtonMask, ChordGesture)>) {
+        let mut events = Vec::new();
+        let suppress = detector.update(pressed, now_ms, &mut |buttons, gesture| {
+            events.push((buttons, gesture))
+        });
+        (suppress, events)
+    }
+
+    #[test]
+    fn single_button_is_not_a_chord() {
+        let mut detector = ChordDetector::new(LONG_PRESS_MS);
+        assert_eq!(update(&mut detector, mask(&[0]), 0), (false, vec![]));
+        assert_eq!(update(&mut detector, mask(&[0]), 5000), (false, vec![]));
+        assert_eq!(update(&mut detector, mask(&[]), 5100), (false, vec![]));
+        assert!(!detector.is_active());
+        assert_eq!(detector.next_deadline_ms(5100), None);
+    }
+
+    #[test]
+    fn chord_starts_with_two_buttons_and_reports_held_time() {
+        let mut detector = ChordDetector::new(LONG_PRESS_MS);
+        assert_eq!(update(&mut detector, mask(&[0]), 100), (false, vec![]));
+        assert_eq!(
+            update(&mut detector, mask(&[0, 1]), 150),
+            (true, vec![(mask(&[0, 1]), ChordGesture::Pressed)])
+        );
+        assert!(detector.is_active());
+        assert_eq!(detector.next_deadline_ms(400), Some(750));
+
+        // 片方だけ離しても同時押しは継続する
+        assert_eq!(update(&mut detector, mask(&[1]), 300), (true, vec![]));
+        assert_eq!(
+            update(&mut detector, mask(&[]), 450),
+            (
+                true,
+                vec![(mask(&[0, 1]), ChordGesture::Released { held_ms: 300 })]
+            )
+        );
+        assert!(!detector.is_active());
+    }
+
+    #[test]
+    fn button_added_mid_chord_joins_it() {
+        let mut detector = ChordDetector::new(LONG_PRESS_MS);
+        update(&mut detector, mask(&[0, 1]), 0);
+        assert_eq!(update(&mut detector, mask(&[0, 1, 2]), 100), (true, vec![]));
+        assert_eq!(
+            update(&mut detector, mask(&[]), 200),
+            (
+                true,
+                vec![(mask(&[0, 1, 2]), ChordGesture::Released { held_ms: 200 })]
+            )
+        );
+    }
+
+    #[test]
+    fn long_press_fires_once() {
+        let mut detector = ChordDetector::new(LONG_PRESS_MS);
+        update(&mut detector, mask(&[0, 1]), 0);
+        assert_eq!(update(&mut detector, mask(&[0, 1]), 999), (true, vec![]));
+        assert_eq!(
+            update(&mut detector, mask(&[0, 1]), 1000),
+            (true, vec![(mask(&[0, 1]), ChordGesture::LongPress)])
+        );
+        assert_eq!(detector.next_deadline_ms(1000), None);
+        assert_eq!(update(&mut detector, mask(&[0, 1]), 3000), (true, vec![]));
+        assert_eq!(
+            update(&mut detector, mask(&[]), 3500),
+            (
+                true,
+                vec![(mask(&[0, 1]), ChordGesture::Released { held_ms: 3500 })]
+            )
+        );
+
+        // 次の同時押しでは再び長押しを判定する
+        update(&mut detector, mask(&[0, 1]), 4000);
+        assert_eq!(detector.next_deadline_ms(4000), Some(LONG_PRESS_MS));
+    }
+}
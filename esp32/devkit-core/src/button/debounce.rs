@@ -0,0 +1,201 @@
+/// チャタリングの統計情報（診断用）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BounceStats {
+    /// 生のピンレベルが変化した回数
+    pub raw_transitions: u32,
+    /// デバウンス後に確定した遷移の回数
+    pub accepted_transitions: u32,
+    /// デバウンス窓内に元に戻ったため捨てた変化（チャタリング）の回数
+    pub rejected_glitches: u32,
+}
+
+/// 時間窓方式のデバウンスフィルタ
+///
+/// 生レベルが確定レベルと異なる状態が `window_ms` 以上続いた場合のみ遷移を確定する。
+/// 途中で元のレベルに戻った変化はチャタリングとして捨てる。
+pub struct Debouncer {
+    window_ms: u32,
+    stable: bool,
+    changed_at: u32,
+    last_raw: bool,
+    candidate_since: Option<u32>,
+    stats: BounceStats,
+}
+
+impl Debouncer {
+    pub fn new(window_ms: u32, initial: bool) -> Self {
+        Self {
+            window_ms,
+            stable: initial,
+            changed_at: 0,
+            last_raw: initial,
+            candidate_since: None,
+            stats: BounceStats::default(),
+        }
+    }
+
+    /// 生レベルのサンプルを与え、デバウンス後のレベルを返す
+    pub fn update(&mut self, raw: bool, now_ms: u32) -> bool {
+        if raw != self.last_raw {
+            self.last_raw = raw;
+            self.stats.raw_transitions = self.stats.raw_transitions.wrapping_add(1);
+        }
+
+        if raw == self.stable {
+            // 窓内に元へ戻った：チャタリング
+            if self.candidate_since.take().is_some() {
+                self.stats.rejected_glitches = self.stats.rejected_glitches.wrapping_add(1);
+            }
+            return self.stable;
+        }
+
+        let since = *self.candidate_since.get_or_insert(now_ms);
+        if now_ms.wrapping_sub(since) >= self.window_ms {
+            self.stable = raw;
+            self.changed_at = since;
+            self.candidate_since = None;
+            self.stats.accepted_transitions = self.stats.accepted_transitions.wrapping_add(1);
+        }
+
+        self.stable
+    }
+
+    /// 現在の確定レベル
+    pub fn level(&self) -> bool {
+        self.stable
+    }
+
+    /// 現在の確定レベルに変化し始めた時刻（最初のエッジの時刻）
+    pub fn changed_at(&self) -> u32 {
+        self.changed_at
+    }
+
+    /// 判定待ちの変化が確定するまでの残り時間（ms）。判定待ちが無ければ `None`
+    pub fn pending_ms(&self, now_ms: u32) -> Option<u32> {
+        self.candidate_since
+            .map(|since| self.window_ms.saturating_sub(now_ms.wrapping_sub(since)))
+    }
+
+    pub fn stats(&self) -> BounceStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (時刻, 生レベル) の列を与え、各時刻のデバウンス後のレベルを返す
+    fn run(debouncer: &mut Debouncer, samples: &[(u32, bool)]) -> Vec<bool> {
+        samples
+            .iter()
+            .map(|&(now_ms, raw)| debouncer.update(raw, now_ms))
+            .collect()
+    }
+
+    #[test]
+    fn stable_change_is_accepted_after_the_window() {
+        let mut debouncer = Debouncer::new(20, false);
+        let levels = run(
+            &mut debouncer,
+            &[(0, false), (5, true), (24, true), (25, true)],
+        );
+        assert_eq!(levels, [false, false, false, true]);
+        assert!(debouncer.level());
+        // 確定した時刻は最初のエッジ
+        assert_eq!(debouncer.changed_at(), 5);
+        assert_eq!(
+            debouncer.stats(),
+            BounceStats {
+                raw_transitions: 1,
+                accepted_transitions: 1,
+                rejected_glitches: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn bounces_within_the_window_are_rejected() {
+        let mut debouncer = Debouncer::new(20, false);
+        let levels = run(
+            &mut debouncer,
+            &[
+                (0, true),
+                (3, false),
+                (6, true),
+                (9, false),
+                (12, true),
+                (15, false),
+                (100, false),
+            ],
+        );
+        assert!(levels.iter().all(|&level| !level));
+        assert_eq!(
+            debouncer.stats(),
+            BounceStats {
+                raw_transitions: 6,
+                accepted_transitions: 0,
+                rejected_glitches: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn bouncy_press_settles_to_one_transition() {
+        let mut debouncer = Debouncer::new(10, false);
+        let levels = run(
+            &mut debouncer,
+            &[(0, true), (2, false), (4, true), (14, true), (50, true)],
+        );
+        assert_eq!(levels, [false, false, false, true, true]);
+        assert_eq!(debouncer.changed_at(), 4);
+        assert_eq!(debouncer.stats().accepted_transitions, 1);
+        assert_eq!(debouncer.stats().rejected_glitches, 1);
+    }
+
+    #[test]
+    fn release_is_debounced_too() {
+        let mut debouncer = Debouncer::new(10, true);
+        let levels = run(
+            &mut debouncer,
+            &[
+                (0, true),
+                (100, false),
+                (105, true),
+                (110, false),
+                (119, false),
+                (120, false),
+            ],
+        );
+        assert_eq!(levels, [true, true, true, true, true, false]);
+        assert_eq!(debouncer.changed_at(), 110);
+    }
+
+    #[test]
+    fn zero_window_accepts_immediately() {
+        let mut debouncer = Debouncer::new(0, false);
+        assert!(debouncer.update(true, 7));
+        assert!(!debouncer.update(false, 8));
+        assert_eq!(debouncer.stats().accepted_transitions, 2);
+    }
+
+    #[test]
+    fn pending_time_counts_down() {
+        let mut debouncer = Debouncer::new(20, false);
+        assert_eq!(debouncer.pending_ms(0), None);
+        debouncer.update(true, 10);
+        assert_eq!(debouncer.pending_ms(15), Some(15));
+        assert_eq!(debouncer.pending_ms(40), Some(0));
+        debouncer.update(true, 30);
+        assert_eq!(debouncer.pending_ms(30), None);
+    }
+
+    #[test]
+    fn window_survives_counter_wraparound() {
+        let start = u32::MAX - 5;
+        let mut debouncer = Debouncer::new(20, false);
+        assert!(!debouncer.update(true, start));
+        assert!(!debouncer.update(true, start.wrapping_add(19)));
+        assert!(debouncer.update(true, start.wrapping_add(20)));
+    }
+}
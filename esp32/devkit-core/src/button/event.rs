@@ -0,0 +1,65 @@
+/// ボタン識別子（`pins.json` の `buttons` の並び順、名前との対応はファームウェア側で引く）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonId(pub u8);
+
+/// 複数ボタンの集合（bit i = ButtonId(i)）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ButtonMask(pub u8);
+
+impl ButtonMask {
+    pub fn with(self, id: ButtonId) -> Self {
+        Self(self.0 | (1 << id.0))
+    }
+
+    /// 押されているボタン数
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// 単一ボタンのジェスチャ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonGesture {
+    /// 短押し（連続クリック待ちのタイムアウト後に確定）
+    ShortPress,
+    /// ダブルクリック
+    DoubleClick,
+    /// トリプルクリック
+    TripleClick,
+    /// 長押し（既定3秒以上）
+    LongPress,
+    /// 超長押し（既定10秒以上、ファクトリーリセット用）
+    VeryLongPress,
+    /// リリース（押下していた時間付き）
+    Released { held_ms: u32 },
+}
+
+/// 複数ボタン同時押し（コード）のジェスチャ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordGesture {
+    /// 2つ以上のボタンが同時に押された
+    Pressed,
+    /// 同時押しのまま長押し
+    LongPress,
+    /// 全ボタンがリリースされた（同時押し開始からの時間付き）
+    Released { held_ms: u32 },
+}
+
+/// ボタンから発行されるイベント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonEvent {
+    /// 単一ボタンのジェスチャ
+    Gesture {
+        button: ButtonId,
+        gesture: ButtonGesture,
+    },
+    /// 同時押し（同時押し中は各ボタン単体のジェスチャは発行しない）
+    Chord {
+        buttons: ButtonMask,
+        gesture: ChordGesture,
+    },
+}
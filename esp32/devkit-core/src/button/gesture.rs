@@ -0,0 +1,339 @@
+use crate::button::event::ButtonGesture;
+
+/// ジェスチャ判定のタイミング設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestureConfig {
+    /// 長押しと判定するまでの押下時間
+    pub long_press_ms: u32,
+    /// 超長押し（ファクトリーリセット等）と判定するまでの押下時間
+    pub very_long_press_ms: u32,
+    /// 連続クリックとみなすリリース後の待ち時間
+    pub multi_click_gap_ms: u32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            long_press_ms: 3000,
+            very_long_press_ms: 10000,
+            multi_click_gap_ms: 300,
+        }
+    }
+}
+
+/// ボタンのジェスチャ認識器
+///
+/// `update` に現在の押下状態と時刻(ms)を渡すと、確定したイベントを `emit` に通知する。
+/// 時刻は単調増加するミリ秒カウンタであればよい（ラップアラウンドは考慮済み）。
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    pressed: bool,
+    pressed_at: u32,
+    released_at: u32,
+    clicks: u8,
+    long_fired: bool,
+    very_long_fired: bool,
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            pressed: false,
+            pressed_at: 0,
+            released_at: 0,
+            clicks: 0,
+            long_fired: false,
+            very_long_fired: false,
+        }
+    }
+
+    /// 次に `update` を呼ぶ必要がある時刻までの残り時間（ms）
+    ///
+    /// 判定待ちが無い場合は `None`（次の入力変化まで待ってよい）
+    pub fn next_deadline_ms(&self, now_ms: u32) -> Option<u32> {
+        let remaining = |since: u32, limit: u32| limit.saturating_sub(now_ms.wrapping_sub(since));
+
+        if self.pressed {
+            if !self.long_fired {
+                Some(remaining(self.pressed_at, self.config.long_press_ms))
+            } else if !self.very_long_fired {
+                Some(remaining(self.pressed_at, self.config.very_long_press_ms))
+            } else {
+                None
+            }
+        } else if self.clicks > 0 {
+            Some(remaining(self.released_at, self.config.multi_click_gap_ms))
+        } else {
+            None
+        }
+    }
+
+    /// 現在の押下・クリック列を破棄する（同時押しに取り込まれた場合など）
+    ///
+    /// 押下中であればリリース時にクリックとして数えない。
+    pub fn cancel(&mut self) {
+        self.clicks = 0;
+        self.long_fired = true;
+        self.very_long_fired = true;
+    }
+
+    pub fn update(&mut self, pressed: bool, now_ms: u32, emit: &mut dyn FnMut(ButtonGesture)) {
+        match (self.pressed, pressed) {
+            // 押下開始
+            (false, true) => {
+                self.pressed = true;
+                self.pressed_at = now_ms;
+                self.long_fired = false;
+                self.very_long_fired = false;
+            }
+            // 押下継続：長押し判定
+            (true, true) => {
+                let held_ms = now_ms.wrapping_sub(self.pressed_at);
+                if !self.long_fired && held_ms >= self.config.long_press_ms {
+                    // 長押しが成立したらクリック列は破棄
+                    self.long_fired = true;
+                    self.clicks = 0;
+                    emit(ButtonGesture::LongPress);
+                }
+                if !self.very_long_fired && held_ms >= self.config.very_long_press_ms {
+                    self.very_long_fired = true;
+                    emit(ButtonGesture::VeryLongPress);
+                }
+            }
+            // リリース
+            (true, false) => {
+                self.pressed = false;
+                let held_ms = now_ms.wrapping_sub(self.pressed_at);
+                emit(ButtonGesture::Released { held_ms });
+
+                if self.long_fired {
+                    self.clicks = 0;
+                    return;
+                }
+
+                self.clicks = self.clicks.saturating_add(1);
+                self.released_at = now_ms;
+
+                // 3回目は待たずに確定
+                if self.clicks >= 3 {
+                    self.clicks = 0;
+                    emit(ButtonGesture::TripleClick);
+                }
+            }
+            // 待機中：連続クリックの確定
+            (false, false) => {
+                if self.clicks == 0 {
+                    return;
+                }
+                if now_ms.wrapping_sub(self.released_at) < self.config.multi_click_gap_ms {
+                    return;
+                }
+
+                let clicks = self.clicks;
+                self.clicks = 0;
+                match clicks {
+                    1 => emit(ButtonGesture::ShortPress),
+                    _ => emit(ButtonGesture::DoubleClick),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ButtonGesture::*;
+
+    /// (時刻, 押下状態) の列を与え、(時刻, ジェスチャ) の列を返す
+    fn run(samples: &[(u32, bool)]) -> Vec<(u32, ButtonGesture)> {
+        run_with(GestureRecognizer::new(GestureConfig::default()), samples)
+    }
+
+    fn run_with(
+        mut recognizer: GestureRecognizer,
+        samples: &[(u32, bool)],
+    ) -> Vec<(u32, ButtonGesture)> {
+        let mut events = Vec::new();
+        for &(now_ms, pressed) in samples {
+            recognizer.update(pressed, now_ms, &mut |gesture| {
+                events.push((now_ms, gesture))
+            });
+        }
+        events
+    }
+
+    #[test]
+    fn short_press_is_confirmed_after_the_gap() {
+        let events = run(&[(0, true), (100, false), (399, false), (400, false)]);
+        assert_eq!(
+            events,
+            [(100, Released { held_ms: 100 }), (400, ShortPress)]
+        );
+    }
+
+    #[test]
+    fn double_click() {
+        let events = run(&[
+            (0, true),
+            (80, false),
+            (300, true),
+            (380, false),
+            (600, false),
+            (680, false),
+        ]);
+        assert_eq!(
+            events,
+            [
+                (80, Released { held_ms: 80 }),
+                (380, Released { held_ms: 80 }),
+                (680, DoubleClick),
+            ]
+        );
+    }
+
+    #[test]
+    fn triple_click_is_confirmed_without_waiting() {
+        let events = run(&[
+            (0, true),
+            (50, false),
+            (200, true),
+            (250, false),
+            (400, true),
+            (450, false),
+            (2000, false),
+        ]);
+        assert_eq!(events.last(), Some(&(450, TripleClick)));
+        assert_eq!(events.len(), 4);
+    }
+
+    #[test]
+    fn clicks_further_apart_than_the_gap_are_separate() {
+        let events = run(&[
+            (0, true),
+            (50, false),
+            (350, false),
+            (400, true),
+            (450, false),
+            (750, false),
+        ]);
+        let gestures: Vec<_> = events
+            .into_iter()
+            .map(|(_, g)| g)
+            .filter(|g| !matches!(g, Released { .. }))
+            .collect();
+        assert_eq!(gestures, [ShortPress, ShortPress]);
+    }
+
+    #[test]
+    fn long_and_very_long_press() {
+        let events = run(&[
+            (0, true),
+            (2999, true),
+            (3000, true),
+            (9999, true),
+            (10_000, true),
+            (12_000, false),
+            (13_000, false),
+        ]);
+        assert_eq!(
+            events,
+            [
+                (3000, LongPress),
+                (10_000, VeryLongPress),
+                (12_000, Released { held_ms: 12_000 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn long_press_discards_pending_clicks() {
+        let events = run(&[
+            (0, true),
+            (50, false),
+            (200, true),
+            (3200, true),
+            (3300, false),
+            (4000, false),
+        ]);
+        assert_eq!(
+            events,
+            [
+                (50, Released { held_ms: 50 }),
+                (3200, LongPress),
+                (3300, Released { held_ms: 3100 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_timing() {
+        let recognizer = GestureRecognizer::new(GestureConfig {
+            long_press_ms: 500,
+            very_long_press_ms: 1000,
+            multi_click_gap_ms: 100,
+        });
+        let events = run_with(recognizer, &[(0, true), (500, true), (1000, false)]);
+        assert_eq!(
+            events,
+            [(500, LongPress), (1000, Released { held_ms: 1000 })]
+        );
+    }
+
+    #[test]
+    fn timing_survives_counter_wraparound() {
+        let start = u32::MAX - 100;
+        let events = run(&[
+            (start, true),
+            (start.wrapping_add(3000), true),
+            (start.wrapping_add(3100), false),
+        ]);
+        assert_eq!(
+            events,
+            [
+                (start.wrapping_add(3000), LongPress),
+                (start.wrapping_add(3100), Released { held_ms: 3100 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn cancel_suppresses_the_current_press() {
+        let mut recognizer = GestureRecognizer::new(GestureConfig::default());
+        let mut events = Vec::new();
+        recognizer.update(true, 0, &mut |g| events.push(g));
+        recognizer.cancel();
+        for (now_ms, pressed) in [
+            (5000, true),
+            (11_000, true),
+            (11_100, false),
+            (12_000, false),
+        ] {
+            recognizer.update(pressed, now_ms, &mut |g| events.push(g));
+        }
+        assert_eq!(events, [Released { held_ms: 11_100 }]);
+    }
+
+    #[test]
+    fn deadlines_follow_the_pending_decision() {
+        let mut recognizer = GestureRecognizer::new(GestureConfig::default());
+        let mut ignore = |_| {};
+        assert_eq!(recognizer.next_deadline_ms(0), None);
+
+        recognizer.update(true, 0, &mut ignore);
+        assert_eq!(recognizer.next_deadline_ms(1000), Some(2000));
+        recognizer.update(true, 3000, &mut ignore);
+        assert_eq!(recognizer.next_deadline_ms(3000), Some(7000));
+        recognizer.update(true, 10_000, &mut ignore);
+        assert_eq!(recognizer.next_deadline_ms(10_000), None);
+        recognizer.update(false, 10_100, &mut ignore);
+        assert_eq!(recognizer.next_deadline_ms(10_100), None);
+
+        recognizer.update(true, 20_000, &mut ignore);
+        recognizer.update(false, 20_050, &mut ignore);
+        assert_eq!(recognizer.next_deadline_ms(20_150), Some(200));
+        assert_eq!(recognizer.next_deadline_ms(21_000), Some(0));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod chord;
+pub mod debounce;
+pub mod event;
+pub mod gesture;
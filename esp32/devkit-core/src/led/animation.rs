@@ -0,0 +1,183 @@
+use std::f32::consts::PI;
+
+use crate::led::led_state::LedState;
+use crate::led::pattern::{Clock, LedPattern, Sequencer};
+
+/// 時間変化する点灯状態
+pub enum Animation {
+    /// 一定輝度
+    Static(u8),
+    /// 輝度を直線的に変化
+    Fade {
+        from: u8,
+        to: u8,
+        start: u32,
+        duration_ms: u32,
+    },
+    /// 呼吸（余弦波で明滅）
+    Breathe { start: u32, period_ms: u32 },
+    /// ステップ列パターン
+    Pattern(Sequencer),
+}
+
+impl Animation {
+    /// 表示状態のアニメーションを現在時刻から開始する（`from_level` は切り替え時の輝度）
+    pub fn start(state: LedState, from_level: u8, clock: &impl Clock) -> Self {
+        match state {
+            LedState::On => Animation::Static(u8::MAX),
+            LedState::Off => Animation::Static(0),
+            LedState::Brightness(level) => Animation::Static(level),
+            LedState::Blink {
+                on_ms,
+                off_ms,
+                count,
+            } => Animation::Pattern(Sequencer::start(
+                LedPattern::blink(on_ms, off_ms, count),
+                clock,
+            )),
+            LedState::FadeTo { level, duration_ms } => Animation::Fade {
+                from: from_level,
+                to: level,
+                start: clock.now_ms(),
+                duration_ms,
+            },
+            LedState::Breathe { period_ms } => Animation::Breathe {
+                start: clock.now_ms(),
+                period_ms: period_ms.max(100),
+            },
+            LedState::Pattern(pattern) => Animation::Pattern(Sequencer::start(pattern, clock)),
+        }
+    }
+
+    /// 現在の輝度を返す。終了したアニメーションは最後の輝度の `Static` に置き換える
+    pub fn level(&mut self, clock: &impl Clock) -> u8 {
+        let (level, finished) = match self {
+            Animation::Static(level) => return *level,
+            Animation::Fade {
+                from,
+                to,
+                start,
+                duration_ms,
+            } => {
+                let t = clock.now_ms().wrapping_sub(*start);
+                if t >= *duration_ms {
+                    (*to, true)
+                } else {
+                    // 長いフェードでも桁あふれしないよう i64 で計算（t < duration_ms なので結果は from〜to の範囲）
+                    let delta = (*to as i64 - *from as i64) * t as i64 / *duration_ms as i64;
+                    ((*from as i64 + delta) as u8, false)
+                }
+            }
+            Animation::Breathe { start, period_ms } => {
+                let t = clock.now_ms().wrapping_sub(*start) % *period_ms;
+                let phase = 2.0 * PI * t as f32 / *period_ms as f32;
+                (((1.0 - phase.cos()) / 2.0 * u8::MAX as f32) as u8, false)
+            }
+            Animation::Pattern(sequencer) => match sequencer.level(clock) {
+                Some(level) => (level, false),
+                None => (sequencer.final_level(), true),
+            },
+        };
+
+        if finished {
+            *self = Animation::Static(level);
+        }
+        level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct TestClock(Cell<u32>);
+
+    impl Clock for TestClock {
+        fn now_ms(&self) -> u32 {
+            self.0.get()
+        }
+    }
+
+    fn levels(animation: &mut Animation, clock: &TestClock, times: &[u32]) -> Vec<u8> {
+        times
+            .iter()
+            .map(|&t| {
+                clock.0.set(t);
+                animation.level(clock)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fade_runs_from_start_level_to_target() {
+        let clock = TestClock(Cell::new(1000));
+        let state = LedState::FadeTo {
+            level: 200,
+            duration_ms: 1000,
+        };
+        let mut animation = Animation::start(state, 100, &clock);
+        assert_eq!(
+            levels(&mut animation, &clock, &[1000, 1500, 1999, 2000]),
+            [100, 150, 199, 200]
+        );
+        assert!(matches!(animation, Animation::Static(200)));
+    }
+
+    #[test]
+    fn fade_down_and_long_fade_do_not_overflow() {
+        let clock = TestClock(Cell::new(0));
+        let state = LedState::FadeTo {
+            level: 0,
+            duration_ms: u32::MAX,
+        };
+        let mut animation = Animation::start(state, 255, &clock);
+        assert_eq!(
+            levels(&mut animation, &clock, &[0, u32::MAX / 2, u32::MAX]),
+            [255, 128, 0]
+        );
+    }
+
+    #[test]
+    fn breathe_follows_period_and_wraps() {
+        // 開始時は消灯、半周期で最大、1周期で再び消灯（時刻のラップアラウンドをまたぐ）
+        let start = u32::MAX - 499;
+        let clock = TestClock(Cell::new(start));
+        let mut animation = Animation::start(LedState::Breathe { period_ms: 1000 }, 0, &clock);
+        assert_eq!(
+            levels(
+                &mut animation,
+                &clock,
+                &[start, start.wrapping_add(500), 500]
+            ),
+            [0, 255, 0]
+        );
+        assert!(matches!(animation, Animation::Breathe { .. }));
+    }
+
+    #[test]
+    fn breathe_period_has_lower_bound() {
+        let clock = TestClock(Cell::new(0));
+        let animation = Animation::start(LedState::Breathe { period_ms: 0 }, 0, &clock);
+        assert!(matches!(
+            animation,
+            Animation::Breathe { period_ms: 100, .. }
+        ));
+    }
+
+    #[test]
+    fn finished_blink_becomes_static_off() {
+        let clock = TestClock(Cell::new(0));
+        let state = LedState::Blink {
+            on_ms: 100,
+            off_ms: 100,
+            count: Some(2),
+        };
+        let mut animation = Animation::start(state, 0, &clock);
+        assert_eq!(
+            levels(&mut animation, &clock, &[0, 100, 200, 399, 400]),
+            [255, 0, 255, 0, 0]
+        );
+        assert!(matches!(animation, Animation::Static(0)));
+    }
+}
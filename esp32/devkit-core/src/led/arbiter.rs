@@ -0,0 +1,171 @@
+use crate::led::color::Rgb;
+use crate::led::led_state::LedState;
+
+/// LED表示の要求元（宣言順に優先度が高くなる）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LedSource {
+    /// BLE接続/アドバタイズ状態
+    Ble,
+    /// BLEコマンドによるリモート操作
+    Remote,
+    /// バッテリー残量低下
+    LowBattery,
+    /// エラー表示
+    Error,
+    /// ユーザー操作へのフィードバック
+    UserFeedback,
+}
+
+/// 要求元からの表示要求
+pub struct LedRequest {
+    pub source: LedSource,
+    pub state: LedState,
+    pub color: Rgb,
+    /// 要求ごとに一意な番号（表示の切り替え判定に使う）
+    pub seq: u32,
+    expires_at: Option<u32>,
+}
+
+/// 要求元ごとの表示要求を保持し、最も優先度の高い要求を選ぶ
+///
+/// 要求は要求元ごとに1つで、同じ要求元からの新しい要求は古いものを置き換える。
+/// 各要求は明示的な解除か期限切れで独立に消える。
+#[derive(Default)]
+pub struct LedArbiter {
+    requests: Vec<LedRequest>,
+    next_seq: u32,
+}
+
+impl LedArbiter {
+    /// 表示を要求する（`ttl_ms` が `None` なら解除されるまで有効）
+    pub fn request(
+        &mut self,
+        source: LedSource,
+        state: LedState,
+        color: Rgb,
+        ttl_ms: Option<u32>,
+        now_ms: u32,
+    ) {
+        self.release(source);
+        self.next_seq = self.next_seq.wrapping_add(1);
+        self.requests.push(LedRequest {
+            source,
+            state,
+            color,
+            seq: self.next_seq,
+            expires_at: ttl_ms.map(|ttl| now_ms.wrapping_add(ttl)),
+        });
+    }
+
+    /// 要求を解除する
+    pub fn release(&mut self, source: LedSource) {
+        self.requests.retain(|r| r.source != source);
+    }
+
+    /// 期限切れの要求を取り除き、最優先の要求を返す
+    pub fn winner(&mut self, now_ms: u32) -> Option<&LedRequest> {
+        self.requests.retain(|r| match r.expires_at {
+            // ラップアラウンドを考慮して期限との差の符号で判定
+            Some(at) => (at.wrapping_sub(now_ms) as i32) > 0,
+            None => true,
+        });
+
+        self.requests.iter().max_by_key(|r| r.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn winner_source(arbiter: &mut LedArbiter, now_ms: u32) -> Option<LedSource> {
+        arbiter.winner(now_ms).map(|r| r.source)
+    }
+
+    #[test]
+    fn highest_priority_request_wins() {
+        let mut arbiter = LedArbiter::default();
+        assert_eq!(winner_source(&mut arbiter, 0), None);
+
+        arbiter.request(LedSource::Error, LedState::On, Rgb::WHITE, None, 0);
+        arbiter.request(LedSource::Ble, LedState::Off, Rgb::WHITE, None, 0);
+        arbiter.request(LedSource::Remote, LedState::On, Rgb::WHITE, None, 0);
+        assert_eq!(winner_source(&mut arbiter, 0), Some(LedSource::Error));
+
+        arbiter.request(LedSource::UserFeedback, LedState::On, Rgb::WHITE, None, 0);
+        assert_eq!(
+            winner_source(&mut arbiter, 0),
+            Some(LedSource::UserFeedback)
+        );
+    }
+
+    #[test]
+    fn release_falls_back_to_next_request() {
+        let mut arbiter = LedArbiter::default();
+        arbiter.request(LedSource::Ble, LedState::Off, Rgb::WHITE, None, 0);
+        arbiter.request(LedSource::LowBattery, LedState::On, Rgb::WHITE, None, 0);
+
+        arbiter.release(LedSource::LowBattery);
+        assert_eq!(winner_source(&mut arbiter, 0), Some(LedSource::Ble));
+        arbiter.release(LedSource::Ble);
+        assert_eq!(winner_source(&mut arbiter, 0), None);
+    }
+
+    #[test]
+    fn expired_request_is_removed() {
+        let mut arbiter = LedArbiter::default();
+        arbiter.request(LedSource::Ble, LedState::Off, Rgb::WHITE, None, 1000);
+        arbiter.request(
+            LedSource::UserFeedback,
+            LedState::On,
+            Rgb::WHITE,
+            Some(200),
+            1000,
+        );
+
+        assert_eq!(
+            winner_source(&mut arbiter, 1199),
+            Some(LedSource::UserFeedback)
+        );
+        assert_eq!(winner_source(&mut arbiter, 1200), Some(LedSource::Ble));
+        // 一度期限切れで消えた要求は時刻が戻っても復活しない
+        assert_eq!(winner_source(&mut arbiter, 1000), Some(LedSource::Ble));
+    }
+
+    #[test]
+    fn expiry_handles_clock_wraparound() {
+        let mut arbiter = LedArbiter::default();
+        let now = u32::MAX - 50;
+        arbiter.request(LedSource::Error, LedState::On, Rgb::WHITE, Some(100), now);
+
+        // 期限（49）は now より数値が小さいが、まだ有効
+        assert_eq!(
+            winner_source(&mut arbiter, u32::MAX),
+            Some(LedSource::Error)
+        );
+        assert_eq!(winner_source(&mut arbiter, 48), Some(LedSource::Error));
+        assert_eq!(winner_source(&mut arbiter, 49), None);
+    }
+
+    #[test]
+    fn same_source_replaces_previous_request() {
+        let mut arbiter = LedArbiter::default();
+        arbiter.request(LedSource::Remote, LedState::On, Rgb::WHITE, Some(100), 0);
+        let first_seq = arbiter.winner(0).unwrap().seq;
+
+        arbiter.request(
+            LedSource::Remote,
+            LedState::Brightness(10),
+            Rgb::WHITE,
+            None,
+            50,
+        );
+        let winner = arbiter.winner(500).unwrap();
+        assert_eq!(winner.state, LedState::Brightness(10));
+        assert_ne!(winner.seq, first_seq);
+
+        // 置き換えた要求だけが残るので、解除すれば何も残らない
+        arbiter.release(LedSource::Remote);
+        assert_eq!(winner_source(&mut arbiter, 500), None);
+    }
+}
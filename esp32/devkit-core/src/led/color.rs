@@ -0,0 +1,24 @@
+/// LEDの表示色（RGB各8bit）
+///
+/// 単色LEDでは無視され、輝度のみが反映される。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const WHITE: Rgb = Rgb::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// 輝度 `level` を掛けた色（知覚輝度が線形に近くなるよう二乗で補正）
+    pub fn scale(self, level: u8) -> Self {
+        let level = level as u32;
+        let scale = |c: u8| (c as u32 * level * level / (255 * 255)) as u8;
+        Self::new(scale(self.r), scale(self.g), scale(self.b))
+    }
+}
@@ -0,0 +1,27 @@
+use crate::led::pattern::LedPattern;
+
+/// LEDの表示状態（各要求元が要求する内容）
+#[derive(PartialEq, Clone, Debug)]
+pub enum LedState {
+    On,
+    Off,
+    /// 指定した輝度で点灯（0 = 消灯, 255 = 最大）
+    Brightness(u8),
+    /// 点灯 `on_ms` / 消灯 `off_ms` の点滅（`count` 回で終了して消灯、`None` なら無限）
+    Blink {
+        on_ms: u32,
+        off_ms: u32,
+        count: Option<u16>,
+    },
+    /// 切り替え時の輝度から `level` まで `duration_ms` かけて変化
+    FadeTo {
+        level: u8,
+        duration_ms: u32,
+    },
+    /// 周期 `period_ms` でゆっくり明滅（呼吸）
+    Breathe {
+        period_ms: u32,
+    },
+    /// ステップ列による点灯パターン（プリセットはファームウェアの `config::led::preset` で取得）
+    Pattern(LedPattern),
+}
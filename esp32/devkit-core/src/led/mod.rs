@@ -0,0 +1,5 @@
+pub mod animation;
+pub mod arbiter;
+pub mod color;
+pub mod led_state;
+pub mod pattern;
@@ -0,0 +1,278 @@
+use std::borrow::Cow;
+
+/// ミリ秒単位の時刻源（実機では起動からの経過時間、テストでは任意の値を返す）
+pub trait Clock {
+    fn now_ms(&self) -> u32;
+}
+
+/// パターンの1ステップ（輝度を `duration_ms` だけ保持）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternStep {
+    pub level: u8,
+    pub duration_ms: u32,
+}
+
+/// パターンの繰り返し回数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternRepeat {
+    /// 停止されるまで繰り返す
+    Forever,
+    /// 指定回数で終了
+    Times(u16),
+}
+
+/// 輝度ステップ列と繰り返し回数からなる点灯パターン
+///
+/// プリセット（`config/led_patterns.json`）は静的スライスを借用し、実行時に組み立てたものは所有する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedPattern {
+    pub steps: Cow<'static, [PatternStep]>,
+    pub repeat: PatternRepeat,
+}
+
+impl LedPattern {
+    /// 点灯 `on_ms` / 消灯 `off_ms` を繰り返すパターン（`count` が `None` なら無限）
+    pub fn blink(on_ms: u32, off_ms: u32, count: Option<u16>) -> Self {
+        // 20ms周期のタスクで再現できる範囲に制限
+        let steps = vec![
+            PatternStep {
+                level: u8::MAX,
+                duration_ms: on_ms.clamp(20, 65535),
+            },
+            PatternStep {
+                level: 0,
+                duration_ms: off_ms.clamp(20, 65535),
+            },
+        ];
+        Self {
+            steps: Cow::Owned(steps),
+            repeat: match count {
+                Some(times) => PatternRepeat::Times(times),
+                None => PatternRepeat::Forever,
+            },
+        }
+    }
+
+    /// 1周期の長さ（ms）
+    pub fn cycle_ms(&self) -> u32 {
+        self.steps
+            .iter()
+            .fold(0u32, |acc, step| acc.saturating_add(step.duration_ms))
+    }
+
+    /// パターン終了後に保持する輝度（最後のステップ）
+    pub fn final_level(&self) -> u8 {
+        self.steps.last().map(|step| step.level).unwrap_or(0)
+    }
+}
+
+/// パターンの再生エンジン
+pub struct Sequencer {
+    pattern: LedPattern,
+    started_at: u32,
+    cycle_ms: u32,
+}
+
+impl Sequencer {
+    pub fn start(pattern: LedPattern, clock: &impl Clock) -> Self {
+        let cycle_ms = pattern.cycle_ms();
+        Self {
+            pattern,
+            started_at: clock.now_ms(),
+            cycle_ms,
+        }
+    }
+
+    /// パターン終了後に保持する輝度
+    pub fn final_level(&self) -> u8 {
+        self.pattern.final_level()
+    }
+
+    /// 現在の輝度。パターンが終了していれば `None`
+    pub fn level(&self, clock: &impl Clock) -> Option<u8> {
+        if self.cycle_ms == 0 {
+            return None;
+        }
+
+        let elapsed = clock.now_ms().wrapping_sub(self.started_at);
+        if let PatternRepeat::Times(times) = self.pattern.repeat {
+            if elapsed / self.cycle_ms >= times as u32 {
+                return None;
+            }
+        }
+
+        let mut offset = elapsed % self.cycle_ms;
+        for step in self.pattern.steps.iter() {
+            if offset < step.duration_ms {
+                return Some(step.level);
+            }
+            offset -= step.duration_ms;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct TestClock(Cell<u32>);
+
+    impl TestClock {
+        fn at(now_ms: u32) -> Self {
+            Self(Cell::new(now_ms))
+        }
+
+        fn set(&self, now_ms: u32) {
+            self.0.set(now_ms);
+        }
+    }
+
+    impl Clock for TestClock {
+        fn now_ms(&self) -> u32 {
+            self.0.get()
+        }
+    }
+
+    const STEPS: [PatternStep; 3] = [
+        PatternStep {
+            level: 255,
+            duration_ms: 100,
+        },
+        PatternStep {
+            level: 0,
+            duration_ms: 50,
+        },
+        PatternStep {
+            level: 128,
+            duration_ms: 50,
+        },
+    ];
+
+    fn levels(sequencer: &Sequencer, clock: &TestClock, times: &[u32]) -> Vec<Option<u8>> {
+        times
+            .iter()
+            .map(|&t| {
+                clock.set(t);
+                sequencer.level(clock)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn steps_follow_their_durations() {
+        let clock = TestClock::at(1000);
+        let pattern = LedPattern {
+            steps: Cow::Borrowed(&STEPS),
+            repeat: PatternRepeat::Forever,
+        };
+        assert_eq!(pattern.cycle_ms(), 200);
+        let sequencer = Sequencer::start(pattern, &clock);
+        assert_eq!(
+            levels(
+                &sequencer,
+                &clock,
+                &[1000, 1099, 1100, 1149, 1150, 1199, 1200, 1350]
+            ),
+            [
+                Some(255),
+                Some(255),
+                Some(0),
+                Some(0),
+                Some(128),
+                Some(128),
+                Some(255),
+                Some(128)
+            ]
+        );
+    }
+
+    #[test]
+    fn finite_pattern_ends_and_holds_the_last_level() {
+        let clock = TestClock::at(0);
+        let pattern = LedPattern {
+            steps: Cow::Borrowed(&STEPS),
+            repeat: PatternRepeat::Times(2),
+        };
+        let sequencer = Sequencer::start(pattern, &clock);
+        assert_eq!(
+            levels(&sequencer, &clock, &[199, 399, 400]),
+            [Some(128), Some(128), None]
+        );
+        assert_eq!(sequencer.final_level(), 128);
+    }
+
+    #[test]
+    fn zero_repeats_or_empty_steps_end_immediately() {
+        let clock = TestClock::at(0);
+        let once = Sequencer::start(
+            LedPattern {
+                steps: Cow::Borrowed(&STEPS),
+                repeat: PatternRepeat::Times(0),
+            },
+            &clock,
+        );
+        assert_eq!(once.level(&clock), None);
+
+        let empty = LedPattern {
+            steps: Cow::Owned(Vec::new()),
+            repeat: PatternRepeat::Forever,
+        };
+        assert_eq!(empty.final_level(), 0);
+        assert_eq!(Sequencer::start(empty, &clock).level(&clock), None);
+    }
+
+    #[test]
+    fn blink_clamps_durations() {
+        let pattern = LedPattern::blink(5, 100_000, Some(3));
+        assert_eq!(
+            pattern.steps.as_ref(),
+            [
+                PatternStep {
+                    level: 255,
+                    duration_ms: 20
+                },
+                PatternStep {
+                    level: 0,
+                    duration_ms: 65535
+                },
+            ]
+        );
+        assert_eq!(pattern.repeat, PatternRepeat::Times(3));
+        assert_eq!(
+            LedPattern::blink(100, 100, None).repeat,
+            PatternRepeat::Forever
+        );
+    }
+
+    #[test]
+    fn cycle_length_saturates() {
+        let steps = vec![
+            PatternStep {
+                level: 1,
+                duration_ms: u32::MAX,
+            },
+            PatternStep {
+                level: 2,
+                duration_ms: 10,
+            },
+        ];
+        let pattern = LedPattern {
+            steps: Cow::Owned(steps),
+            repeat: PatternRepeat::Forever,
+        };
+        assert_eq!(pattern.cycle_ms(), u32::MAX);
+    }
+
+    #[test]
+    fn timing_survives_counter_wraparound() {
+        let clock = TestClock::at(u32::MAX - 50);
+        let pattern = LedPattern::blink(100, 100, None);
+        let sequencer = Sequencer::start(pattern, &clock);
+        assert_eq!(
+            levels(&sequencer, &clock, &[48, 49, 148, 149]),
+            [Some(255), Some(0), Some(0), Some(255)]
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! esp32-devkit-v1 のハードウェア・BLE スタックに依存しない処理
+//!
+//! ファームウェアは各モジュールを `app` 以下の同じ位置に再エクスポートして使う。
+//! ホストでは `cargo test` で単体テストを実行できる。
+//!
+//! - [`battery`] モジュール: バッテリー電圧から残量への換算と低下の判定
+//! - [`ble`] モジュール: アドバタイズの状態データ、ビーコンのフレーム、接続の一覧、再アドバタイズの方針、リモートコマンドの解析、バイナリプロトコルとの変換、NUS コンソール、スキャン結果、GATT クライアントの要求
+//! - [`button`] モジュール: ボタンのイベント、デバウンス、ジェスチャ・同時押しの判定
+//! - [`led`] モジュール: LED の表示色、表示状態、要求元ごとの調停、点灯パターンとアニメーションの輝度計算
+
+pub mod battery;
+pub mod ble;
+pub mod button;
+pub mod led;
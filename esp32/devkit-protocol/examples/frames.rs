@@ -0,0 +1,136 @@
+//! ホストで BLE の Write/Notify に載せるフレームを作る・読むツール
+//!
+//! ```bash
+//! # コマンドをフレームに分割して16進で表示（既定の ATT MTU は 23）
+//! cargo run -p devkit-protocol --example frames -- encode query
+//! cargo run -p devkit-protocol --example frames -- encode led status on --mtu 185
+//! cargo run -p devkit-protocol --example frames -- encode led status blink 100 100 3
+//! # 受信したフレーム（16進、複数可）を再構成して表示
+//! cargo run -p devkit-protocol --example frames -- decode a501000101...
+//! ```
+
+use std::process::ExitCode;
+
+use devkit_protocol::{Command, Fragmenter, LedMode, Message, Reassembler};
+
+const USAGE: &str =
+    "usage: frames encode <query|stop|reboot|led <name> <on|off|level N|blink ON OFF [COUNT]|fade N MS>> [--mtu N]
+       frames decode <hex frame>...";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("encode") => encode(&args[1..]),
+        Some("decode") => decode(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn encode(args: &[String]) -> Result<(), String> {
+    let (args, mtu) = match args {
+        [rest @ .., flag, mtu] if flag == "--mtu" => (
+            rest,
+            mtu.parse::<usize>()
+                .map_err(|_| format!("invalid MTU: {mtu}"))?,
+        ),
+        _ => (args, 23),
+    };
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let command = match args.as_slice() {
+        ["query"] => Command::QueryState,
+        ["stop"] => Command::StopAdvertise,
+        ["reboot"] => Command::Reboot,
+        ["led", led, "on"] => set_led(led, LedMode::On),
+        ["led", led, "off"] => set_led(led, LedMode::Off),
+        ["led", led, "level", level] => set_led(led, LedMode::Level(parse("level", level)?)),
+        ["led", led, "blink", on_ms, off_ms, count @ ..] if count.len() <= 1 => set_led(
+            led,
+            LedMode::Blink {
+                on_ms: parse("on_ms", on_ms)?,
+                off_ms: parse("off_ms", off_ms)?,
+                count: count
+                    .first()
+                    .map(|c| parse("count", c))
+                    .transpose()?
+                    .unwrap_or(0),
+            },
+        ),
+        ["led", led, "fade", level, duration_ms] => set_led(
+            led,
+            LedMode::Fade {
+                level: parse("level", level)?,
+                duration_ms: parse("duration_ms", duration_ms)?,
+            },
+        ),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let mut payload = [0u8; 512];
+    let len = Message::Command(command)
+        .encode(&mut payload)
+        .map_err(|e| e.to_string())?;
+    // Write で送れるのは ATT MTU - 3 バイトまで
+    let max_frame_len = mtu.saturating_sub(3);
+    let mut fragmenter =
+        Fragmenter::new(&payload[..len], 0, max_frame_len).map_err(|e| e.to_string())?;
+    let mut frame = vec![0u8; max_frame_len];
+    while let Some(frame_len) = fragmenter.next_frame(&mut frame) {
+        let frame_len = frame_len.map_err(|e| e.to_string())?;
+        println!("{}", to_hex(&frame[..frame_len]));
+    }
+    Ok(())
+}
+
+fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid {name}: {value}"))
+}
+
+fn set_led<'a>(led: &'a str, mode: LedMode<'a>) -> Command<'a> {
+    Command::SetLed {
+        led,
+        mode,
+        color: None,
+    }
+}
+
+fn decode(frames: &[String]) -> Result<(), String> {
+    if frames.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    let mut reassembler = Reassembler::<4096>::new();
+    for hex in frames {
+        let frame = from_hex(hex).ok_or_else(|| format!("invalid hex: {hex}"))?;
+        match reassembler.push(&frame) {
+            Ok(Some(payload)) => match Message::decode(payload) {
+                Ok(message) => println!("{message:?}"),
+                Err(e) => println!("decode error: {e}"),
+            },
+            Ok(None) => {}
+            Err(e) => println!("frame error: {e}"),
+        }
+    }
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
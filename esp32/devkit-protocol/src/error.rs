@@ -0,0 +1,54 @@
+use core::fmt;
+
+/// エンコード・デコード・フレーム処理のエラー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// 書き込み先のバッファが足りない
+    BufferTooSmall,
+    /// 文字列が長すぎる（最大255バイト）
+    StringTooLong,
+    /// データが途中で終わっている
+    UnexpectedEnd,
+    /// 未知のタグ
+    UnknownTag { kind: &'static str, tag: u8 },
+    /// 文字列が UTF-8 ではない
+    InvalidUtf8,
+    /// メッセージの後ろに余分なデータがある
+    TrailingBytes,
+    /// フレームの先頭が `MAGIC` ではない
+    BadMagic,
+    /// 対応していないプロトコルバージョン
+    UnsupportedVersion(u8),
+    /// フレームの長さフィールドが実際の長さと合わない
+    BadLength,
+    /// CRC が一致しない
+    CrcMismatch { expected: u16, actual: u16 },
+    /// 分割番号が不正、または途中の分割が欠けている
+    UnexpectedFragment,
+    /// 分割の数が上限（255）を超える、または MTU がフレームのオーバーヘッド以下
+    TooManyFragments,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BufferTooSmall => write!(f, "buffer too small"),
+            Error::StringTooLong => write!(f, "string too long"),
+            Error::UnexpectedEnd => write!(f, "unexpected end of data"),
+            Error::UnknownTag { kind, tag } => write!(f, "unknown {kind} tag: {tag:#04x}"),
+            Error::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            Error::TrailingBytes => write!(f, "trailing bytes after message"),
+            Error::BadMagic => write!(f, "bad frame magic"),
+            Error::UnsupportedVersion(v) => write!(f, "unsupported protocol version: {v}"),
+            Error::BadLength => write!(f, "bad frame length"),
+            Error::CrcMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "CRC mismatch (expected {expected:#06x}, actual {actual:#06x})"
+                )
+            }
+            Error::UnexpectedFragment => write!(f, "unexpected fragment"),
+            Error::TooManyFragments => write!(f, "too many fragments"),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! バージョン付きフレームと、ATT MTU を超えるメッセージの分割・再構成
+//!
+//! フレーム形式（数値はリトルエンディアン）:
+//!
+//! | magic | version | msg_id | frag_index | frag_count | len (u16) | chunk | crc16 (u16) |
+//!
+//! - `msg_id`: 送信側がメッセージごとに割り当てる番号（分割の取り違え検出用）
+//! - `frag_index` / `frag_count`: 0 始まりの分割番号と分割数（分割しなければ 0 / 1）
+//! - `crc16`: 先頭から `chunk` の末尾までに対する CRC-16/CCITT-FALSE
+
+use crate::crc::crc16;
+use crate::error::Error;
+use crate::wire::{Reader, Writer};
+
+/// フレーム先頭の識別バイト（テキストコマンドとの判別にも使う）
+pub const MAGIC: u8 = 0xA5;
+
+/// 現在のプロトコルバージョン（互換性の無い変更で上げる）
+pub const PROTOCOL_VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 7;
+
+/// フレーム1つあたりのヘッダと CRC のバイト数
+pub const FRAME_OVERHEAD: usize = HEADER_LEN + 2;
+
+/// ペイロードを `max_frame_len` 以下のフレームに分割する
+pub struct Fragmenter<'a> {
+    payload: &'a [u8],
+    msg_id: u8,
+    chunk_len: usize,
+    count: u8,
+    index: u8,
+}
+
+impl<'a> Fragmenter<'a> {
+    /// `max_frame_len` は通常 ATT MTU - 3（Notify/Write で送れる最大長）
+    pub fn new(payload: &'a [u8], msg_id: u8, max_frame_len: usize) -> Result<Self, Error> {
+        let chunk_len = max_frame_len
+            .saturating_sub(FRAME_OVERHEAD)
+            .min(u16::MAX as usize);
+        if chunk_len == 0 {
+            return Err(Error::TooManyFragments);
+        }
+        // 空のペイロードも1フレームとして送る
+        let count = payload.len().div_ceil(chunk_len).max(1);
+        let count = u8::try_from(count).map_err(|_| Error::TooManyFragments)?;
+        Ok(Self {
+            payload,
+            msg_id,
+            chunk_len,
+            count,
+            index: 0,
+        })
+    }
+
+    /// 分割数
+    pub fn fragment_count(&self) -> u8 {
+        self.count
+    }
+
+    /// 次のフレームを `out` に書き込み、その長さを返す（全て出力したら `None`）
+    pub fn next_frame(&mut self, out: &mut [u8]) -> Option<Result<usize, Error>> {
+        if self.index >= self.count {
+            return None;
+        }
+        let start = self.index as usize * self.chunk_len;
+        let end = (start + self.chunk_len).min(self.payload.len());
+        let result = write_frame(
+            out,
+            self.msg_id,
+            self.index,
+            self.count,
+            &self.payload[start..end],
+        );
+        self.index += 1;
+        Some(result)
+    }
+}
+
+fn write_frame(
+    out: &mut [u8],
+    msg_id: u8,
+    index: u8,
+    count: u8,
+    chunk: &[u8],
+) -> Result<usize, Error> {
+    let mut w = Writer::new(out);
+    w.u8(MAGIC)?;
+    w.u8(PROTOCOL_VERSION)?;
+    w.u8(msg_id)?;
+    w.u8(index)?;
+    w.u8(count)?;
+    w.u16(chunk.len() as u16)?;
+    w.bytes(chunk)?;
+    let len = w.position();
+    let crc = crc16(&out[..len]);
+    out.get_mut(len..len + 2)
+        .ok_or(Error::BufferTooSmall)?
+        .copy_from_slice(&crc.to_le_bytes());
+    Ok(len + 2)
+}
+
+/// 受信したフレームの内容（検証済み）
+struct Fragment<'a> {
+    msg_id: u8,
+    index: u8,
+    count: u8,
+    chunk: &'a [u8],
+}
+
+fn parse_frame(frame: &[u8]) -> Result<Fragment<'_>, Error> {
+    if frame.len() < FRAME_OVERHEAD {
+        return Err(Error::UnexpectedEnd);
+    }
+    let (body, crc) = frame.split_at(frame.len() - 2);
+    let mut r = Reader::new(body);
+    if r.u8()? != MAGIC {
+        return Err(Error::BadMagic);
+    }
+    let version = r.u8()?;
+    if version != PROTOCOL_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    let msg_id = r.u8()?;
+    let index = r.u8()?;
+    let count = r.u8()?;
+    let len = r.u16()? as usize;
+    if body.len() != HEADER_LEN + len {
+        return Err(Error::BadLength);
+    }
+
+    let expected = u16::from_le_bytes([crc[0], crc[1]]);
+    let actual = crc16(body);
+    if expected != actual {
+        return Err(Error::CrcMismatch { expected, actual });
+    }
+    if index >= count {
+        return Err(Error::UnexpectedFragment);
+    }
+    Ok(Fragment {
+        msg_id,
+        index,
+        count,
+        chunk: r.bytes(len)?,
+    })
+}
+
+/// 受信したフレームを最大 `N` バイトのペイロードに再構成する
+///
+/// 分割は `frag_index` の順に届く前提（BLE の Write/Notify は順序が保たれる）。
+/// 途中で番号が飛んだ場合は組み立て中のメッセージを破棄する。
+pub struct Reassembler<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    /// 組み立て中のメッセージの (msg_id, 次に期待する frag_index, frag_count)
+    pending: Option<(u8, u8, u8)>,
+    /// 最後に取り込んだフレームの msg_id
+    last_msg_id: Option<u8>,
+}
+
+impl<const N: usize> Default for Reassembler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Reassembler<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+            pending: None,
+            last_msg_id: None,
+        }
+    }
+
+    /// 組み立て中のメッセージを破棄する
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.pending = None;
+    }
+
+    /// 最後に取り込んだフレームの msg_id（検証できなかったフレームでは `None`）
+    ///
+    /// 揃ったメッセージへの応答に同じ msg_id を付けるために使う。
+    pub fn msg_id(&self) -> Option<u8> {
+        self.last_msg_id
+    }
+
+    /// フレームを1つ取り込み、メッセージが揃ったらそのペイロードを返す
+    ///
+    /// `frag_index` が 0 のフレームは常に新しいメッセージの開始として扱う。
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<&[u8]>, Error> {
+        let fragment = match parse_frame(frame) {
+            Ok(fragment) => fragment,
+            Err(e) => {
+                self.reset();
+                self.last_msg_id = None;
+                return Err(e);
+            }
+        };
+        self.last_msg_id = Some(fragment.msg_id);
+
+        if fragment.index == 0 {
+            self.reset();
+        } else if self.pending != Some((fragment.msg_id, fragment.index, fragment.count)) {
+            self.reset();
+            return Err(Error::UnexpectedFragment);
+        }
+
+        let end = self.len + fragment.chunk.len();
+        if end > N {
+            self.reset();
+            return Err(Error::BufferTooSmall);
+        }
+        self.buf[self.len..end].copy_from_slice(fragment.chunk);
+        self.len = end;
+
+        if fragment.index + 1 == fragment.count {
+            let len = self.len;
+            self.reset();
+            Ok(Some(&self.buf[..len]))
+        } else {
+            self.pending = Some((fragment.msg_id, fragment.index + 1, fragment.count));
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! esp32-devkit-v1 とホストツールで共有するバイナリ通信プロトコル（`no_std`、依存クレートなし）
+//!
+//! - [`message`] モジュール: コマンド・イベント・状態スナップショット・エラーなどのメッセージとシリアライズ
+//! - [`frame`] モジュール: バージョン付きフレーム（CRC16）と、ATT MTU を超えるメッセージの分割・再構成
+//!
+//! 送信側は [`Message::encode`] でペイロードを作り、[`Fragmenter`] でフレームに分割して送る。
+//! 受信側は [`Reassembler`] にフレームを渡し、揃ったペイロードを [`Message::decode`] で復元する。
+
+#![no_std]
+
+pub mod crc;
+pub mod error;
+pub mod frame;
+pub mod message;
+mod wire;
+
+pub use error::Error;
+pub use frame::{Fragmenter, Reassembler, FRAME_OVERHEAD, MAGIC, PROTOCOL_VERSION};
+pub use message::{
+    ButtonGesture, ChordGesture, Command, ErrorCode, Event, LedMode, Message, StateSnapshot,
+};
@@ -0,0 +1,414 @@
+//! メッセージ定義とシリアライズ
+//!
+//! 文字列はデコード元のバッファを借用するため、デコード時にアロケーションは発生しない。
+//! 各 enum は先頭 1 バイトのタグで種類を表し、タグの値は互換性のため変更しない。
+
+use crate::error::Error;
+use crate::wire::{Reader, Writer};
+
+/// LEDの表示（コマンドでの指定と、イベントでの通知で共用）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedMode<'a> {
+    On,
+    Off,
+    /// 指定した輝度で点灯
+    Level(u8),
+    /// 点滅（`count` が 0 なら無限）
+    Blink {
+        on_ms: u32,
+        off_ms: u32,
+        count: u16,
+    },
+    /// 現在の輝度から `level` まで変化
+    Fade {
+        level: u8,
+        duration_ms: u32,
+    },
+    Breathe {
+        period_ms: u32,
+    },
+    /// 名前付きプリセットのパターン
+    Preset(&'a str),
+    /// 名前の無いパターン（イベントでの通知用、コマンドでは `Preset` を使う）
+    Pattern,
+    /// 表示要求の解除（コマンド用）
+    Release,
+}
+
+/// アプリケーションコマンド
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command<'a> {
+    SetLed {
+        led: &'a str,
+        mode: LedMode<'a>,
+        color: Option<[u8; 3]>,
+    },
+    StopAdvertise,
+    QueryState,
+    Reboot,
+}
+
+/// 単一ボタンのジェスチャ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonGesture {
+    Short,
+    Double,
+    Triple,
+    Long,
+    VeryLong,
+    Released { held_ms: u32 },
+}
+
+/// 同時押しのジェスチャ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordGesture {
+    Pressed,
+    Long,
+    Released { held_ms: u32 },
+}
+
+/// デバイスから通知されるイベント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'a> {
+    Button {
+        button: &'a str,
+        gesture: ButtonGesture,
+    },
+    /// `buttons` はボタン番号のビットマスク
+    Chord { buttons: u8, gesture: ChordGesture },
+    /// LEDの表示が切り替わった（`source` は要求元、`None` なら要求なし）
+    Led {
+        led: &'a str,
+        source: Option<&'a str>,
+        mode: LedMode<'a>,
+    },
+}
+
+/// 接続/アドバタイズ状態のスナップショット
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StateSnapshot {
+    pub connected: bool,
+    pub advertising: bool,
+    pub error: bool,
+}
+
+/// エラー応答の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// メッセージを解釈できない
+    Malformed,
+    /// 引数が不正（未知のLED名など）
+    InvalidArgument,
+    /// 現在の状態では実行できない
+    InvalidState,
+    /// 内部エラー
+    Internal,
+}
+
+/// フレームで運ばれるメッセージ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message<'a> {
+    Command(Command<'a>),
+    Event(Event<'a>),
+    State(StateSnapshot),
+    /// コマンドの成功応答
+    Ack {
+        message: &'a str,
+    },
+    /// コマンドの失敗応答
+    Error {
+        code: ErrorCode,
+        message: &'a str,
+    },
+}
+
+impl<'a> Message<'a> {
+    /// `buf` にシリアライズし、書き込んだバイト数を返す
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut w = Writer::new(buf);
+        match self {
+            Message::Command(command) => {
+                w.u8(0x01)?;
+                encode_command(&mut w, command)?;
+            }
+            Message::Event(event) => {
+                w.u8(0x02)?;
+                encode_event(&mut w, event)?;
+            }
+            Message::State(state) => {
+                w.u8(0x03)?;
+                w.bool(state.connected)?;
+                w.bool(state.advertising)?;
+                w.bool(state.error)?;
+            }
+            Message::Ack { message } => {
+                w.u8(0x04)?;
+                w.str(message)?;
+            }
+            Message::Error { code, message } => {
+                w.u8(0x05)?;
+                w.u8(match code {
+                    ErrorCode::Malformed => 0x01,
+                    ErrorCode::InvalidArgument => 0x02,
+                    ErrorCode::InvalidState => 0x03,
+                    ErrorCode::Internal => 0x04,
+                })?;
+                w.str(message)?;
+            }
+        }
+        Ok(w.position())
+    }
+
+    /// `payload` 全体を1つのメッセージとして復元する
+    pub fn decode(payload: &'a [u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(payload);
+        let message = match r.u8()? {
+            0x01 => Message::Command(decode_command(&mut r)?),
+            0x02 => Message::Event(decode_event(&mut r)?),
+            0x03 => Message::State(StateSnapshot {
+                connected: r.bool()?,
+                advertising: r.bool()?,
+                error: r.bool()?,
+            }),
+            0x04 => Message::Ack { message: r.str()? },
+            0x05 => {
+                let code = match r.u8()? {
+                    0x01 => ErrorCode::Malformed,
+                    0x02 => ErrorCode::InvalidArgument,
+                    0x03 => ErrorCode::InvalidState,
+                    0x04 => ErrorCode::Internal,
+                    tag => {
+                        return Err(Error::UnknownTag {
+                            kind: "error code",
+                            tag,
+                        })
+                    }
+                };
+                Message::Error {
+                    code,
+                    message: r.str()?,
+                }
+            }
+            tag => {
+                return Err(Error::UnknownTag {
+                    kind: "message",
+                    tag,
+                })
+            }
+        };
+        r.finish()?;
+        Ok(message)
+    }
+}
+
+fn encode_command(w: &mut Writer, command: &Command) -> Result<(), Error> {
+    match command {
+        Command::SetLed { led, mode, color } => {
+            w.u8(0x01)?;
+            w.str(led)?;
+            encode_led_mode(w, mode)?;
+            match color {
+                Some(rgb) => {
+                    w.u8(1)?;
+                    w.bytes(rgb)?;
+                }
+                None => w.u8(0)?,
+            }
+        }
+        Command::StopAdvertise => w.u8(0x02)?,
+        Command::QueryState => w.u8(0x03)?,
+        Command::Reboot => w.u8(0x04)?,
+    }
+    Ok(())
+}
+
+fn decode_command<'a>(r: &mut Reader<'a>) -> Result<Command<'a>, Error> {
+    Ok(match r.u8()? {
+        0x01 => {
+            let led = r.str()?;
+            let mode = decode_led_mode(r)?;
+            let color = match r.u8()? {
+                0 => None,
+                1 => Some([r.u8()?, r.u8()?, r.u8()?]),
+                tag => return Err(Error::UnknownTag { kind: "color", tag }),
+            };
+            Command::SetLed { led, mode, color }
+        }
+        0x02 => Command::StopAdvertise,
+        0x03 => Command::QueryState,
+        0x04 => Command::Reboot,
+        tag => {
+            return Err(Error::UnknownTag {
+                kind: "command",
+                tag,
+            })
+        }
+    })
+}
+
+fn encode_led_mode(w: &mut Writer, mode: &LedMode) -> Result<(), Error> {
+    match mode {
+        LedMode::On => w.u8(0x01),
+        LedMode::Off => w.u8(0x02),
+        LedMode::Level(level) => {
+            w.u8(0x03)?;
+            w.u8(*level)
+        }
+        LedMode::Blink {
+            on_ms,
+            off_ms,
+            count,
+        } => {
+            w.u8(0x04)?;
+            w.u32(*on_ms)?;
+            w.u32(*off_ms)?;
+            w.u16(*count)
+        }
+        LedMode::Fade { level, duration_ms } => {
+            w.u8(0x05)?;
+            w.u8(*level)?;
+            w.u32(*duration_ms)
+        }
+        LedMode::Breathe { period_ms } => {
+            w.u8(0x06)?;
+            w.u32(*period_ms)
+        }
+        LedMode::Preset(name) => {
+            w.u8(0x07)?;
+            w.str(name)
+        }
+        LedMode::Pattern => w.u8(0x08),
+        LedMode::Release => w.u8(0x09),
+    }
+}
+
+fn decode_led_mode<'a>(r: &mut Reader<'a>) -> Result<LedMode<'a>, Error> {
+    Ok(match r.u8()? {
+        0x01 => LedMode::On,
+        0x02 => LedMode::Off,
+        0x03 => LedMode::Level(r.u8()?),
+        0x04 => LedMode::Blink {
+            on_ms: r.u32()?,
+            off_ms: r.u32()?,
+            count: r.u16()?,
+        },
+        0x05 => LedMode::Fade {
+            level: r.u8()?,
+            duration_ms: r.u32()?,
+        },
+        0x06 => LedMode::Breathe {
+            period_ms: r.u32()?,
+        },
+        0x07 => LedMode::Preset(r.str()?),
+        0x08 => LedMode::Pattern,
+        0x09 => LedMode::Release,
+        tag => {
+            return Err(Error::UnknownTag {
+                kind: "LED mode",
+                tag,
+            })
+        }
+    })
+}
+
+fn encode_event(w: &mut Writer, event: &Event) -> Result<(), Error> {
+    match event {
+        Event::Button { button, gesture } => {
+            w.u8(0x01)?;
+            w.str(button)?;
+            match gesture {
+                ButtonGesture::Short => w.u8(0x01)?,
+                ButtonGesture::Double => w.u8(0x02)?,
+                ButtonGesture::Triple => w.u8(0x03)?,
+                ButtonGesture::Long => w.u8(0x04)?,
+                ButtonGesture::VeryLong => w.u8(0x05)?,
+                ButtonGesture::Released { held_ms } => {
+                    w.u8(0x06)?;
+                    w.u32(*held_ms)?;
+                }
+            }
+        }
+        Event::Chord { buttons, gesture } => {
+            w.u8(0x02)?;
+            w.u8(*buttons)?;
+            match gesture {
+                ChordGesture::Pressed => w.u8(0x01)?,
+                ChordGesture::Long => w.u8(0x02)?,
+                ChordGesture::Released { held_ms } => {
+                    w.u8(0x03)?;
+                    w.u32(*held_ms)?;
+                }
+            }
+        }
+        Event::Led { led, source, mode } => {
+            w.u8(0x03)?;
+            w.str(led)?;
+            match source {
+                Some(source) => {
+                    w.u8(1)?;
+                    w.str(source)?;
+                }
+                None => w.u8(0)?,
+            }
+            encode_led_mode(w, mode)?;
+        }
+    }
+    Ok(())
+}
+
+fn decode_event<'a>(r: &mut Reader<'a>) -> Result<Event<'a>, Error> {
+    Ok(match r.u8()? {
+        0x01 => {
+            let button = r.str()?;
+            let gesture = match r.u8()? {
+                0x01 => ButtonGesture::Short,
+                0x02 => ButtonGesture::Double,
+                0x03 => ButtonGesture::Triple,
+                0x04 => ButtonGesture::Long,
+                0x05 => ButtonGesture::VeryLong,
+                0x06 => ButtonGesture::Released { held_ms: r.u32()? },
+                tag => {
+                    return Err(Error::UnknownTag {
+                        kind: "button gesture",
+                        tag,
+                    })
+                }
+            };
+            Event::Button { button, gesture }
+        }
+        0x02 => {
+            let buttons = r.u8()?;
+            let gesture = match r.u8()? {
+                0x01 => ChordGesture::Pressed,
+                0x02 => ChordGesture::Long,
+                0x03 => ChordGesture::Released { held_ms: r.u32()? },
+                tag => {
+                    return Err(Error::UnknownTag {
+                        kind: "chord gesture",
+                        tag,
+                    })
+                }
+            };
+            Event::Chord { buttons, gesture }
+        }
+        0x03 => {
+            let led = r.str()?;
+            let source = match r.u8()? {
+                0 => None,
+                1 => Some(r.str()?),
+                tag => {
+                    return Err(Error::UnknownTag {
+                        kind: "LED source",
+                        tag,
+                    })
+                }
+            };
+            Event::Led {
+                led,
+                source,
+                mode: decode_led_mode(r)?,
+            }
+        }
+        tag => return Err(Error::UnknownTag { kind: "event", tag }),
+    })
+}
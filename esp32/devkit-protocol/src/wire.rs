@@ -0,0 +1,108 @@
+//! 固定長整数（リトルエンディアン）と長さ付き文字列の読み書き
+
+use crate::error::Error;
+
+pub struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// 書き込んだバイト数
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn bytes(&mut self, data: &[u8]) -> Result<(), Error> {
+        let end = self.pos + data.len();
+        self.buf
+            .get_mut(self.pos..end)
+            .ok_or(Error::BufferTooSmall)?
+            .copy_from_slice(data);
+        self.pos = end;
+        Ok(())
+    }
+
+    pub fn u8(&mut self, value: u8) -> Result<(), Error> {
+        self.bytes(&[value])
+    }
+
+    pub fn u16(&mut self, value: u16) -> Result<(), Error> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u32(&mut self, value: u32) -> Result<(), Error> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn bool(&mut self, value: bool) -> Result<(), Error> {
+        self.u8(value as u8)
+    }
+
+    /// 長さ（u8）+ UTF-8 バイト列
+    pub fn str(&mut self, value: &str) -> Result<(), Error> {
+        let len = u8::try_from(value.len()).map_err(|_| Error::StringTooLong)?;
+        self.u8(len)?;
+        self.bytes(value.as_bytes())
+    }
+}
+
+pub struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// 全て読み終えたか確認する
+    pub fn finish(&self) -> Result<(), Error> {
+        if self.pos == self.buf.len() {
+            Ok(())
+        } else {
+            Err(Error::TrailingBytes)
+        }
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self.pos.checked_add(len).ok_or(Error::UnexpectedEnd)?;
+        let data = self.buf.get(self.pos..end).ok_or(Error::UnexpectedEnd)?;
+        self.pos = end;
+        Ok(data)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, Error> {
+        let mut raw = [0; 2];
+        raw.copy_from_slice(self.bytes(2)?);
+        Ok(u16::from_le_bytes(raw))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        let mut raw = [0; 4];
+        raw.copy_from_slice(self.bytes(4)?);
+        Ok(u32::from_le_bytes(raw))
+    }
+
+    pub fn bool(&mut self) -> Result<bool, Error> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(Error::UnknownTag { kind: "bool", tag }),
+        }
+    }
+
+    pub fn str(&mut self) -> Result<&'a str, Error> {
+        let len = self.u8()? as usize;
+        core::str::from_utf8(self.bytes(len)?).map_err(|_| Error::InvalidUtf8)
+    }
+}
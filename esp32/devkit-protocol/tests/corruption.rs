@@ -0,0 +1,129 @@
+//! CRC・magic・長さが壊れたフレームを受け付けないこと
+
+use devkit_protocol::{crc::crc16, Error, Fragmenter, Reassembler, MAGIC, PROTOCOL_VERSION};
+
+fn frame(payload: &[u8]) -> Vec<u8> {
+    let mut fragmenter = Fragmenter::new(payload, 7, 64).unwrap();
+    let mut buf = [0u8; 64];
+    let len = fragmenter.next_frame(&mut buf).unwrap().unwrap();
+    buf[..len].to_vec()
+}
+
+/// ヘッダを書き換えた後に CRC を付け直す（CRC 以外の検査を確かめるため）
+fn fix_crc(frame: &mut [u8]) {
+    let body = frame.len() - 2;
+    let crc = crc16(&frame[..body]);
+    frame[body..].copy_from_slice(&crc.to_le_bytes());
+}
+
+fn push(frame: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    Reassembler::<256>::new()
+        .push(frame)
+        .map(|p| p.map(<[u8]>::to_vec))
+}
+
+#[test]
+fn crc_matches_reference_vector() {
+    // CRC-16/CCITT-FALSE の check 値
+    assert_eq!(crc16(b"123456789"), 0x29B1);
+    assert_eq!(crc16(&[]), 0xFFFF);
+}
+
+#[test]
+fn valid_frame_is_accepted() {
+    let good = frame(b"hello");
+    assert_eq!(good[0], MAGIC);
+    assert_eq!(good[1], PROTOCOL_VERSION);
+    assert_eq!(push(&good), Ok(Some(b"hello".to_vec())));
+}
+
+#[test]
+fn every_single_bit_flip_is_rejected() {
+    let good = frame(b"hello, world");
+    for byte in 0..good.len() {
+        for bit in 0..8 {
+            let mut bad = good.clone();
+            bad[byte] ^= 1 << bit;
+            assert!(push(&bad).is_err(), "flip byte {byte} bit {bit}");
+        }
+    }
+}
+
+#[test]
+fn payload_corruption_is_a_crc_mismatch() {
+    let mut bad = frame(b"hello");
+    bad[8] ^= 0xFF;
+    assert!(matches!(push(&bad), Err(Error::CrcMismatch { .. })));
+}
+
+#[test]
+fn crc_corruption_is_a_crc_mismatch() {
+    let mut bad = frame(b"hello");
+    let last = bad.len() - 1;
+    bad[last] ^= 0x01;
+    assert!(matches!(push(&bad), Err(Error::CrcMismatch { .. })));
+}
+
+#[test]
+fn bad_magic_is_rejected() {
+    let mut bad = frame(b"hello");
+    bad[0] = b'l';
+    fix_crc(&mut bad);
+    assert_eq!(push(&bad), Err(Error::BadMagic));
+}
+
+#[test]
+fn unknown_version_is_rejected() {
+    let mut bad = frame(b"hello");
+    bad[1] = PROTOCOL_VERSION + 1;
+    fix_crc(&mut bad);
+    assert_eq!(
+        push(&bad),
+        Err(Error::UnsupportedVersion(PROTOCOL_VERSION + 1))
+    );
+}
+
+#[test]
+fn wrong_length_field_is_rejected() {
+    for len in [0u16, 4, 6, 255, u16::MAX] {
+        let mut bad = frame(b"hello");
+        bad[5..7].copy_from_slice(&len.to_le_bytes());
+        fix_crc(&mut bad);
+        assert_eq!(push(&bad), Err(Error::BadLength), "len {len}");
+    }
+}
+
+#[test]
+fn truncated_frame_is_rejected() {
+    let good = frame(b"hello");
+    for cut in 0..good.len() {
+        assert!(push(&good[..cut]).is_err(), "cut {cut}");
+    }
+}
+
+#[test]
+fn fragment_index_out_of_range_is_rejected() {
+    let mut bad = frame(b"hello");
+    // frag_index 1 / frag_count 1
+    bad[3] = 1;
+    fix_crc(&mut bad);
+    assert_eq!(push(&bad), Err(Error::UnexpectedFragment));
+}
+
+#[test]
+fn error_discards_the_partial_message() {
+    let mut fragmenter = Fragmenter::new(&[0u8; 30], 3, 20).unwrap();
+    let mut buf = [0u8; 20];
+    let len = fragmenter.next_frame(&mut buf).unwrap().unwrap();
+    let first = buf[..len].to_vec();
+    let len = fragmenter.next_frame(&mut buf).unwrap().unwrap();
+    let mut second = buf[..len].to_vec();
+    second[len - 1] ^= 0xFF;
+
+    let mut reassembler = Reassembler::<256>::new();
+    assert_eq!(reassembler.push(&first), Ok(None));
+    assert!(reassembler.push(&second).is_err());
+    // 正しい2つ目が届いても、組み立て中のメッセージは破棄済み
+    second[len - 1] ^= 0xFF;
+    assert_eq!(reassembler.push(&second), Err(Error::UnexpectedFragment));
+}
@@ -0,0 +1,150 @@
+//! `Fragmenter` で分割したフレームを `Reassembler` で元のペイロードに戻せること
+
+use devkit_protocol::{Error, Fragmenter, Reassembler, FRAME_OVERHEAD};
+
+/// BLE で一般的な ATT MTU（最小、iOS の既定、最大）
+const MTUS: [usize; 3] = [23, 185, 517];
+
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + 3) as u8).collect()
+}
+
+fn fragment(payload: &[u8], msg_id: u8, max_frame_len: usize) -> Vec<Vec<u8>> {
+    let mut fragmenter = Fragmenter::new(payload, msg_id, max_frame_len).unwrap();
+    let mut frames = Vec::new();
+    let mut buf = vec![0u8; max_frame_len];
+    while let Some(len) = fragmenter.next_frame(&mut buf) {
+        frames.push(buf[..len.unwrap()].to_vec());
+    }
+    assert_eq!(frames.len(), fragmenter.fragment_count() as usize);
+    frames
+}
+
+fn reassemble<const N: usize>(
+    reassembler: &mut Reassembler<N>,
+    frames: &[Vec<u8>],
+) -> Option<Vec<u8>> {
+    let (last, rest) = frames.split_last().unwrap();
+    for frame in rest {
+        assert_eq!(reassembler.push(frame), Ok(None));
+    }
+    reassembler.push(last).unwrap().map(<[u8]>::to_vec)
+}
+
+#[test]
+fn round_trip_at_common_mtus() {
+    for mtu in MTUS {
+        // Notify/Write で送れる最大長
+        let max_frame_len = mtu - 3;
+        let chunk = max_frame_len - FRAME_OVERHEAD;
+        for len in [0, 1, chunk - 1, chunk, chunk + 1, 3 * chunk, 2000] {
+            let data = payload(len);
+            let frames = fragment(&data, 42, max_frame_len);
+            assert_eq!(
+                frames.len(),
+                len.div_ceil(chunk).max(1),
+                "mtu {mtu} len {len}"
+            );
+            assert!(frames.iter().all(|f| f.len() <= max_frame_len));
+            let mut reassembler = Reassembler::<2048>::new();
+            assert_eq!(
+                reassemble(&mut reassembler, &frames),
+                Some(data),
+                "mtu {mtu} len {len}"
+            );
+        }
+    }
+}
+
+#[test]
+fn consecutive_messages_reuse_the_reassembler() {
+    let mut reassembler = Reassembler::<512>::new();
+    for (msg_id, len) in [(0u8, 100), (1, 5), (2, 300)] {
+        let data = payload(len);
+        let frames = fragment(&data, msg_id, 20);
+        assert_eq!(reassemble(&mut reassembler, &frames), Some(data));
+    }
+}
+
+#[test]
+fn missing_fragment_is_detected() {
+    let frames = fragment(&payload(100), 1, 20);
+    let mut reassembler = Reassembler::<512>::new();
+    assert_eq!(reassembler.push(&frames[0]), Ok(None));
+    assert_eq!(reassembler.push(&frames[2]), Err(Error::UnexpectedFragment));
+    // 破棄した後は次のメッセージを受け付ける
+    let data = payload(30);
+    assert_eq!(
+        reassemble(&mut reassembler, &fragment(&data, 2, 20)),
+        Some(data)
+    );
+}
+
+#[test]
+fn fragment_of_another_message_is_detected() {
+    let first = fragment(&payload(100), 1, 20);
+    let second = fragment(&payload(100), 2, 20);
+    let mut reassembler = Reassembler::<512>::new();
+    assert_eq!(reassembler.push(&first[0]), Ok(None));
+    assert_eq!(reassembler.push(&second[1]), Err(Error::UnexpectedFragment));
+}
+
+#[test]
+fn new_first_fragment_restarts_the_message() {
+    let abandoned = fragment(&payload(100), 1, 20);
+    let data = payload(50);
+    let frames = fragment(&data, 2, 20);
+    let mut reassembler = Reassembler::<512>::new();
+    assert_eq!(reassembler.push(&abandoned[0]), Ok(None));
+    assert_eq!(reassemble(&mut reassembler, &frames), Some(data));
+}
+
+#[test]
+fn payload_larger_than_buffer_is_rejected() {
+    let frames = fragment(&payload(100), 1, 20);
+    let mut reassembler = Reassembler::<64>::new();
+    let result = frames
+        .iter()
+        .map(|frame| reassembler.push(frame).map(|p| p.is_some()))
+        .find(|r| r.is_err());
+    assert_eq!(result, Some(Err(Error::BufferTooSmall)));
+}
+
+#[test]
+fn too_small_mtu_or_too_many_fragments_is_rejected() {
+    assert!(matches!(
+        Fragmenter::new(&[1, 2, 3], 0, FRAME_OVERHEAD),
+        Err(Error::TooManyFragments)
+    ));
+    // 1バイトずつでは 255 分割を超える
+    assert!(matches!(
+        Fragmenter::new(&payload(256), 0, FRAME_OVERHEAD + 1),
+        Err(Error::TooManyFragments)
+    ));
+    assert!(Fragmenter::new(&payload(255), 0, FRAME_OVERHEAD + 1).is_ok());
+}
+
+#[test]
+fn frame_buffer_too_small_is_reported() {
+    let mut fragmenter = Fragmenter::new(&[1, 2, 3], 0, 20).unwrap();
+    let mut buf = [0u8; FRAME_OVERHEAD + 2];
+    assert_eq!(
+        fragmenter.next_frame(&mut buf),
+        Some(Err(Error::BufferTooSmall))
+    );
+}
+
+#[test]
+fn msg_id_of_the_last_frame_is_kept() {
+    let mut reassembler = Reassembler::<256>::new();
+    assert_eq!(reassembler.msg_id(), None);
+
+    let frames = fragment(&payload(100), 42, 20);
+    assert!(reassemble(&mut reassembler, &frames).is_some());
+    assert_eq!(reassembler.msg_id(), Some(42));
+
+    let mut broken = fragment(&payload(10), 43, 64).remove(0);
+    broken[0] ^= 0xFF;
+    assert!(reassembler.push(&broken).is_err());
+    assert_eq!(reassembler.msg_id(), None);
+}
@@ -0,0 +1,169 @@
+//! 任意のバイト列を受け取ってもパニックせず、正しいメッセージは必ず復元できること（proptest）
+
+use devkit_protocol::{
+    ButtonGesture, ChordGesture, Command, ErrorCode, Event, Fragmenter, LedMode, Message,
+    Reassembler, StateSnapshot, MAGIC, PROTOCOL_VERSION,
+};
+use proptest::prelude::*;
+
+fn text() -> impl Strategy<Value = String> {
+    // 長さは u8 に収まるバイト数まで（マルチバイト文字を含む）
+    "[a-z0-9 _あ-ん]{0,60}"
+}
+
+fn led_mode(name: String) -> impl Strategy<Value = LedMode<'static>> {
+    let name: &'static str = Box::leak(name.into_boxed_str());
+    prop_oneof![
+        Just(LedMode::On),
+        Just(LedMode::Off),
+        any::<u8>().prop_map(LedMode::Level),
+        (any::<u32>(), any::<u32>(), any::<u16>()).prop_map(|(on_ms, off_ms, count)| {
+            LedMode::Blink {
+                on_ms,
+                off_ms,
+                count,
+            }
+        }),
+        (any::<u8>(), any::<u32>())
+            .prop_map(|(level, duration_ms)| LedMode::Fade { level, duration_ms }),
+        any::<u32>().prop_map(|period_ms| LedMode::Breathe { period_ms }),
+        Just(LedMode::Preset(name)),
+        Just(LedMode::Pattern),
+        Just(LedMode::Release),
+    ]
+}
+
+fn leak(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
+
+fn message() -> impl Strategy<Value = Message<'static>> {
+    let button_gesture = prop_oneof![
+        Just(ButtonGesture::Short),
+        Just(ButtonGesture::Double),
+        Just(ButtonGesture::Triple),
+        Just(ButtonGesture::Long),
+        Just(ButtonGesture::VeryLong),
+        any::<u32>().prop_map(|held_ms| ButtonGesture::Released { held_ms }),
+    ];
+    let chord_gesture = prop_oneof![
+        Just(ChordGesture::Pressed),
+        Just(ChordGesture::Long),
+        any::<u32>().prop_map(|held_ms| ChordGesture::Released { held_ms }),
+    ];
+    let error_code = prop_oneof![
+        Just(ErrorCode::Malformed),
+        Just(ErrorCode::InvalidArgument),
+        Just(ErrorCode::InvalidState),
+        Just(ErrorCode::Internal),
+    ];
+    prop_oneof![
+        (
+            text(),
+            text().prop_flat_map(led_mode),
+            any::<Option<[u8; 3]>>()
+        )
+            .prop_map(|(led, mode, color)| Message::Command(Command::SetLed {
+                led: leak(led),
+                mode,
+                color,
+            })),
+        Just(Message::Command(Command::StopAdvertise)),
+        Just(Message::Command(Command::QueryState)),
+        Just(Message::Command(Command::Reboot)),
+        (text(), button_gesture).prop_map(|(button, gesture)| Message::Event(Event::Button {
+            button: leak(button),
+            gesture,
+        })),
+        (any::<u8>(), chord_gesture)
+            .prop_map(|(buttons, gesture)| Message::Event(Event::Chord { buttons, gesture })),
+        (
+            text(),
+            proptest::option::of(text()),
+            text().prop_flat_map(led_mode)
+        )
+            .prop_map(|(led, source, mode)| Message::Event(Event::Led {
+                led: leak(led),
+                source: source.map(leak),
+                mode,
+            })),
+        any::<(bool, bool, bool)>().prop_map(|(connected, advertising, error)| {
+            Message::State(StateSnapshot {
+                connected,
+                advertising,
+                error,
+            })
+        }),
+        text().prop_map(|message| Message::Ack {
+            message: leak(message)
+        }),
+        (error_code, text()).prop_map(|(code, message)| Message::Error {
+            code,
+            message: leak(message),
+        }),
+    ]
+}
+
+/// 先頭を正しいヘッダにしたフレーム風のバイト列（CRC 以降の検査まで到達させる）
+fn frame_like() -> impl Strategy<Value = Vec<u8>> {
+    (
+        any::<u8>(),
+        any::<u8>(),
+        any::<u8>(),
+        proptest::collection::vec(any::<u8>(), 0..64),
+    )
+        .prop_map(|(msg_id, index, count, rest)| {
+            let mut frame = vec![MAGIC, PROTOCOL_VERSION, msg_id, index, count];
+            frame.extend(rest);
+            frame
+        })
+}
+
+proptest! {
+    #[test]
+    fn reassembler_never_panics_on_arbitrary_frames(
+        frames in proptest::collection::vec(
+            prop_oneof![proptest::collection::vec(any::<u8>(), 0..600), frame_like()],
+            0..16,
+        )
+    ) {
+        let mut reassembler = Reassembler::<512>::new();
+        for frame in &frames {
+            if let Ok(Some(payload)) = reassembler.push(frame) {
+                prop_assert!(payload.len() <= 512);
+                let _ = Message::decode(payload);
+            }
+        }
+    }
+
+    #[test]
+    fn decode_never_panics_on_arbitrary_payloads(
+        payload in proptest::collection::vec(any::<u8>(), 0..300)
+    ) {
+        let _ = Message::decode(&payload);
+    }
+
+    #[test]
+    fn encoded_messages_survive_fragmentation(
+        message in message(),
+        max_frame_len in 10usize..=514,
+        msg_id in any::<u8>(),
+    ) {
+        let mut payload = [0u8; 1024];
+        let len = message.encode(&mut payload).unwrap();
+
+        let mut fragmenter = Fragmenter::new(&payload[..len], msg_id, max_frame_len).unwrap();
+        let mut reassembler = Reassembler::<1024>::new();
+        let mut frame = vec![0u8; max_frame_len];
+        let mut completed = 0;
+        while let Some(frame_len) = fragmenter.next_frame(&mut frame) {
+            let frame_len = frame_len.unwrap();
+            prop_assert!(frame_len <= max_frame_len);
+            if let Some(payload) = reassembler.push(&frame[..frame_len]).unwrap() {
+                prop_assert_eq!(Message::decode(payload), Ok(message));
+                completed += 1;
+            }
+        }
+        prop_assert_eq!(completed, 1);
+    }
+}
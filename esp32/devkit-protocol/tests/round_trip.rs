@@ -0,0 +1,178 @@
+//! すべてのメッセージが encode → decode で元に戻ること
+
+use devkit_protocol::{
+    ButtonGesture, ChordGesture, Command, ErrorCode, Event, LedMode, Message, StateSnapshot,
+};
+
+fn led_modes() -> Vec<LedMode<'static>> {
+    vec![
+        LedMode::On,
+        LedMode::Off,
+        LedMode::Level(0),
+        LedMode::Level(255),
+        LedMode::Blink {
+            on_ms: 100,
+            off_ms: u32::MAX,
+            count: 0,
+        },
+        LedMode::Blink {
+            on_ms: 1,
+            off_ms: 2,
+            count: u16::MAX,
+        },
+        LedMode::Fade {
+            level: 128,
+            duration_ms: 1500,
+        },
+        LedMode::Breathe { period_ms: 3000 },
+        LedMode::Preset("double_blink"),
+        LedMode::Preset(""),
+        LedMode::Pattern,
+        LedMode::Release,
+    ]
+}
+
+fn messages() -> Vec<Message<'static>> {
+    let mut messages = Vec::new();
+    for mode in led_modes() {
+        for color in [None, Some([0x12, 0x34, 0x56])] {
+            messages.push(Message::Command(Command::SetLed {
+                led: "status",
+                mode,
+                color,
+            }));
+        }
+        for source in [None, Some("remote")] {
+            messages.push(Message::Event(Event::Led {
+                led: "status",
+                source,
+                mode,
+            }));
+        }
+    }
+    messages.extend([
+        Message::Command(Command::StopAdvertise),
+        Message::Command(Command::QueryState),
+        Message::Command(Command::Reboot),
+    ]);
+    for gesture in [
+        ButtonGesture::Short,
+        ButtonGesture::Double,
+        ButtonGesture::Triple,
+        ButtonGesture::Long,
+        ButtonGesture::VeryLong,
+        ButtonGesture::Released { held_ms: 12_345 },
+    ] {
+        messages.push(Message::Event(Event::Button {
+            button: "main",
+            gesture,
+        }));
+    }
+    for gesture in [
+        ChordGesture::Pressed,
+        ChordGesture::Long,
+        ChordGesture::Released { held_ms: 7 },
+    ] {
+        messages.push(Message::Event(Event::Chord {
+            buttons: 0b11,
+            gesture,
+        }));
+    }
+    for bits in 0..8u8 {
+        messages.push(Message::State(StateSnapshot {
+            connected: bits & 1 != 0,
+            advertising: bits & 2 != 0,
+            error: bits & 4 != 0,
+        }));
+    }
+    messages.push(Message::Ack { message: "ok" });
+    messages.push(Message::Ack { message: "" });
+    for code in [
+        ErrorCode::Malformed,
+        ErrorCode::InvalidArgument,
+        ErrorCode::InvalidState,
+        ErrorCode::Internal,
+    ] {
+        messages.push(Message::Error {
+            code,
+            message: "unknown LED: x",
+        });
+    }
+    messages
+}
+
+#[test]
+fn every_message_round_trips() {
+    let mut buf = [0u8; 512];
+    for message in messages() {
+        let len = message.encode(&mut buf).unwrap();
+        assert_eq!(Message::decode(&buf[..len]), Ok(message), "{message:?}");
+    }
+}
+
+#[test]
+fn multibyte_strings_round_trip() {
+    let mut buf = [0u8; 512];
+    let message = Message::Error {
+        code: ErrorCode::InvalidArgument,
+        message: "不明なLED名",
+    };
+    let len = message.encode(&mut buf).unwrap();
+    assert_eq!(Message::decode(&buf[..len]), Ok(message));
+}
+
+#[test]
+fn longest_string_round_trips() {
+    let text = "x".repeat(255);
+    let message = Message::Ack { message: &text };
+    let mut buf = [0u8; 512];
+    let len = message.encode(&mut buf).unwrap();
+    assert_eq!(len, 1 + 1 + 255);
+    assert_eq!(Message::decode(&buf[..len]), Ok(message));
+}
+
+#[test]
+fn string_over_255_bytes_is_rejected() {
+    let text = "x".repeat(256);
+    let mut buf = [0u8; 512];
+    assert_eq!(
+        Message::Ack { message: &text }.encode(&mut buf),
+        Err(devkit_protocol::Error::StringTooLong)
+    );
+}
+
+#[test]
+fn encode_into_short_buffer_fails() {
+    let message = Message::Command(Command::SetLed {
+        led: "status",
+        mode: LedMode::Breathe { period_ms: 1000 },
+        color: Some([1, 2, 3]),
+    });
+    let mut buf = [0u8; 64];
+    let len = message.encode(&mut buf).unwrap();
+    for short in 0..len {
+        assert_eq!(
+            message.encode(&mut buf[..short]),
+            Err(devkit_protocol::Error::BufferTooSmall)
+        );
+    }
+}
+
+#[test]
+fn truncated_or_padded_payload_is_rejected() {
+    let mut buf = [0u8; 512];
+    for message in messages() {
+        let len = message.encode(&mut buf).unwrap();
+        for cut in 0..len {
+            assert!(
+                Message::decode(&buf[..cut]).is_err(),
+                "{message:?} cut {cut}"
+            );
+        }
+        buf[len] = 0;
+        assert_eq!(
+            Message::decode(&buf[..len + 1]),
+            Err(devkit_protocol::Error::TrailingBytes)
+        );
+    }
+}
@@ -1,32 +1,506 @@
-use std::{env, error::Error, fs, path::Path};
+use std::{collections::BTreeMap, env, error::Error, fs, path::Path};
 
+use devkit_core::ble::{adv_status::STATUS_DATA_LEN, beacon::encode_eddystone_url, gatt_client};
+use devkit_core::button::gesture::GestureConfig;
 use serde::Deserialize;
 
 fn main() -> Result<(), Box<dyn Error>> {
     embuild::espidf::sysenv::output();
     generate_pins_config()?;
     generate_ble_config()?;
+    generate_led_patterns_config()?;
+    generate_led_colors_config()?;
     Ok(())
 }
 
 #[derive(Debug, Deserialize)]
 struct PinConfig {
-    led: u8,
-    button: u8,
+    leds: Vec<LedConfig>,
+    #[serde(default = "default_button_mode")]
+    button_mode: String,
+    buttons: Vec<ButtonConfig>,
+    /// 全ボタン共通のジェスチャ判定時間（省略時は `GestureConfig::default()`）
+    #[serde(default)]
+    gestures: GesturesConfig,
+    /// バッテリー電圧の測定（省略時はバッテリーサービスを作らない）
+    #[serde(default)]
+    battery: Option<BatteryConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LedConfig {
+    name: String,
+    pin: u8,
+    #[serde(default = "default_led_driver")]
+    driver: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ButtonConfig {
+    name: String,
+    pin: u8,
+    #[serde(default = "default_button_debounce_ms")]
+    debounce_ms: u32,
+    #[serde(default = "default_button_active_low")]
+    active_low: bool,
+    #[serde(default = "default_button_pull")]
+    pull: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GesturesConfig {
+    #[serde(default = "default_long_press_ms")]
+    long_press_ms: u32,
+    #[serde(default = "default_very_long_press_ms")]
+    very_long_press_ms: u32,
+    #[serde(default = "default_multi_click_gap_ms")]
+    multi_click_gap_ms: u32,
+}
+
+impl Default for GesturesConfig {
+    fn default() -> Self {
+        Self {
+            long_press_ms: default_long_press_ms(),
+            very_long_press_ms: default_very_long_press_ms(),
+            multi_click_gap_ms: default_multi_click_gap_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BatteryConfig {
+    /// ADC1 のピン（32-39）
+    pin: u8,
+    /// 分圧比（バッテリー電圧 / ピン電圧）
+    #[serde(default = "default_battery_divider")]
+    divider: f32,
+    /// 0% とみなす電圧（mV）
+    #[serde(default = "default_battery_empty_mv")]
+    empty_mv: u32,
+    /// 100% とみなす電圧（mV）
+    #[serde(default = "default_battery_full_mv")]
+    full_mv: u32,
+    /// この残量（%）以下でバッテリー低下を表示
+    #[serde(default = "default_battery_low_percent")]
+    low_percent: u8,
+}
+
+fn default_battery_divider() -> f32 {
+    2.0
+}
+
+fn default_battery_empty_mv() -> u32 {
+    3300
+}
+
+fn default_battery_full_mv() -> u32 {
+    4200
+}
+
+fn default_battery_low_percent() -> u8 {
+    15
+}
+
+fn default_led_driver() -> String {
+    "pwm".to_string()
+}
+
+fn default_button_mode() -> String {
+    "interrupt".to_string()
+}
+
+fn default_button_debounce_ms() -> u32 {
+    30
+}
+
+fn default_long_press_ms() -> u32 {
+    GestureConfig::default().long_press_ms
+}
+
+fn default_very_long_press_ms() -> u32 {
+    GestureConfig::default().very_long_press_ms
+}
+
+fn default_multi_click_gap_ms() -> u32 {
+    GestureConfig::default().multi_click_gap_ms
+}
+
+fn default_button_active_low() -> bool {
+    true
+}
+
+fn default_button_pull() -> String {
+    "up".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct LedPatternsConfig {
+    patterns: BTreeMap<String, LedPatternConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LedPatternConfig {
+    steps: Vec<LedPatternStepConfig>,
+    /// 0 は無限に繰り返す
+    #[serde(default)]
+    repeat: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct LedPatternStepConfig {
+    level: u8,
+    duration_ms: u32,
+}
+
+/// 状態ごとの表示色（`[r, g, b]`）
+#[derive(Debug, Deserialize)]
+struct LedColorsConfig {
+    advertising: [u8; 3],
+    connected: [u8; 3],
+    error: [u8; 3],
+    feedback: [u8; 3],
+    #[serde(default = "default_low_battery_color")]
+    low_battery: [u8; 3],
+}
+
+fn default_low_battery_color() -> [u8; 3] {
+    [255, 128, 0]
 }
 
 #[derive(Debug, Deserialize)]
 struct BleConfig {
     service_uuid: String,
     characteristic_uuid: String,
+    #[serde(default = "default_command_characteristic_uuid")]
+    command_characteristic_uuid: String,
+    #[serde(default = "default_response_characteristic_uuid")]
+    response_characteristic_uuid: String,
+    #[serde(default = "default_event_characteristic_uuid")]
+    event_characteristic_uuid: String,
     device_name: String,
+    /// Device Information Service の製造者名
+    #[serde(default = "default_manufacturer")]
+    manufacturer: String,
+    /// Device Information Service のモデル番号
+    #[serde(default = "default_model")]
+    model: String,
+    /// Nordic UART Service のコンソールを有効にするか
+    #[serde(default)]
+    nus_enabled: bool,
+    #[serde(default)]
+    security: BleSecurityConfig,
+    #[serde(default)]
+    advertising: AdvertisingConfig,
+    #[serde(default)]
+    beacon: BeaconConfig,
+    #[serde(default)]
+    connections: ConnectionsConfig,
+    #[serde(default)]
+    reconnect: ReconnectConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReconnectConfig {
+    /// 切断後の再アドバタイズ: "never" | "window" | "bonded_only" | "backoff"
+    #[serde(default = "default_reconnect_policy")]
+    policy: String,
+    /// 1回の再アドバタイズの長さ
+    #[serde(default = "default_reconnect_window_s")]
+    window_s: u32,
+    /// backoff: 最初の待ち時間（窓ごとに倍にする）
+    #[serde(default = "default_reconnect_backoff_initial_s")]
+    backoff_initial_s: u32,
+    /// backoff: 待ち時間の上限
+    #[serde(default = "default_reconnect_backoff_max_s")]
+    backoff_max_s: u32,
+    /// backoff: 窓の回数の上限（0 なら再接続されるまで続ける）
+    #[serde(default)]
+    max_attempts: u32,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            policy: default_reconnect_policy(),
+            window_s: default_reconnect_window_s(),
+            backoff_initial_s: default_reconnect_backoff_initial_s(),
+            backoff_max_s: default_reconnect_backoff_max_s(),
+            max_attempts: 0,
+        }
+    }
+}
+
+fn default_reconnect_policy() -> String {
+    "never".to_string()
+}
+
+fn default_reconnect_window_s() -> u32 {
+    30
+}
+
+fn default_reconnect_backoff_initial_s() -> u32 {
+    5
+}
+
+fn default_reconnect_backoff_max_s() -> u32 {
+    300
+}
+
+#[derive(Debug, Deserialize)]
+struct ConnectionsConfig {
+    /// 同時に接続できるセントラルの数
+    #[serde(default = "default_max_connections")]
+    max: u8,
+    /// 上限に達するまで接続中もアドバタイズを続けるか
+    #[serde(default)]
+    keep_advertising: bool,
+}
+
+impl Default for ConnectionsConfig {
+    fn default() -> Self {
+        Self {
+            max: default_max_connections(),
+            keep_advertising: false,
+        }
+    }
+}
+
+fn default_max_connections() -> u8 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+struct BeaconConfig {
+    /// 起動時のモード: "off" | "ibeacon" | "eddystone"
+    #[serde(default = "default_beacon_mode")]
+    mode: String,
+    /// ペアリング用のアドバタイズ中もビーコンと交互に送信するか
+    #[serde(default)]
+    interleave: bool,
+    /// 送信内容を切り替える間隔
+    #[serde(default = "default_beacon_slot_ms")]
+    slot_ms: u32,
+    /// ビーコンのアドバタイズ間隔
+    #[serde(default = "default_beacon_interval_ms")]
+    interval_ms: u32,
+    #[serde(default)]
+    ibeacon: Option<IBeaconConfig>,
+    #[serde(default)]
+    eddystone: Option<EddystoneConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IBeaconConfig {
+    uuid: String,
+    #[serde(default)]
+    major: u16,
+    #[serde(default)]
+    minor: u16,
+    /// 1m での RSSI（dBm）
+    #[serde(default = "default_ibeacon_measured_power")]
+    measured_power: i8,
+}
+
+#[derive(Debug, Deserialize)]
+struct EddystoneConfig {
+    /// 送信する frame: "uid" | "url" | "tlm"
+    frames: Vec<String>,
+    /// 0m での RSSI（dBm）
+    #[serde(default = "default_eddystone_tx_power")]
+    tx_power: i8,
+    /// UID の Namespace（16進 20 桁）
+    #[serde(default)]
+    namespace: String,
+    /// UID の Instance（16進 12 桁）
+    #[serde(default)]
+    instance: String,
+    #[serde(default)]
+    url: String,
+}
+
+impl Default for BeaconConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_beacon_mode(),
+            interleave: false,
+            slot_ms: default_beacon_slot_ms(),
+            interval_ms: default_beacon_interval_ms(),
+            ibeacon: None,
+            eddystone: None,
+        }
+    }
+}
+
+fn default_beacon_mode() -> String {
+    "off".to_string()
+}
+
+fn default_beacon_slot_ms() -> u32 {
+    1000
+}
+
+fn default_beacon_interval_ms() -> u32 {
+    500
+}
+
+fn default_ibeacon_measured_power() -> i8 {
+    -59
+}
+
+fn default_eddystone_tx_power() -> i8 {
+    -20
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvertisingConfig {
+    #[serde(default = "default_adv_interval_min_ms")]
+    interval_min_ms: u32,
+    #[serde(default = "default_adv_interval_max_ms")]
+    interval_max_ms: u32,
+    /// 送信出力（dBm）
+    #[serde(default)]
+    tx_power_dbm: i8,
+    /// false ならビーコンのように接続を受け付けない
+    #[serde(default = "default_adv_connectable")]
+    connectable: bool,
+    /// GAP Appearance（0 は Unknown）
+    #[serde(default)]
+    appearance: u16,
+    #[serde(default)]
+    manufacturer_data: Option<ManufacturerDataConfig>,
+    /// 現在の状態を載せるメーカー固有データ（項目名 "status"）
+    #[serde(default)]
+    status: Option<AdvStatusConfig>,
+    /// アドバタイズパケットに載せる項目
+    #[serde(default = "default_adv_data")]
+    data: Vec<String>,
+    /// スキャンレスポンスに載せる項目（空ならスキャンレスポンスを返さない）
+    #[serde(default = "default_adv_scan_response")]
+    scan_response: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManufacturerDataConfig {
+    /// Bluetooth SIG の Company Identifier（0xFFFF はテスト用）
+    company_id: u16,
+    /// 16進文字列
+    #[serde(default)]
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvStatusConfig {
+    company_id: u16,
+    /// 状態を反映する周期
+    #[serde(default = "default_adv_status_interval_ms")]
+    interval_ms: u32,
+}
+
+impl Default for AdvertisingConfig {
+    fn default() -> Self {
+        Self {
+            interval_min_ms: default_adv_interval_min_ms(),
+            interval_max_ms: default_adv_interval_max_ms(),
+            tx_power_dbm: 0,
+            connectable: default_adv_connectable(),
+            appearance: 0,
+            manufacturer_data: None,
+            status: None,
+            data: default_adv_data(),
+            scan_response: default_adv_scan_response(),
+        }
+    }
+}
+
+fn default_adv_interval_min_ms() -> u32 {
+    100
+}
+
+fn default_adv_interval_max_ms() -> u32 {
+    150
+}
+
+fn default_adv_connectable() -> bool {
+    true
+}
+
+fn default_adv_status_interval_ms() -> u32 {
+    5000
+}
+
+fn default_adv_data() -> Vec<String> {
+    vec!["service_uuid".to_string()]
+}
+
+fn default_adv_scan_response() -> Vec<String> {
+    vec!["name".to_string()]
+}
+
+#[derive(Debug, Deserialize)]
+struct BleSecurityConfig {
+    /// "none" | "just_works" | "passkey_display" | "passkey_entry"
+    #[serde(default = "default_security_mode")]
+    mode: String,
+    /// ペアリング情報を NVS に保存して再接続時に再利用するか
+    #[serde(default = "default_security_bonding")]
+    bonding: bool,
+}
+
+impl Default for BleSecurityConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_security_mode(),
+            bonding: default_security_bonding(),
+        }
+    }
+}
+
+fn default_manufacturer() -> String {
+    "Espressif".to_string()
+}
+
+fn default_model() -> String {
+    "ESP32-DevKitC-V1".to_string()
+}
+
+fn default_security_mode() -> String {
+    "none".to_string()
+}
+
+fn default_security_bonding() -> bool {
+    true
+}
+
+fn default_command_characteristic_uuid() -> String {
+    "b5ed2175-9e9d-41f1-8960-bbd06b2a9f2e".to_string()
+}
+
+fn default_response_characteristic_uuid() -> String {
+    "a68b0180-a90f-4f06-ab0e-247336ff0cec".to_string()
+}
+
+fn default_event_characteristic_uuid() -> String {
+    "c8778ee4-75cc-4b50-9736-8f7d4937d38d".to_string()
 }
 
 fn generate_pins_config() -> Result<(), Box<dyn Error>> {
     // デフォルト値（従来のハードコードと同じ）
     let default = PinConfig {
-        led: 12,
-        button: 14,
+        leds: vec![LedConfig {
+            name: "status".to_string(),
+            pin: 12,
+            driver: default_led_driver(),
+        }],
+        button_mode: default_button_mode(),
+        buttons: vec![ButtonConfig {
+            name: "main".to_string(),
+            pin: 14,
+            debounce_ms: default_button_debounce_ms(),
+            active_low: default_button_active_low(),
+            pull: default_button_pull(),
+        }],
+        gestures: GesturesConfig::default(),
+        battery: None,
     };
 
     let config_path = Path::new("config/pins.json");
@@ -37,8 +511,8 @@ fn generate_pins_config() -> Result<(), Box<dyn Error>> {
                 "Warning: failed to read pin configuration from {}: {}. Falling back to defaults (led={}, button={}).",
                 config_path.display(),
                 e,
-                default.led,
-                default.button,
+                default.leds[0].pin,
+                default.buttons[0].pin,
             );
             default
         }
@@ -58,25 +532,263 @@ fn generate_pins_config() -> Result<(), Box<dyn Error>> {
         }
     }
 
-    if cfg.led == cfg.button {
-        return Err(format!("led and button pins must differ: {}", cfg.led).into());
+    // 名前はコマンドのルーティングに使うため識別子的な文字列に限定
+    fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+
+    // LED数は LedId(u8) と割り当て可能なチャネル数の範囲に制限
+    if cfg.leds.is_empty() || cfg.leds.len() > 8 {
+        return Err(format!("leds must contain 1 to 8 entries: {}", cfg.leds.len()).into());
+    }
+
+    let mut used_pins = Vec::new();
+    let mut led_entries = String::new();
+    let mut led_fields = String::new();
+    let (mut pwm_count, mut ws2812_count) = (0, 0);
+
+    for (i, led) in cfg.leds.iter().enumerate() {
+        pin_type(led.pin)?;
+
+        if used_pins.contains(&led.pin) {
+            return Err(format!(
+                "LED '{}' uses pin {} which is already assigned",
+                led.name, led.pin
+            )
+            .into());
+        }
+        used_pins.push(led.pin);
+
+        if !is_valid_name(&led.name) {
+            return Err(format!("invalid LED name: {:?}", led.name).into());
+        }
+        if cfg.leds[..i].iter().any(|l| l.name == led.name) {
+            return Err(format!("duplicate LED name: {}", led.name).into());
+        }
+
+        // LED駆動方式（PWM非対応の用途では GPIO のオン/オフ）
+        let driver = match led.driver.as_str() {
+            "gpio" => "LedDriverKind::Gpio",
+            "pwm" => {
+                pwm_count += 1;
+                "LedDriverKind::Pwm"
+            }
+            "ws2812" => {
+                ws2812_count += 1;
+                "LedDriverKind::Ws2812"
+            }
+            other => {
+                return Err(format!(
+                "LED '{}': unsupported driver: {other} (expected \"gpio\", \"pwm\" or \"ws2812\")",
+                led.name
+            )
+                .into())
+            }
+        };
+
+        led_entries.push_str(&format!(
+            "    LedPinConfig {{ name: \"{name}\", driver: {driver} }},\n",
+            name = led.name,
+        ));
+        led_fields.push_str(&format!(
+            "        esp_idf_hal::gpio::OutputPin::downgrade_output(pins.gpio{}),\n",
+            led.pin
+        ));
+    }
+
+    // LEDC / RMT は先頭から4チャネルまでを LED に割り当てる
+    if pwm_count > 4 || ws2812_count > 4 {
+        return Err(format!(
+            "at most 4 pwm and 4 ws2812 LEDs are supported: pwm={pwm_count}, ws2812={ws2812_count}"
+        )
+        .into());
     }
 
-    let led_ty = pin_type(cfg.led)?;
-    let button_ty = pin_type(cfg.button)?;
+    // ボタン入力方式（割り込み or ポーリング）
+    let button_mode = match cfg.button_mode.as_str() {
+        "interrupt" => "ButtonInputMode::Interrupt",
+        "polling" => "ButtonInputMode::Polling",
+        other => {
+            return Err(format!(
+                "unsupported button_mode: {other} (expected \"interrupt\" or \"polling\")"
+            )
+            .into())
+        }
+    };
+
+    // ボタン数は ButtonMask(u8) で表現できる範囲に制限
+    if cfg.buttons.is_empty() || cfg.buttons.len() > 8 {
+        return Err(format!("buttons must contain 1 to 8 entries: {}", cfg.buttons.len()).into());
+    }
+
+    let mut button_entries = String::new();
+    let mut button_fields = String::new();
+
+    for (i, button) in cfg.buttons.iter().enumerate() {
+        pin_type(button.pin)?;
+
+        if used_pins.contains(&button.pin) {
+            return Err(format!(
+                "button '{}' uses pin {} which is already assigned",
+                button.name, button.pin
+            )
+            .into());
+        }
+        used_pins.push(button.pin);
+
+        if !is_valid_name(&button.name) {
+            return Err(format!("invalid button name: {:?}", button.name).into());
+        }
+        if cfg.buttons[..i].iter().any(|b| b.name == button.name) {
+            return Err(format!("duplicate button name: {}", button.name).into());
+        }
+
+        // デバウンス窓はロングプレス判定より十分短い範囲に制限
+        if button.debounce_ms > 500 {
+            return Err(format!(
+                "button '{}': debounce_ms must be <= 500: {}",
+                button.name, button.debounce_ms
+            )
+            .into());
+        }
+
+        // ボタンのプル設定（none はフローティング＝外付け抵抗前提）
+        let pull = match button.pull.as_str() {
+            "up" => "esp_idf_hal::gpio::Pull::Up",
+            "down" => "esp_idf_hal::gpio::Pull::Down",
+            "none" => "esp_idf_hal::gpio::Pull::Floating",
+            other => {
+                return Err(format!(
+                "button '{}': unsupported pull: {other} (expected \"up\", \"down\" or \"none\")",
+                button.name
+            )
+                .into())
+            }
+        };
+
+        // 非押下時のレベルと押下判定が逆になる組み合わせは配線ミスの可能性が高い
+        match (button.active_low, button.pull.as_str()) {
+            (true, "down") | (false, "up") => eprintln!(
+                "Warning: button '{}' active_low={} with pull={} keeps the button reported as pressed while idle.",
+                button.name, button.active_low, button.pull,
+            ),
+            _ => {}
+        }
+
+        button_entries.push_str(&format!(
+            "    ButtonPinConfig {{ name: \"{name}\", active_low: {active_low}, pull: {pull}, debounce_ms: {debounce_ms} }},\n",
+            name = button.name,
+            active_low = button.active_low,
+            pull = pull,
+            debounce_ms = button.debounce_ms,
+        ));
+        button_fields.push_str(&format!(
+            "        esp_idf_hal::gpio::IOPin::downgrade(pins.gpio{}),\n",
+            button.pin
+        ));
+    }
+
+    // ジェスチャ判定時間は全ボタン共通。リリースがデバウンスで確定する前に連続クリックの待ちが切れないようにする
+    let gestures = &cfg.gestures;
+    let max_debounce_ms = cfg.buttons.iter().map(|b| b.debounce_ms).max().unwrap_or(0);
+    if !(50..=2000).contains(&gestures.multi_click_gap_ms)
+        || gestures.multi_click_gap_ms <= max_debounce_ms
+    {
+        return Err(format!(
+            "gestures.multi_click_gap_ms must be 50-2000 and longer than every debounce_ms ({max_debounce_ms}): {}",
+            gestures.multi_click_gap_ms
+        )
+        .into());
+    }
+    if gestures.long_press_ms <= gestures.multi_click_gap_ms || gestures.long_press_ms > 60000 {
+        return Err(format!(
+            "gestures.long_press_ms must be longer than multi_click_gap_ms ({}) and <= 60000: {}",
+            gestures.multi_click_gap_ms, gestures.long_press_ms
+        )
+        .into());
+    }
+    if gestures.very_long_press_ms <= gestures.long_press_ms || gestures.very_long_press_ms > 120000
+    {
+        return Err(format!(
+            "gestures.very_long_press_ms must be longer than long_press_ms ({}) and <= 120000: {}",
+            gestures.long_press_ms, gestures.very_long_press_ms
+        )
+        .into());
+    }
+
+    // バッテリー電圧は ADC1 で測定（ADC2 は無線と併用できない）
+    let (battery, battery_field) = match &cfg.battery {
+        None => ("None".to_string(), "{ let _ = adc1; Ok(None) }".to_string()),
+        Some(battery) => {
+            if !(32..=39).contains(&battery.pin) {
+                return Err(
+                    format!("battery pin must be an ADC1 pin (32-39): {}", battery.pin).into(),
+                );
+            }
+            if used_pins.contains(&battery.pin) {
+                return Err(
+                    format!("battery uses pin {} which is already assigned", battery.pin).into(),
+                );
+            }
+            if battery.empty_mv >= battery.full_mv {
+                return Err(format!(
+                    "battery empty_mv must be lower than full_mv: {} >= {}",
+                    battery.empty_mv, battery.full_mv
+                )
+                .into());
+            }
+            if battery.divider.is_nan() || battery.divider < 1.0 {
+                return Err(format!("battery divider must be >= 1.0: {}", battery.divider).into());
+            }
+            if battery.low_percent > 100 {
+                return Err(format!(
+                    "battery low_percent must be <= 100: {}",
+                    battery.low_percent
+                )
+                .into());
+            }
+            (
+                format!(
+                    "Some(BatteryPinConfig {{ pin: {pin}, divider: {divider:?}, empty_mv: {empty_mv}, full_mv: {full_mv}, low_percent: {low_percent} }})",
+                    pin = battery.pin,
+                    divider = battery.divider,
+                    empty_mv = battery.empty_mv,
+                    full_mv = battery.full_mv,
+                    low_percent = battery.low_percent,
+                ),
+                format!(
+                    "BATTERY.map(|config| Battery::new(adc1, pins.gpio{}, config)).transpose()",
+                    battery.pin
+                ),
+            )
+        }
+    };
 
     let code = format!(
         "// Auto-generated by build.rs. Do not edit manually.\n\n\
-         pub type LedPinType = {led};\n\
-         pub type ButtonPinType = {button};\n\
-         pub fn split_pins(peripherals: esp_idf_hal::peripherals::Peripherals) -> (LedPinType, ButtonPinType) {{\n\
-             let pins = peripherals.pins;\n\
-             (pins.{led_field}, pins.{button_field})\n\
+         pub const LEDS: [LedPinConfig; {led_count}] = [\n{led_entries}];\n\
+         pub const BUTTON_INPUT_MODE: ButtonInputMode = {button_mode};\n\
+         pub const BUTTONS: [ButtonPinConfig; {button_count}] = [\n{button_entries}];\n\
+         pub const GESTURES: GestureConfig = GestureConfig {{ long_press_ms: {long_press_ms}, very_long_press_ms: {very_long_press_ms}, multi_click_gap_ms: {multi_click_gap_ms} }};\n\
+         pub const BATTERY: Option<BatteryPinConfig> = {battery};\n\
+         pub fn split_pins(pins: esp_idf_hal::gpio::Pins, adc1: esp_idf_hal::adc::ADC1) -> ([esp_idf_hal::gpio::AnyOutputPin; {led_count}], [esp_idf_hal::gpio::AnyIOPin; {button_count}], Result<Option<Battery>>) {{\n\
+             ([\n{led_fields}    ], [\n{button_fields}    ], {battery_field})\n\
          }}\n",
-        led = led_ty,
-        button = button_ty,
-        led_field = format!("gpio{}", cfg.led),
-        button_field = format!("gpio{}", cfg.button),
+        led_count = cfg.leds.len(),
+        led_entries = led_entries,
+        led_fields = led_fields,
+        button_mode = button_mode,
+        button_count = cfg.buttons.len(),
+        button_entries = button_entries,
+        button_fields = button_fields,
+        long_press_ms = gestures.long_press_ms,
+        very_long_press_ms = gestures.very_long_press_ms,
+        multi_click_gap_ms = gestures.multi_click_gap_ms,
+        battery = battery,
+        battery_field = battery_field,
     );
 
     let out_dir = env::var("OUT_DIR")?;
@@ -91,7 +803,18 @@ fn generate_ble_config() -> Result<(), Box<dyn Error>> {
     let default = BleConfig {
         service_uuid: "9b574847-f706-436c-bed7-fc01eb0965c1".to_string(),
         characteristic_uuid: "681285a6-247f-48c6-80ad-68c3dce18585".to_string(),
+        command_characteristic_uuid: default_command_characteristic_uuid(),
+        response_characteristic_uuid: default_response_characteristic_uuid(),
+        event_characteristic_uuid: default_event_characteristic_uuid(),
         device_name: "esp32-devkit-v1".to_string(),
+        manufacturer: default_manufacturer(),
+        model: default_model(),
+        nus_enabled: false,
+        security: BleSecurityConfig::default(),
+        advertising: AdvertisingConfig::default(),
+        beacon: BeaconConfig::default(),
+        connections: ConnectionsConfig::default(),
+        reconnect: ReconnectConfig::default(),
     };
 
     let config_path = Path::new("config/ble.json");
@@ -116,21 +839,568 @@ fn generate_ble_config() -> Result<(), Box<dyn Error>> {
 
     let service_uuid_escaped = escape_rust_string(&cfg.service_uuid);
     let characteristic_uuid_escaped = escape_rust_string(&cfg.characteristic_uuid);
+    let command_characteristic_uuid_escaped = escape_rust_string(&cfg.command_characteristic_uuid);
+    let response_characteristic_uuid_escaped =
+        escape_rust_string(&cfg.response_characteristic_uuid);
+    let event_characteristic_uuid_escaped = escape_rust_string(&cfg.event_characteristic_uuid);
     let device_name_escaped = escape_rust_string(&cfg.device_name);
+    let manufacturer_escaped = escape_rust_string(&cfg.manufacturer);
+    let model_escaped = escape_rust_string(&cfg.model);
+
+    let security_mode = match cfg.security.mode.as_str() {
+        "none" => "BleSecurityMode::None",
+        "just_works" => "BleSecurityMode::JustWorks",
+        "passkey_display" => "BleSecurityMode::PasskeyDisplay",
+        "passkey_entry" => "BleSecurityMode::PasskeyEntry",
+        other => {
+            return Err(format!(
+                "unsupported security.mode: {other} (expected \"none\", \"just_works\", \"passkey_display\" or \"passkey_entry\")"
+            )
+            .into())
+        }
+    };
 
     let code = format!(
         "// Auto-generated by build.rs. Do not edit manually.\n\n\
          pub const BLE_SERVICE_UUID: &str = \"{service_uuid}\";\n\
          pub const BLE_CHARACTERISTIC_UUID: &str = \"{characteristic_uuid}\";\n\
-         pub const BLE_DEVICE_NAME: &str = \"{device_name}\";\n",
+         pub const BLE_COMMAND_CHARACTERISTIC_UUID: &str = \"{command_characteristic_uuid}\";\n\
+         pub const BLE_RESPONSE_CHARACTERISTIC_UUID: &str = \"{response_characteristic_uuid}\";\n\
+         pub const BLE_EVENT_CHARACTERISTIC_UUID: &str = \"{event_characteristic_uuid}\";\n\
+         pub const BLE_DEVICE_NAME: &str = \"{device_name}\";\n\
+         pub const BLE_MANUFACTURER: &str = \"{manufacturer}\";\n\
+         pub const BLE_MODEL: &str = \"{model}\";\n\
+         pub const BLE_NUS_ENABLED: bool = {nus_enabled};\n\
+         pub const BLE_SECURITY: BleSecurity = BleSecurity {{ mode: {security_mode}, bonding: {bonding} }};\n",
         service_uuid = service_uuid_escaped,
         characteristic_uuid = characteristic_uuid_escaped,
+        command_characteristic_uuid = command_characteristic_uuid_escaped,
+        response_characteristic_uuid = response_characteristic_uuid_escaped,
+        event_characteristic_uuid = event_characteristic_uuid_escaped,
         device_name = device_name_escaped,
+        manufacturer = manufacturer_escaped,
+        model = model_escaped,
+        nus_enabled = cfg.nus_enabled,
+        security_mode = security_mode,
+        bonding = cfg.security.bonding,
     );
 
+    let code = code
+        + &advertising_code(&cfg)?
+        + &beacon_code(&cfg.beacon)?
+        + &connections_code(&cfg.connections)?
+        + &reconnect_code(&cfg.reconnect)?;
+
     let out_dir = env::var("OUT_DIR")?;
     fs::write(Path::new(&out_dir).join("ble_gen.rs"), code)?;
 
     println!("cargo:rerun-if-changed=config/ble.json");
     Ok(())
 }
+
+/// 同時接続の設定を検証し、`BLE_CONNECTIONS` の定義を生成する
+fn connections_code(cfg: &ConnectionsConfig) -> Result<String, Box<dyn Error>> {
+    // NimBLE の CONFIG_BT_NIMBLE_MAX_CONNECTIONS の上限と既定値
+    const NIMBLE_MAX_CONNECTIONS: u8 = 9;
+    const NIMBLE_DEFAULT_CONNECTIONS: u8 = 3;
+    // GATT クライアントとしての接続数
+    const MAX_REMOTE_PEERS: u8 = gatt_client::MAX_REMOTE_PEERS as u8;
+
+    if !(1..=NIMBLE_MAX_CONNECTIONS - MAX_REMOTE_PEERS).contains(&cfg.max) {
+        return Err(format!(
+            "connections.max must be 1..={}: {}",
+            NIMBLE_MAX_CONNECTIONS - MAX_REMOTE_PEERS,
+            cfg.max
+        )
+        .into());
+    }
+
+    // NimBLE 側の接続数が足りなければ警告（GATT クライアントの接続も同じ枠を使う）
+    let sdk_connections = fs::read_to_string("sdkconfig.defaults")
+        .ok()
+        .and_then(|data| {
+            data.lines().find_map(|line| {
+                line.trim()
+                    .strip_prefix("CONFIG_BT_NIMBLE_MAX_CONNECTIONS=")
+                    .and_then(|value| value.trim().parse::<u8>().ok())
+            })
+        })
+        .unwrap_or(NIMBLE_DEFAULT_CONNECTIONS);
+    if cfg.max + MAX_REMOTE_PEERS > sdk_connections {
+        eprintln!(
+            "Warning: connections.max ({}) plus GATT client peers ({}) exceeds CONFIG_BT_NIMBLE_MAX_CONNECTIONS ({}); set it in sdkconfig.defaults.",
+            cfg.max, MAX_REMOTE_PEERS, sdk_connections
+        );
+    }
+    println!("cargo:rerun-if-changed=sdkconfig.defaults");
+
+    Ok(format!(
+        "pub const BLE_CONNECTIONS: ConnectionsConfig = ConnectionsConfig {{ max: {}, keep_advertising: {} }};\n",
+        cfg.max, cfg.keep_advertising
+    ))
+}
+
+/// 再接続の設定を検証し、`BLE_RECONNECT` の定義を生成する
+fn reconnect_code(cfg: &ReconnectConfig) -> Result<String, Box<dyn Error>> {
+    // 1時間を超える窓・待ち時間は設定ミスとして扱う
+    const MAX_SECONDS: u32 = 3600;
+
+    let policy = match cfg.policy.as_str() {
+        "never" => "ReconnectPolicy::Never",
+        "window" => "ReconnectPolicy::Window",
+        "bonded_only" => "ReconnectPolicy::BondedOnly",
+        "backoff" => "ReconnectPolicy::Backoff",
+        other => {
+            return Err(format!(
+                "unsupported reconnect.policy: {other} (expected \"never\", \"window\", \"bonded_only\" or \"backoff\")"
+            )
+            .into())
+        }
+    };
+    for (name, seconds) in [
+        ("window_s", cfg.window_s),
+        ("backoff_initial_s", cfg.backoff_initial_s),
+        ("backoff_max_s", cfg.backoff_max_s),
+    ] {
+        if !(1..=MAX_SECONDS).contains(&seconds) {
+            return Err(format!("reconnect.{name} must be 1..={MAX_SECONDS}: {seconds}").into());
+        }
+    }
+    if cfg.backoff_initial_s > cfg.backoff_max_s {
+        return Err(format!(
+            "reconnect.backoff_initial_s must be <= backoff_max_s: {} > {}",
+            cfg.backoff_initial_s, cfg.backoff_max_s
+        )
+        .into());
+    }
+    if cfg.max_attempts != 0 && cfg.policy != "backoff" {
+        eprintln!(
+            "Warning: reconnect.max_attempts is only used by the \"backoff\" policy (policy: {}).",
+            cfg.policy
+        );
+    }
+
+    Ok(format!(
+        "pub const BLE_RECONNECT: ReconnectConfig = ReconnectConfig {{ policy: {policy}, window_ms: {}, backoff_initial_ms: {}, backoff_max_ms: {}, max_attempts: {} }};\n",
+        cfg.window_s * 1000,
+        cfg.backoff_initial_s * 1000,
+        cfg.backoff_max_s * 1000,
+        cfg.max_attempts
+    ))
+}
+
+/// アドバタイズ設定を検証し、`BLE_ADVERTISING` の定義を生成する
+fn advertising_code(cfg: &BleConfig) -> Result<String, Box<dyn Error>> {
+    // AD構造の最大長（レガシーアドバタイズ / スキャンレスポンス）
+    const MAX_PAYLOAD_LEN: usize = 31;
+    // NimBLE が先頭に付ける Flags（長さ + タイプ + 値）
+    const FLAGS_LEN: usize = 3;
+    let adv = &cfg.advertising;
+
+    // BLE 仕様のアドバタイズ間隔の範囲（20ms〜10.24s）
+    for (name, ms) in [
+        ("interval_min_ms", adv.interval_min_ms),
+        ("interval_max_ms", adv.interval_max_ms),
+    ] {
+        if !(20..=10_240).contains(&ms) {
+            return Err(format!("advertising.{name} must be 20..=10240: {ms}").into());
+        }
+    }
+    if adv.interval_min_ms > adv.interval_max_ms {
+        return Err(format!(
+            "advertising.interval_min_ms must be <= interval_max_ms: {} > {}",
+            adv.interval_min_ms, adv.interval_max_ms
+        )
+        .into());
+    }
+
+    // ESP32 で選べる送信出力
+    let tx_power = match adv.tx_power_dbm {
+        -12 => "N12",
+        -9 => "N9",
+        -6 => "N6",
+        -3 => "N3",
+        0 => "N0",
+        3 => "P3",
+        6 => "P6",
+        9 => "P9",
+        other => {
+            return Err(format!(
+                "advertising.tx_power_dbm must be one of -12, -9, -6, -3, 0, 3, 6, 9: {other}"
+            )
+            .into())
+        }
+    };
+
+    // メーカー固有データ（Company ID をリトルエンディアンで先頭に付ける）
+    let manufacturer_data = match &adv.manufacturer_data {
+        None => Vec::new(),
+        Some(m) => {
+            let mut bytes = m.company_id.to_le_bytes().to_vec();
+            bytes.extend(parse_hex(
+                "advertising.manufacturer_data.data",
+                &m.data,
+                None,
+            )?);
+            bytes
+        }
+    };
+
+    // 各項目を AD 構造に変換した時の長さ（長さ 1 + タイプ 1 + 値）
+    let field = |name: &str, target: &str| -> Result<(&'static str, usize), Box<dyn Error>> {
+        Ok(match name {
+            "name" => ("AdvField::Name", 2 + cfg.device_name.len()),
+            "service_uuid" => ("AdvField::ServiceUuid", 2 + 16),
+            "appearance" => ("AdvField::Appearance", 2 + 2),
+            "manufacturer_data" => {
+                if adv.manufacturer_data.is_none() {
+                    return Err(format!(
+                        "advertising.{target} includes manufacturer_data but advertising.manufacturer_data is not set"
+                    )
+                    .into());
+                }
+                ("AdvField::ManufacturerData", 2 + manufacturer_data.len())
+            }
+            "status" => {
+                if adv.status.is_none() {
+                    return Err(format!(
+                        "advertising.{target} includes status but advertising.status is not set"
+                    )
+                    .into());
+                }
+                ("AdvField::Status", 2 + STATUS_DATA_LEN)
+            }
+            other => {
+                return Err(format!(
+                    "advertising.{target}: unsupported field: {other} (expected \"name\", \"service_uuid\", \"appearance\", \"manufacturer_data\" or \"status\")"
+                )
+                .into())
+            }
+        })
+    };
+
+    let mut fields = Vec::new();
+    for (target, names, base_len) in [
+        ("data", &adv.data, FLAGS_LEN),
+        ("scan_response", &adv.scan_response, 0),
+    ] {
+        let mut entries = Vec::new();
+        let mut len = base_len;
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                return Err(format!("advertising.{target}: duplicate field: {name}").into());
+            }
+            // どちらもメーカー固有データ（AD タイプ 0xFF）で、1 パケットに 1 つしか載せられない
+            if name == "status" && names.iter().any(|n| n == "manufacturer_data") {
+                return Err(format!(
+                    "advertising.{target}: status and manufacturer_data cannot be in the same packet"
+                )
+                .into());
+            }
+            let (entry, field_len) = field(name, target)?;
+            entries.push(entry);
+            len += field_len;
+        }
+        // サイズ超過は実行時の set_data 失敗ではなくビルドエラーにする
+        if len > MAX_PAYLOAD_LEN {
+            return Err(format!(
+                "advertising.{target} is {len} bytes (max {MAX_PAYLOAD_LEN}): {names:?}"
+            )
+            .into());
+        }
+        fields.push(entries.join(", "));
+    }
+    if adv.connectable
+        && !adv
+            .data
+            .iter()
+            .chain(&adv.scan_response)
+            .any(|f| f == "service_uuid")
+    {
+        eprintln!("Warning: advertising does not include service_uuid; clients filtering by service will not find the device.");
+    }
+
+    let status = match &adv.status {
+        Some(status) => {
+            if status.interval_ms < 1000 {
+                return Err(format!(
+                    "advertising.status.interval_ms must be >= 1000: {}",
+                    status.interval_ms
+                )
+                .into());
+            }
+            if !adv
+                .data
+                .iter()
+                .chain(&adv.scan_response)
+                .any(|f| f == "status")
+            {
+                eprintln!(
+                    "Warning: advertising.status is set but not included in data or scan_response."
+                );
+            }
+            format!(
+                "Some(AdvStatusConfig {{ company_id: {:#06x}, interval_ms: {} }})",
+                status.company_id, status.interval_ms
+            )
+        }
+        None => "None".to_string(),
+    };
+
+    Ok(format!(
+        "pub const BLE_ADVERTISING: AdvertisingConfig = AdvertisingConfig {{ \
+         interval_min_ms: {interval_min_ms}, interval_max_ms: {interval_max_ms}, \
+         tx_power: esp32_nimble::enums::PowerLevel::{tx_power}, connectable: {connectable}, \
+         appearance: {appearance:#06x}, manufacturer_data: &{manufacturer_data:?}, \
+         status: {status}, data: &[{data}], scan_response: &[{scan_response}] }};\n",
+        interval_min_ms = adv.interval_min_ms,
+        interval_max_ms = adv.interval_max_ms,
+        connectable = adv.connectable,
+        appearance = adv.appearance,
+        data = fields[0],
+        scan_response = fields[1],
+    ))
+}
+
+/// 16進文字列をバイト列にする（`len` が指定されていれば長さも検証する）
+fn parse_hex(name: &str, value: &str, len: Option<usize>) -> Result<Vec<u8>, Box<dyn Error>> {
+    if value.len() % 2 != 0 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("{name} must be hex: {value:?}").into());
+    }
+    let bytes = (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()?;
+    match len {
+        Some(len) if bytes.len() != len => {
+            Err(format!("{name} must be {len} bytes: {value:?}").into())
+        }
+        _ => Ok(bytes),
+    }
+}
+
+/// ビーコン設定を検証し、`BLE_BEACON` の定義を生成する
+fn beacon_code(beacon: &BeaconConfig) -> Result<String, Box<dyn Error>> {
+    let mode = match beacon.mode.as_str() {
+        "off" => "BeaconMode::Off",
+        "ibeacon" if beacon.ibeacon.is_none() => {
+            return Err("beacon.mode is ibeacon but beacon.ibeacon is not set".into())
+        }
+        "ibeacon" => "BeaconMode::IBeacon",
+        "eddystone" if beacon.eddystone.is_none() => {
+            return Err("beacon.mode is eddystone but beacon.eddystone is not set".into())
+        }
+        "eddystone" => "BeaconMode::Eddystone",
+        other => {
+            return Err(format!(
+                "unsupported beacon.mode: {other} (expected \"off\", \"ibeacon\" or \"eddystone\")"
+            )
+            .into())
+        }
+    };
+    if !(20..=10_240).contains(&beacon.interval_ms) {
+        return Err(format!(
+            "beacon.interval_ms must be 20..=10240: {}",
+            beacon.interval_ms
+        )
+        .into());
+    }
+    if beacon.slot_ms < beacon.interval_ms {
+        return Err(format!(
+            "beacon.slot_ms must be >= interval_ms: {} < {}",
+            beacon.slot_ms, beacon.interval_ms
+        )
+        .into());
+    }
+
+    let ibeacon = match &beacon.ibeacon {
+        Some(ibeacon) => {
+            let uuid = parse_hex(
+                "beacon.ibeacon.uuid",
+                &ibeacon.uuid.replace('-', ""),
+                Some(16),
+            )?;
+            format!(
+                "Some(IBeaconConfig {{ uuid: {uuid:?}, major: {}, minor: {}, measured_power: {} }})",
+                ibeacon.major, ibeacon.minor, ibeacon.measured_power
+            )
+        }
+        None => "None".to_string(),
+    };
+
+    let eddystone = match &beacon.eddystone {
+        Some(eddystone) => {
+            if eddystone.frames.is_empty() {
+                return Err("beacon.eddystone.frames must not be empty".into());
+            }
+            let mut frames = Vec::new();
+            for (i, frame) in eddystone.frames.iter().enumerate() {
+                if eddystone.frames[..i].contains(frame) {
+                    return Err(format!("beacon.eddystone.frames: duplicate frame: {frame}").into());
+                }
+                frames.push(match frame.as_str() {
+                    "uid" => "EddystoneFrame::Uid",
+                    "url" => "EddystoneFrame::Url",
+                    "tlm" => "EddystoneFrame::Tlm",
+                    other => {
+                        return Err(format!(
+                            "beacon.eddystone.frames: unsupported frame: {other} (expected \"uid\", \"url\" or \"tlm\")"
+                        )
+                        .into())
+                    }
+                });
+            }
+            let has = |frame: &str| eddystone.frames.iter().any(|f| f == frame);
+            let (namespace, instance) = if has("uid") {
+                (
+                    parse_hex("beacon.eddystone.namespace", &eddystone.namespace, Some(10))?,
+                    parse_hex("beacon.eddystone.instance", &eddystone.instance, Some(6))?,
+                )
+            } else {
+                (vec![0; 10], vec![0; 6])
+            };
+            let url = if has("url") {
+                encode_eddystone_url(&eddystone.url)
+                    .map_err(|e| format!("beacon.eddystone.url {e}: {}", eddystone.url))?
+            } else {
+                Vec::new()
+            };
+            if has("tlm") && eddystone.frames.len() == 1 {
+                eprintln!("Warning: beacon.eddystone.frames has only tlm; scanners usually need uid or url to identify the beacon.");
+            }
+            format!(
+                "Some(EddystoneConfig {{ frames: &[{frames}], tx_power: {tx_power}, \
+                 namespace: {namespace:?}, instance: {instance:?}, url: &{url:?} }})",
+                frames = frames.join(", "),
+                tx_power = eddystone.tx_power,
+            )
+        }
+        None => "None".to_string(),
+    };
+
+    Ok(format!(
+        "pub const BLE_BEACON: BeaconConfig = BeaconConfig {{ mode: {mode}, \
+         interleave: {interleave}, slot_ms: {slot_ms}, interval_ms: {interval_ms}, \
+         ibeacon: {ibeacon}, eddystone: {eddystone} }};\n",
+        interleave = beacon.interleave,
+        slot_ms = beacon.slot_ms,
+        interval_ms = beacon.interval_ms,
+    ))
+}
+
+fn generate_led_patterns_config() -> Result<(), Box<dyn Error>> {
+    let config_path = Path::new("config/led_patterns.json");
+    let cfg: LedPatternsConfig = match fs::read_to_string(config_path) {
+        Ok(data) => serde_json::from_str(&data)?,
+        Err(e) => {
+            eprintln!(
+                "Warning: failed to read LED pattern presets from {}: {}. No presets will be available.",
+                config_path.display(),
+                e,
+            );
+            LedPatternsConfig {
+                patterns: BTreeMap::new(),
+            }
+        }
+    };
+
+    let mut entries = String::new();
+    for (name, pattern) in &cfg.patterns {
+        // 名前はコードから参照するため識別子的な文字列に限定
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("invalid LED pattern name: {name:?}").into());
+        }
+        if pattern.steps.is_empty() {
+            return Err(format!("LED pattern '{name}' has no steps").into());
+        }
+        // 20ms周期のタスクで再現できない短すぎるステップは弾く
+        if let Some(step) = pattern.steps.iter().find(|s| s.duration_ms < 20) {
+            return Err(format!(
+                "LED pattern '{name}': step duration must be >= 20ms: {}",
+                step.duration_ms
+            )
+            .into());
+        }
+
+        let steps: String = pattern
+            .steps
+            .iter()
+            .map(|s| {
+                format!(
+                    "PatternStep {{ level: {}, duration_ms: {} }}, ",
+                    s.level, s.duration_ms
+                )
+            })
+            .collect();
+        let repeat = match pattern.repeat {
+            0 => "PatternRepeat::Forever".to_string(),
+            n => format!("PatternRepeat::Times({n})"),
+        };
+
+        entries.push_str(&format!(
+            "    (\"{name}\", LedPattern {{ steps: Cow::Borrowed(&[{steps}]), repeat: {repeat} }}),\n"
+        ));
+    }
+
+    let code = format!(
+        "// Auto-generated by build.rs. Do not edit manually.\n\n\
+         pub const LED_PATTERN_PRESETS: &[(&str, LedPattern)] = &[\n{entries}];\n"
+    );
+
+    let out_dir = env::var("OUT_DIR")?;
+    fs::write(Path::new(&out_dir).join("led_patterns_gen.rs"), code)?;
+
+    println!("cargo:rerun-if-changed=config/led_patterns.json");
+    Ok(())
+}
+
+fn generate_led_colors_config() -> Result<(), Box<dyn Error>> {
+    // デフォルト値（青 = アドバタイズ、緑 = 接続、赤 = エラー、白 = 操作フィードバック、橙 = バッテリー低下）
+    let default = LedColorsConfig {
+        advertising: [0, 0, 255],
+        connected: [0, 255, 0],
+        error: [255, 0, 0],
+        feedback: [255, 255, 255],
+        low_battery: default_low_battery_color(),
+    };
+
+    let config_path = Path::new("config/led_colors.json");
+    let cfg: LedColorsConfig = match fs::read_to_string(config_path) {
+        Ok(data) => serde_json::from_str(&data)?,
+        Err(e) => {
+            eprintln!(
+                "Warning: failed to read LED colors from {}: {}. Falling back to defaults.",
+                config_path.display(),
+                e,
+            );
+            default
+        }
+    };
+
+    let rgb = |[r, g, b]: [u8; 3]| format!("Rgb::new({r}, {g}, {b})");
+    let code = format!(
+        "// Auto-generated by build.rs. Do not edit manually.\n\n\
+         pub const STATUS_COLORS: StatusColors = StatusColors {{\n\
+         advertising: {},\n\
+         connected: {},\n\
+         error: {},\n\
+         feedback: {},\n\
+         low_battery: {},\n\
+         }};\n",
+        rgb(cfg.advertising),
+        rgb(cfg.connected),
+        rgb(cfg.error),
+        rgb(cfg.feedback),
+        rgb(cfg.low_battery),
+    );
+
+    let out_dir = env::var("OUT_DIR")?;
+    fs::write(Path::new(&out_dir).join("led_colors_gen.rs"), code)?;
+
+    println!("cargo:rerun-if-changed=config/led_colors.json");
+    Ok(())
+}
@@ -0,0 +1,6 @@
+/// バッテリータスクから発行されるイベント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryEvent {
+    /// 残量が変化した（`low` はバッテリー低下の判定結果）
+    Level { percent: u8, mv: u32, low: bool },
+}
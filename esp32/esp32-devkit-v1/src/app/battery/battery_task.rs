@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use esp_idf_hal::delay::FreeRtos;
+
+use super::{battery_event::BatteryEvent, level, Battery};
+use crate::app::tasks::Tasks;
+use crate::common::{Error, Result};
+
+/// 測定周期
+const POLL_MS: u32 = 30_000;
+
+pub struct BatteryTask {
+    _handle: JoinHandle<()>,
+}
+
+impl BatteryTask {
+    pub fn start(tasks: Arc<Tasks>, mut battery: Battery) -> Result<Self> {
+        let alive = tasks.register_task("battery_task");
+        let h = thread::Builder::new()
+            .name("battery_task".into())
+            .stack_size(4096)
+            .spawn(move || {
+                let _alive = alive;
+                log::info!("Battery task started");
+                let config = *battery.config();
+                // 前回通知した残量と低下判定（変化した時だけ通知）
+                let mut last: Option<u8> = None;
+                let mut low = false;
+
+                loop {
+                    match battery.read_mv() {
+                        Ok(mv) => {
+                            let percent = level::percent(mv, &config);
+                            low = level::is_low(percent, low, &config);
+                            if last != Some(percent) {
+                                log::debug!("Battery: {}mV ({}%, low: {})", mv, percent, low);
+                                tasks.send_battery_event(BatteryEvent::Level { percent, mv, low });
+                                last = Some(percent);
+                            }
+                        }
+                        Err(e) => log::warn!("Battery read failed: {e}"),
+                    }
+
+                    FreeRtos::delay_ms(POLL_MS);
+                }
+            })
+            .map_err(|e| Error::new_unexpected(&format!("failed to spawn battery_task: {e}")))?;
+
+        Ok(Self { _handle: h })
+    }
+}
@@ -0,0 +1,67 @@
+pub mod battery_event;
+pub mod battery_task;
+
+pub use devkit_core::battery::level;
+
+use esp_idf_hal::adc::attenuation::DB_12;
+use esp_idf_hal::adc::oneshot::config::{AdcChannelConfig, Calibration};
+use esp_idf_hal::adc::oneshot::{AdcChannelDriver, AdcDriver};
+use esp_idf_hal::adc::ADC1;
+use esp_idf_hal::gpio::ADCPin;
+
+use crate::common::{Error, Result};
+use crate::config::pins::BatteryPinConfig;
+
+/// 1回の測定で平均するサンプル数
+const SAMPLES: u32 = 8;
+
+/// ADC1 のワンショット測定でバッテリー電圧を読む
+///
+/// ピンは `Pins::take` で `pins.json` の `battery` に従って取り出す。
+pub struct Battery {
+    /// 校正済みのピン電圧（mV）を読む（ピンごとに型が異なるため関数として保持）
+    read: Box<dyn FnMut() -> Result<u16> + Send>,
+    config: BatteryPinConfig,
+}
+
+impl Battery {
+    pub fn new<P>(adc1: ADC1, pin: P, config: BatteryPinConfig) -> Result<Self>
+    where
+        P: ADCPin<Adc = ADC1> + Send + 'static,
+    {
+        let adc = AdcDriver::new(adc1)
+            .map_err(|e| Error::new_esp(&format!("failed to init ADC1: {e}")))?;
+
+        // 12dB 減衰で約 0-3.1V を測定し、eFuse の校正値で電圧に換算する
+        let channel_config = AdcChannelConfig {
+            attenuation: DB_12,
+            calibration: Calibration::Line,
+            ..Default::default()
+        };
+        let mut channel = AdcChannelDriver::new(adc, pin, &channel_config)
+            .map_err(|e| Error::new_esp(&format!("failed to configure ADC channel: {e}")))?;
+
+        log::info!("Battery ADC initialized (GPIO{})", config.pin);
+        Ok(Self {
+            read: Box::new(move || {
+                channel
+                    .read()
+                    .map_err(|e| Error::new_esp(&format!("failed to read ADC: {e}")))
+            }),
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &BatteryPinConfig {
+        &self.config
+    }
+
+    /// バッテリー電圧（mV、分圧前）を測定する
+    pub fn read_mv(&mut self) -> Result<u32> {
+        let mut total = 0u32;
+        for _ in 0..SAMPLES {
+            total += (self.read)()? as u32;
+        }
+        Ok(level::battery_mv(total / SAMPLES, &self.config))
+    }
+}
@@ -0,0 +1,111 @@
+//! アドバタイズパケット・スキャンレスポンス・間隔・送信出力の設定（`ble.json` の `advertising`）
+
+use esp32_nimble::{
+    enums::{ConnMode, PowerType},
+    utilities::{mutex::Mutex, BleUuid},
+    uuid128, BLEAdvertisementData, BLEAdvertising, BLEDevice,
+};
+
+use crate::app::ble::beacon::{BeaconPayload, EDDYSTONE_SERVICE_UUID};
+use crate::common::{Error, Result};
+use crate::config::ble::{AdvField, BleConfig};
+
+/// アドバタイズ間隔の単位（0.625ms）を µs で表した値
+const INTERVAL_UNIT_US: u32 = 625;
+
+/// 設定に従ってアドバタイズの内容とパラメータを設定する（`status` は状態データの初期値）
+pub(super) fn configure(advertiser: &Mutex<BLEAdvertising>, status: &[u8]) -> Result<()> {
+    let config = BleConfig::ADVERTISING;
+
+    BLEDevice::set_power(PowerType::Advertising, config.tx_power)
+        .map_err(|e| Error::new_esp(&format!("set adv tx power failed: {e:?}")))?;
+
+    set_connectable(&mut advertiser.lock(), status)?;
+
+    log::debug!(
+        "Advertising configured (interval: {}-{} ms, tx power: {:?}, connectable: {})",
+        config.interval_min_ms,
+        config.interval_max_ms,
+        config.tx_power,
+        config.connectable
+    );
+    Ok(())
+}
+
+/// 設定どおりのアドバタイズの内容に戻す（ビーコンの送信から切り替える時にも使う）
+pub(super) fn set_connectable(adv: &mut BLEAdvertising, status: &[u8]) -> Result<()> {
+    let config = BleConfig::ADVERTISING;
+
+    adv.set_data(&mut build_data(config.data, status))
+        .map_err(|e| Error::new_esp(&format!("set adv data failed: {e:?}")))?;
+    adv.scan_response(!config.scan_response.is_empty());
+    if !config.scan_response.is_empty() {
+        adv.set_scan_response_data(&mut build_data(config.scan_response, status))
+            .map_err(|e| Error::new_esp(&format!("set scan response failed: {e:?}")))?;
+    }
+
+    adv.advertisement_type(if config.connectable {
+        ConnMode::Und
+    } else {
+        ConnMode::Non
+    })
+    .min_interval(to_interval_units(config.interval_min_ms))
+    .max_interval(to_interval_units(config.interval_max_ms));
+    Ok(())
+}
+
+/// ビーコンの frame を接続を受け付けないアドバタイズとして設定する
+pub(super) fn set_beacon(adv: &mut BLEAdvertising, payload: &BeaconPayload) -> Result<()> {
+    let mut data = BLEAdvertisementData::new();
+    match payload {
+        BeaconPayload::ManufacturerData(bytes) => data.manufacturer_data(bytes),
+        BeaconPayload::Eddystone(bytes) => {
+            let uuid = BleUuid::from_uuid16(EDDYSTONE_SERVICE_UUID);
+            data.add_service_uuid(uuid).service_data(uuid, bytes)
+        }
+    };
+    adv.set_data(&mut data)
+        .map_err(|e| Error::new_esp(&format!("set beacon data failed: {e:?}")))?;
+
+    let interval = to_interval_units(BleConfig::BEACON.interval_ms);
+    adv.scan_response(false)
+        .advertisement_type(ConnMode::Non)
+        .min_interval(interval)
+        .max_interval(interval);
+    Ok(())
+}
+
+/// 状態データを含むパケットだけを作り直す（アドバタイズ中でもそのまま反映される）
+pub(super) fn update_status(advertiser: &Mutex<BLEAdvertising>, status: &[u8]) -> Result<()> {
+    let config = BleConfig::ADVERTISING;
+    let mut adv = advertiser.lock();
+    if config.data.contains(&AdvField::Status) {
+        adv.set_data(&mut build_data(config.data, status))
+            .map_err(|e| Error::new_esp(&format!("set adv data failed: {e:?}")))?;
+    }
+    if config.scan_response.contains(&AdvField::Status) {
+        adv.set_scan_response_data(&mut build_data(config.scan_response, status))
+            .map_err(|e| Error::new_esp(&format!("set scan response failed: {e:?}")))?;
+    }
+    Ok(())
+}
+
+fn build_data(fields: &[AdvField], status: &[u8]) -> BLEAdvertisementData {
+    let config = BleConfig::ADVERTISING;
+    let mut data = BLEAdvertisementData::new();
+    for field in fields {
+        match field {
+            AdvField::Name => data.name(BleConfig::DEVICE_NAME),
+            AdvField::ServiceUuid => data.add_service_uuid(uuid128!(BleConfig::SERVICE_UUID)),
+            AdvField::Appearance => data.appearance(config.appearance),
+            AdvField::ManufacturerData => data.manufacturer_data(config.manufacturer_data),
+            AdvField::Status => data.manufacturer_data(status),
+        };
+    }
+    data
+}
+
+fn to_interval_units(ms: u32) -> u16 {
+    // build.rs で 20〜10240ms に制限済みなので u16 に収まる
+    (ms * 1000 / INTERVAL_UNIT_US) as u16
+}
@@ -1,10 +1,50 @@
+use crate::app::ble::ble_event::ReplyTo;
+use crate::app::ble::gatt_client::GattRequest;
+use crate::app::ble::notification::BleNotification;
+use crate::app::ble::scan::ScanFilter;
+use crate::config::ble::BeaconMode;
+
+pub use devkit_core::ble::reconnect::AdvertiseMode;
+
 #[derive(Clone, Debug)]
 pub enum BleCommand {
     StartAdvertise {
         timeout_ms: u32,
+        mode: AdvertiseMode,
     },
     StopAdvertise,
     /// 現在のBLE接続状態を取得
     GetState,
+    /// 接続中のセントラルの一覧を取得
+    GetConnections,
+    /// Battery Service の残量（%）とアドバタイズの低下フラグ・電圧を更新
+    SetBatteryLevel {
+        percent: u8,
+        mv: u32,
+        low: bool,
+    },
+    /// ビーコンのモードを切り替える
+    SetBeacon(BeaconMode),
+    /// 周辺デバイスをスキャンし、結果を `BleEvent::ScanResults` で返す
+    Scan {
+        duration_ms: u32,
+        filter: ScanFilter,
+    },
+    /// GATT クライアントとしての操作（結果は `BleEvent::GattResponse` で返す）
+    Gatt(GattRequest),
+    /// ペアリング中の相手が表示したパスキーを入力し、結果を応答する
+    EnterPasskey {
+        passkey: u32,
+        reply: ReplyTo,
+    },
+    /// 保存済みのボンド情報を全削除
+    ClearBonds,
+    /// リモートコマンドの実行結果を、コマンドを受け付けた接続・経路で通知
+    Respond {
+        reply: ReplyTo,
+        message: String,
+    },
+    /// イベントキャラクタリスティックの購読者に通知
+    Notify(BleNotification),
     Shutdown,
 }
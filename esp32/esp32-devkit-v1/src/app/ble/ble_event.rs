@@ -1,18 +1,68 @@
 use crate::app::ble::ble_state::BleState;
+use crate::app::ble::command_parser::RemoteCommand;
+use crate::app::ble::connections::Connection;
+use crate::app::ble::gatt_client::GattEvent;
+use crate::app::ble::scan::ScannedDevice;
 
-/// BLEタスクから発行される状態変化イベント
+/// リモートコマンドを受け付けた経路
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyRoute {
+    /// コマンドキャラクタリスティックへのテキスト
+    Text,
+    /// コマンドキャラクタリスティックへのバイナリフレーム（応答に要求の `msg_id` を付ける）
+    Binary { msg_id: u8 },
+    /// NUS コンソール
+    Console,
+}
+
+/// リモートコマンドの応答先（`BleCommand::Respond` でそのまま返す）
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplyTo {
+    pub route: ReplyRoute,
+    /// コマンドを書き込んだ接続
+    pub conn_handle: u16,
+    /// 書き込み時のその接続の ATT MTU
+    pub mtu: u16,
+}
+
+/// BLEタスクから発行される状態変化イベント
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum BleEvent {
     /// アドバタイズ開始
     AdvertisingStarted,
     /// アドバタイズ停止
     AdvertisingStopped,
-    /// デバイス接続
-    Connected,
-    /// デバイス切断
-    Disconnected,
+    /// 切断後の再アドバタイズを開始（`window_ms` 後に止める）
+    ReconnectStarted { window_ms: u32 },
+    /// 次の再アドバタイズまで待機中（Backoff）
+    ReconnectWaiting { retry_in_ms: u32 },
+    /// デバイス接続（`count` は接続後の接続数）
+    Connected { peer: Connection, count: usize },
+    /// デバイス切断（`count` は切断後の接続数）
+    Disconnected { peer: Connection, count: usize },
     /// エラー発生
     Error,
     /// 接続/アドバタイズ状態応答
     StateResponse(BleState),
+    /// 接続一覧の応答（RSSI は取得時に測定）
+    ConnectionsResponse(Vec<Connection>),
+    /// ペアリング完了（`bonded` ならボンド情報を保存済み）
+    PairingCompleted { bonded: bool },
+    /// ペアリング失敗（パスキー不一致など）
+    PairingFailed,
+    /// ボンド情報を全削除
+    BondsCleared,
+    /// スキャン結果（重複排除・絞り込み済み、RSSI の強い順）
+    ScanResults(Vec<ScannedDevice>),
+    /// スキャン失敗
+    ScanFailed,
+    /// GATT クライアントの要求（`BleCommand::Gatt`）の結果
+    GattResponse(GattEvent),
+    /// GATT クライアントで購読中の通知・リモートからの切断（要求とは無関係に届く）
+    Gatt(GattEvent),
+    /// コマンドキャラクタリスティック・コンソールへの書き込み（解析済み、`reply` が応答先）
+    RemoteCommand {
+        command: RemoteCommand,
+        reply: ReplyTo,
+    },
 }
@@ -1,5 +1,6 @@
 // app/tasks/ble_handle.rs
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 
 use crate::app::ble::ble_command::BleCommand;
 
@@ -7,4 +8,13 @@ use crate::app::ble::ble_command::BleCommand;
 #[derive(Clone)]
 pub struct BleHandle {
     pub(crate) tx: mpsc::Sender<BleCommand>,
+    /// イベント通知の購読者がいるか（BLE タスクが更新）
+    pub(crate) subscribed: Arc<AtomicBool>,
+}
+
+impl BleHandle {
+    /// イベント通知の購読者がいるか（いなければ `BleCommand::Notify` を送る必要はない）
+    pub fn has_subscribers(&self) -> bool {
+        self.subscribed.load(Ordering::Acquire)
+    }
 }
@@ -2,6 +2,10 @@
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct BleState {
     pub(crate) connected: bool,
+    /// 接続中のセントラルの数
+    pub(crate) connections: u8,
     pub(crate) advertising: bool,
+    /// 切断後の再アドバタイズ中、または次の窓を待っているか
+    pub(crate) reconnecting: bool,
     pub(crate) error: bool,
 }
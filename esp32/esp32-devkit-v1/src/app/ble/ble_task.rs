@@ -2,13 +2,26 @@ use esp_idf_hal::delay::FreeRtos;
 
 use crate::{
     app::{
-        ble::{ble_command::BleCommand, ble_event::BleEvent, ble_handle::BleHandle, Ble},
+        ble::{
+            ble_command::{AdvertiseMode, BleCommand},
+            ble_event::BleEvent,
+            ble_handle::BleHandle,
+            ble_state::BleState,
+            gatt_client::GattEvent,
+            notification::BleNotification,
+            reconnect::{Reconnect, Window},
+            Ble,
+        },
         tasks::Tasks,
     },
     common::{Error, Result},
+    config::ble::BleConfig,
 };
 use std::{
-    sync::{mpsc, Arc},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
     thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
@@ -21,29 +34,87 @@ pub struct BleTask {
 impl BleTask {
     pub fn start(tasks: Arc<Tasks>) -> Result<(Self, BleHandle)> {
         let (tx, rx) = mpsc::channel::<BleCommand>();
+        let subscribed = Arc::new(AtomicBool::new(false));
+        let subscribed_on_task = subscribed.clone();
 
+        let alive = tasks.register_task("ble_task");
         let handle = thread::Builder::new()
             .name("ble_task".into())
             .stack_size(8192)
             .spawn(move || {
+                let _alive = alive;
                 log::info!("BLE task started");
                 let mut ble = Ble::new();
+                ble.set_subscribed_flag(subscribed_on_task);
                 let event_tasks = tasks.clone();
+                // NimBLE のコールバックで接続・切断されたら立て、ループで再アドバタイズを判断する
+                let connected = Arc::new(AtomicBool::new(false));
+                let disconnected = Arc::new(AtomicBool::new(false));
+                let connected_on_event = connected.clone();
+                let disconnected_on_event = disconnected.clone();
 
                 ble.set_event_sink(Arc::new(move |event| {
                     log::debug!("BLE event emitted: {:?}", event);
+                    match event {
+                        BleEvent::Connected { .. } => {
+                            connected_on_event.store(true, Ordering::Release)
+                        }
+                        BleEvent::Disconnected { .. } => {
+                            disconnected_on_event.store(true, Ordering::Release)
+                        }
+                        _ => {}
+                    }
                     event_tasks.send_ble_event(event);
                 }));
                 let mut pairing_deadline: Option<Instant> = None;
+                let mut reconnect = Reconnect::new(BleConfig::RECONNECT);
+                // 購読者に最後に通知した状態（購読者がいない間は None に戻し、購読開始時に現在値を通知）
+                let mut notified_state: Option<BleState> = None;
+                let mut status_refreshed_at = Instant::now();
 
                 loop {
+                    // 再接続できたら再アドバタイズの方針を終える（アドバタイズは窓の終わりまで続く）
+                    if connected.swap(false, Ordering::AcqRel) {
+                        reconnect.cancel();
+                    }
+                    // 切断後の再アドバタイズ（接続が残っていれば、接続中もアドバタイズする設定の時のみ）
+                    // アドバタイズ中の切断はフラグを残し、その窓が時間切れで終わってから判断する
+                    // （StartAdvertise / StopAdvertise の操作ではフラグを捨てる）
+                    if !ble.is_advertising()
+                        && (!ble.is_connected() || BleConfig::CONNECTIONS.keep_advertising)
+                        && disconnected.swap(false, Ordering::AcqRel)
+                    {
+                        if let Some(window) = reconnect.on_disconnected(ble.advertise_mode()) {
+                            pairing_deadline =
+                                start_reconnect_window(&mut ble, &tasks, &mut reconnect, window);
+                        }
+                    }
+                    if let Some(window) = reconnect.poll(Instant::now()) {
+                        pairing_deadline =
+                            start_reconnect_window(&mut ble, &tasks, &mut reconnect, window);
+                    }
+
                     // コマンド処理
                     while let Ok(cmd) = rx.try_recv() {
                         log::debug!("BLE command received: {:?}", cmd);
                         match cmd {
-                            BleCommand::StartAdvertise { timeout_ms } => {
-                                log::info!("Processing StartAdvertise (timeout: {}ms)", timeout_ms);
-                                match ble.start_pairing() {
+                            BleCommand::StartAdvertise { timeout_ms, mode } => {
+                                log::info!(
+                                    "Processing StartAdvertise ({:?}, timeout: {}ms)",
+                                    mode,
+                                    timeout_ms
+                                );
+                                // ボンド済みの相手がいなければ何もしない（エラー表示にしない）
+                                if mode == AdvertiseMode::BondedOnly && !ble.has_bonds() {
+                                    log::info!(
+                                        "No bonded peers; ignoring bonded-only advertising (use open pairing first)"
+                                    );
+                                    continue;
+                                }
+                                // 手動の操作が優先（保留中の切断で再接続の窓を開かない）
+                                reconnect.cancel();
+                                disconnected.store(false, Ordering::Release);
+                                match ble.start_pairing(mode) {
                                     Ok(()) => {
                                         ble.set_error(false);
                                         tasks.send_ble_event(BleEvent::AdvertisingStarted);
@@ -62,10 +133,15 @@ impl BleTask {
                             }
                             BleCommand::StopAdvertise => {
                                 log::info!("Processing StopAdvertise");
+                                reconnect.cancel();
+                                disconnected.store(false, Ordering::Release);
                                 match ble.stop_pairing() {
                                     Ok(()) => {
                                         ble.set_error(false);
-                                        tasks.send_ble_event(BleEvent::AdvertisingStopped);
+                                        // 接続中ならLEDは接続中の表示のままにする
+                                        if !ble.is_connected() {
+                                            tasks.send_ble_event(BleEvent::AdvertisingStopped);
+                                        }
                                     }
                                     Err(e) => {
                                         ble.set_error(true);
@@ -76,7 +152,7 @@ impl BleTask {
                                 pairing_deadline = None;
                             }
                             BleCommand::GetState => {
-                                let state = ble.state();
+                                let state = current_state(&ble, &reconnect);
                                 log::debug!(
                                     "Processing GetState: connected={}, advertising={}",
                                     state.connected,
@@ -84,10 +160,86 @@ impl BleTask {
                                 );
                                 tasks.send_ble_event(BleEvent::StateResponse(state));
                             }
+                            BleCommand::GetConnections => {
+                                let connections = ble.connections();
+                                log::debug!(
+                                    "Processing GetConnections: {} connected",
+                                    connections.len()
+                                );
+                                tasks.send_ble_event(BleEvent::ConnectionsResponse(connections));
+                            }
+                            BleCommand::Respond { reply, message } => {
+                                log::debug!("Processing Respond ({:?}): {}", reply, message);
+                                if let Err(e) = ble.respond(&reply, &message) {
+                                    log::warn!("Failed to send command response: {e}");
+                                }
+                            }
+                            BleCommand::SetBatteryLevel { percent, mv, low } => {
+                                ble.set_battery_level(percent, mv, low)
+                            }
+                            BleCommand::SetBeacon(mode) => {
+                                log::info!("Processing SetBeacon ({:?})", mode);
+                                if let Err(e) = ble.set_beacon_mode(mode) {
+                                    log::warn!("Failed to set beacon mode: {e}");
+                                }
+                            }
+                            BleCommand::Scan {
+                                duration_ms,
+                                filter,
+                            } => {
+                                log::info!("Processing Scan ({}ms, {:?})", duration_ms, filter);
+                                // 結果はスキャンのスレッドから BleEvent で通知される
+                                if let Err(e) = ble.start_scan(duration_ms, filter) {
+                                    tasks.send_ble_event(BleEvent::ScanFailed);
+                                    log::error!("Failed to start scan: {e}");
+                                }
+                            }
+                            BleCommand::Gatt(request) => {
+                                log::info!("Processing Gatt ({:?})", request);
+                                let address = request.address().to_string();
+                                // 結果は GATT クライアントのスレッドから BleEvent で通知される
+                                if let Err(e) = ble.gatt(request) {
+                                    log::warn!("GATT request failed: {e}");
+                                    tasks.send_ble_event(BleEvent::GattResponse(GattEvent::Failed {
+                                        address,
+                                        reason: e.message,
+                                    }));
+                                }
+                            }
+                            BleCommand::EnterPasskey { passkey, reply } => {
+                                log::info!("Processing EnterPasskey");
+                                let message = match ble.enter_passkey(passkey) {
+                                    Ok(()) => "ok".to_string(),
+                                    Err(e) => {
+                                        log::warn!("Failed to enter passkey: {e}");
+                                        format!("err {}", e.message)
+                                    }
+                                };
+                                if let Err(e) = ble.respond(&reply, &message) {
+                                    log::warn!("Failed to send command response: {e}");
+                                }
+                            }
+                            BleCommand::ClearBonds => {
+                                log::info!("Processing ClearBonds");
+                                match ble.clear_bonds() {
+                                    Ok(()) => tasks.send_ble_event(BleEvent::BondsCleared),
+                                    Err(e) => {
+                                        ble.set_error(true);
+                                        tasks.send_ble_event(BleEvent::Error);
+                                        log::error!("Failed to clear bonds: {e}");
+                                    }
+                                }
+                            }
+                            BleCommand::Notify(notification) => {
+                                if let BleNotification::Button(event) = &notification {
+                                    ble.record_button(event);
+                                }
+                                ble.notify(&notification);
+                            }
                             BleCommand::Shutdown => {
                                 log::info!("Processing Shutdown");
                                 let _ = ble.stop_pairing();
-                                let _ = ble.on_disconnected();
+                                ble.disconnect_remote_peers();
                                 log::info!("BLE task shutting down");
                                 return;
                             }
@@ -98,23 +250,67 @@ impl BleTask {
                     if let Some(deadline) = pairing_deadline {
                         if Instant::now() >= deadline {
                             log::warn!("Pairing timeout reached");
-                            if ble.is_connected() {
+                            // 接続中でもアドバタイズを続けている場合は止める
+                            if ble.is_connected() && !ble.is_advertising() {
                                 log::info!(
                                     "Pairing timeout reached but device is connected; skipping stop_pairing"
                                 );
-                                pairing_deadline = None;
                             } else {
                                 match ble.stop_pairing() {
-                                    Ok(()) => {
-                                        tasks.send_ble_event(BleEvent::AdvertisingStopped);
-                                    }
+                                    Ok(()) => match reconnect.on_window_expired(Instant::now()) {
+                                        Some(delay) => {
+                                            log::info!(
+                                                "No reconnection; re-advertising again in {}ms",
+                                                delay.as_millis()
+                                            );
+                                            if !ble.is_connected() {
+                                                tasks.send_ble_event(BleEvent::ReconnectWaiting {
+                                                    retry_in_ms: delay.as_millis() as u32,
+                                                });
+                                            }
+                                        }
+                                        None => {
+                                            if !ble.is_connected() {
+                                                tasks.send_ble_event(BleEvent::AdvertisingStopped);
+                                            }
+                                        }
+                                    },
                                     Err(e) => {
+                                        reconnect.cancel();
                                         ble.set_error(true);
                                         tasks.send_ble_event(BleEvent::Error);
                                         log::error!("Failed to stop pairing on timeout: {e}");
                                     }
                                 }
-                                pairing_deadline = None;
+                            }
+                            pairing_deadline = None;
+                        }
+                    }
+
+                    // 状態変化の通知
+                    if ble.has_subscribers() {
+                        let state = current_state(&ble, &reconnect);
+                        if notified_state != Some(state) {
+                            ble.notify(&BleNotification::State(state));
+                            notified_state = Some(state);
+                        }
+                    } else {
+                        notified_state = None;
+                    }
+
+                    // ビーコンの送信・ペアリング中の交互送信
+                    if let Err(e) = ble.update_broadcast() {
+                        log::warn!("Failed to update broadcast: {e}");
+                    }
+
+                    // アドバタイズの状態データを周期的に更新
+                    if let Some(status) = BleConfig::ADVERTISING.status {
+                        if status_refreshed_at.elapsed()
+                            >= Duration::from_millis(status.interval_ms as u64)
+                        {
+                            status_refreshed_at = Instant::now();
+                            if let Err(e) = ble.refresh_adv_status() {
+                                log::warn!("Failed to update advertised status: {e}");
                             }
                         }
                     }
@@ -124,6 +320,49 @@ impl BleTask {
             })
             .map_err(|e| Error::new_unexpected(&format!("failed to spawn ble_task: {e}")))?;
 
-        Ok((Self { handle }, BleHandle { tx }))
+        Ok((Self { handle }, BleHandle { tx, subscribed }))
+    }
+}
+
+/// 再接続の状態を含めた現在のBLE状態
+fn current_state(ble: &Ble, reconnect: &Reconnect) -> BleState {
+    BleState {
+        reconnecting: reconnect.is_active(),
+        ..ble.state()
+    }
+}
+
+/// 再アドバタイズの窓を開始し、終了時刻を返す（開始できなければ再接続を諦める）
+fn start_reconnect_window(
+    ble: &mut Ble,
+    tasks: &Tasks,
+    reconnect: &mut Reconnect,
+    window: Window,
+) -> Option<Instant> {
+    log::info!(
+        "Re-advertising after disconnect ({:?}, {}ms)",
+        window.mode,
+        window.duration_ms
+    );
+    match ble.start_pairing(window.mode) {
+        Ok(()) => {
+            ble.set_error(false);
+            // 他の接続が残っていれば接続中の表示を優先する
+            if !ble.is_connected() {
+                tasks.send_ble_event(BleEvent::ReconnectStarted {
+                    window_ms: window.duration_ms,
+                });
+            }
+            Some(Instant::now() + Duration::from_millis(window.duration_ms as u64))
+        }
+        Err(e) => {
+            // ボンド済みの相手がいない BondedOnly など（エラー表示はしない）
+            log::warn!("Failed to re-advertise: {e}");
+            reconnect.cancel();
+            if !ble.is_connected() {
+                tasks.send_ble_event(BleEvent::AdvertisingStopped);
+            }
+            None
+        }
     }
 }
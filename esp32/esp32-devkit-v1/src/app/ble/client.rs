@@ -0,0 +1,282 @@
+//! GATT クライアント（セントラル）としてリモートのペリフェラルに接続・読み書きする
+//!
+//! 接続・探索・読み書きは完了を待つため、BLE タスクとは別のスレッドで1件ずつ処理する。
+
+use esp32_nimble::{
+    utilities::BleUuid, BLEAddress, BLEAddressType, BLEClient, BLERemoteCharacteristic,
+};
+use esp_idf_hal::task::block_on;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc,
+};
+use std::thread;
+
+use crate::app::ble::ble_event::BleEvent;
+use crate::app::ble::gatt_client::{
+    GattEvent, GattRequest, RemoteCharacteristic, MAX_REMOTE_PEERS,
+};
+use crate::common::{Error, Result};
+
+/// GATT クライアントのスレッドへの指示
+enum Job {
+    Request(GattRequest),
+    DisconnectAll,
+}
+
+/// GATT クライアントの処理を行うスレッド
+///
+/// 要求の結果は `BleEvent::GattResponse`、リモートからの通知・切断は `BleEvent::Gatt` で送る。
+pub(super) struct GattWorker {
+    tx: mpsc::Sender<Job>,
+}
+
+impl GattWorker {
+    pub(super) fn start(sink: Arc<dyn Fn(BleEvent) + Send + Sync>) -> Result<Self> {
+        let (tx, rx) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name("ble_gatt".into())
+            .stack_size(8192)
+            .spawn(move || {
+                log::info!("GATT client worker started");
+                let mut clients = GattClients::default();
+                for job in rx {
+                    match job {
+                        Job::Request(request) => {
+                            let event = clients.handle(request, &sink);
+                            if let GattEvent::Failed { reason, .. } = &event {
+                                log::warn!("GATT request failed: {reason}");
+                            }
+                            sink(BleEvent::GattResponse(event));
+                        }
+                        Job::DisconnectAll => clients.disconnect_all(),
+                    }
+                }
+            })
+            .map_err(|e| Error::new_unexpected(&format!("failed to spawn ble_gatt: {e}")))?;
+        Ok(Self { tx })
+    }
+
+    /// 要求を順番待ちに加える
+    pub(super) fn request(&self, request: GattRequest) -> Result<()> {
+        self.tx
+            .send(Job::Request(request))
+            .map_err(|e| Error::new_unexpected(&format!("GATT worker stopped: {e}")))
+    }
+
+    /// すべてのリモートペリフェラルから切断する（順番待ちの要求の後に行う）
+    pub(super) fn disconnect_all(&self) {
+        if self.tx.send(Job::DisconnectAll).is_err() {
+            log::warn!("GATT worker stopped; cannot disconnect remote peers");
+        }
+    }
+}
+
+/// 接続中のリモートペリフェラル
+struct RemotePeer {
+    address: String,
+    client: BLEClient,
+    /// 要求による切断中（切断イベントは要求の結果として返すため、コールバックからは送らない）
+    closing: Arc<AtomicBool>,
+}
+
+/// リモートペリフェラルとの接続の一覧
+#[derive(Default)]
+struct GattClients {
+    peers: Vec<RemotePeer>,
+}
+
+impl GattClients {
+    /// 要求を実行し、結果のイベントを返す（ワーカースレッドで接続・探索・読み書きが終わるまで待つ）
+    fn handle(
+        &mut self,
+        request: GattRequest,
+        sink: &Arc<dyn Fn(BleEvent) + Send + Sync>,
+    ) -> GattEvent {
+        // 相手から切断された接続を片付ける
+        self.peers.retain(|peer| peer.client.connected());
+
+        let address = request.address().to_string();
+        let result = match request {
+            GattRequest::Connect { address, random } => self.connect(address, random, sink),
+            GattRequest::Disconnect { address } => self.disconnect(&address),
+            GattRequest::Read { address, target } => self.read(&address, &target),
+            GattRequest::Write {
+                address,
+                target,
+                value,
+                with_response,
+            } => self.write(&address, &target, &value, with_response),
+            GattRequest::Subscribe { address, target } => self.subscribe(&address, &target, sink),
+        };
+        result.unwrap_or_else(|e| GattEvent::Failed {
+            address,
+            reason: e.message,
+        })
+    }
+
+    /// すべてのリモートペリフェラルから切断する
+    fn disconnect_all(&mut self) {
+        for peer in &mut self.peers {
+            peer.closing.store(true, Ordering::Release);
+            if let Err(e) = peer.client.disconnect() {
+                log::warn!("Failed to disconnect {}: {e:?}", peer.address);
+            }
+        }
+        self.peers.clear();
+    }
+
+    fn connect(
+        &mut self,
+        address: String,
+        random: bool,
+        sink: &Arc<dyn Fn(BleEvent) + Send + Sync>,
+    ) -> Result<GattEvent> {
+        if self.peers.iter().any(|peer| peer.address == address) {
+            return Err(Error::new_invalid_state("already connected"));
+        }
+        if self.peers.len() >= MAX_REMOTE_PEERS {
+            return Err(Error::new_invalid_state("too many remote peers"));
+        }
+        let address_type = if random {
+            BLEAddressType::Random
+        } else {
+            BLEAddressType::Public
+        };
+        let remote = BLEAddress::from_str(&address, address_type)
+            .ok_or_else(|| Error::new_invalid_state("invalid address"))?;
+
+        let mut client = BLEClient::new();
+        let closing = Arc::new(AtomicBool::new(false));
+        let closing_on_disconnect = closing.clone();
+        let sink_on_disconnect = sink.clone();
+        let address_on_disconnect = address.clone();
+        client.on_disconnect(move |reason| {
+            log::info!(
+                "Remote peer {} disconnected (reason: {})",
+                address_on_disconnect,
+                reason
+            );
+            if closing_on_disconnect.load(Ordering::Acquire) {
+                return;
+            }
+            sink_on_disconnect(BleEvent::Gatt(GattEvent::Disconnected {
+                address: address_on_disconnect.clone(),
+            }));
+        });
+
+        block_on(client.connect(&remote))
+            .map_err(|e| Error::new_esp(&format!("connect failed: {e:?}")))?;
+        let services = match block_on(client.get_services()) {
+            Ok(services) => services
+                .map(|service| service.uuid().to_string().to_ascii_lowercase())
+                .collect(),
+            Err(e) => {
+                let _ = client.disconnect();
+                return Err(Error::new_esp(&format!("service discovery failed: {e:?}")));
+            }
+        };
+        log::info!("Connected to remote peer {} ({:?})", address, services);
+
+        self.peers.push(RemotePeer {
+            address: address.clone(),
+            client,
+            closing,
+        });
+        Ok(GattEvent::Connected { address, services })
+    }
+
+    fn disconnect(&mut self, address: &str) -> Result<GattEvent> {
+        let index = self
+            .peers
+            .iter()
+            .position(|peer| peer.address == address)
+            .ok_or_else(|| Error::new_invalid_state("not connected"))?;
+        let mut peer = self.peers.remove(index);
+        peer.closing.store(true, Ordering::Release);
+        peer.client
+            .disconnect()
+            .map_err(|e| Error::new_esp(&format!("disconnect failed: {e:?}")))?;
+        Ok(GattEvent::Disconnected {
+            address: address.to_string(),
+        })
+    }
+
+    fn read(&mut self, address: &str, target: &RemoteCharacteristic) -> Result<GattEvent> {
+        let characteristic = self.characteristic(address, target)?;
+        let value = block_on(characteristic.read_value())
+            .map_err(|e| Error::new_esp(&format!("read failed: {e:?}")))?;
+        Ok(GattEvent::Value {
+            address: address.to_string(),
+            characteristic: target.characteristic.clone(),
+            value,
+        })
+    }
+
+    fn write(
+        &mut self,
+        address: &str,
+        target: &RemoteCharacteristic,
+        value: &[u8],
+        with_response: bool,
+    ) -> Result<GattEvent> {
+        let characteristic = self.characteristic(address, target)?;
+        block_on(characteristic.write_value(value, with_response))
+            .map_err(|e| Error::new_esp(&format!("write failed: {e:?}")))?;
+        Ok(GattEvent::Written {
+            address: address.to_string(),
+            characteristic: target.characteristic.clone(),
+        })
+    }
+
+    fn subscribe(
+        &mut self,
+        address: &str,
+        target: &RemoteCharacteristic,
+        sink: &Arc<dyn Fn(BleEvent) + Send + Sync>,
+    ) -> Result<GattEvent> {
+        let characteristic = self.characteristic(address, target)?;
+        let sink = sink.clone();
+        let notify_address = address.to_string();
+        let notify_characteristic = target.characteristic.clone();
+        characteristic.on_notify(move |value| {
+            sink(BleEvent::Gatt(GattEvent::Notification {
+                address: notify_address.clone(),
+                characteristic: notify_characteristic.clone(),
+                value: value.to_vec(),
+            }));
+        });
+        block_on(characteristic.subscribe_notify(false))
+            .map_err(|e| Error::new_esp(&format!("subscribe failed: {e:?}")))?;
+        Ok(GattEvent::Subscribed {
+            address: address.to_string(),
+            characteristic: target.characteristic.clone(),
+        })
+    }
+
+    fn characteristic(
+        &mut self,
+        address: &str,
+        target: &RemoteCharacteristic,
+    ) -> Result<&mut BLERemoteCharacteristic> {
+        let peer = self
+            .peers
+            .iter_mut()
+            .find(|peer| peer.address == address)
+            .ok_or_else(|| Error::new_invalid_state("not connected"))?;
+        let service = block_on(peer.client.get_service(parse_uuid(&target.service)?))
+            .map_err(|e| Error::new_esp(&format!("service not found: {e:?}")))?;
+        block_on(service.get_characteristic(parse_uuid(&target.characteristic)?))
+            .map_err(|e| Error::new_esp(&format!("characteristic not found: {e:?}")))
+    }
+}
+
+/// `2a19` または `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`（書式は解析時に確認済み）
+fn parse_uuid(value: &str) -> Result<BleUuid> {
+    if value.len() == 4 {
+        let uuid =
+            u16::from_str_radix(value, 16).map_err(|_| Error::new_invalid_state("invalid UUID"))?;
+        return Ok(BleUuid::from_uuid16(uuid));
+    }
+    BleUuid::from_uuid128_string(value).map_err(|_| Error::new_invalid_state("invalid UUID"))
+}
@@ -1,28 +1,97 @@
+mod advertising;
 pub mod ble_command;
 pub mod ble_event;
 pub mod ble_handle;
 mod ble_state;
 pub mod ble_task;
+mod client;
+pub mod notification;
+mod security;
+mod standard_services;
 
+pub use devkit_core::ble::{adv_status, beacon, command_parser, console, gatt_client, scan, wire};
+
+use devkit_protocol::{ErrorCode, Message, Reassembler};
 use esp32_nimble::{
-    utilities::mutex::Mutex, uuid128, BLEAdvertisementData, BLEAdvertising, BLEDevice, BLEServer,
-    NimbleProperties,
+    enums::AdvFilterPolicy, utilities::mutex::Mutex, uuid128, BLEAdvertising, BLECharacteristic,
+    BLEConnDesc, BLEDevice, BLEScan, BLEServer, NimbleProperties,
 };
+use esp_idf_hal::task::block_on;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::app::ble::ble_event::BleEvent;
+use crate::app::ble::adv_status::{AdvStatus, STATUS_DATA_LEN};
+use crate::app::ble::beacon::{BeaconFrame, Telemetry};
+use crate::app::ble::ble_command::AdvertiseMode;
+use crate::app::ble::ble_event::{BleEvent, ReplyRoute, ReplyTo};
 use crate::app::ble::ble_state::BleState;
+use crate::app::ble::client::GattWorker;
+use crate::app::ble::connections::{Connection, ConnectionTable};
+use crate::app::ble::console::{Line, LineBuffer};
+use crate::app::ble::gatt_client::GattRequest;
+use crate::app::ble::notification::BleNotification;
+use crate::app::ble::scan::{ScanCollector, ScanFilter, ScannedDevice, MAX_SCAN_DURATION_MS};
+use crate::app::button::event::ButtonEvent;
 use crate::common::{Error, Result};
-use crate::config::ble::BleConfig;
+use crate::config::ble::{BeaconMode, BleConfig, BleSecurityMode, EddystoneFrame};
+use crate::config::pins::BATTERY;
+
+/// 現在アドバタイズしている内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnAir {
+    /// 接続を受け付けるアドバタイズ（ペアリング）
+    Connectable,
+    Beacon(BeaconFrame),
+}
 
 pub struct Ble {
     advertising: Arc<AtomicBool>,
     error: Arc<AtomicBool>,
     server: Option<&'static mut BLEServer>,
     advertiser: Option<&'static Mutex<BLEAdvertising>>,
+    /// コマンド実行結果を通知するキャラクタリスティック
+    response: Option<Arc<Mutex<BLECharacteristic>>>,
+    /// 状態変化などを通知するキャラクタリスティック
+    events: Option<Arc<Mutex<BLECharacteristic>>>,
+    /// イベント通知を購読している接続ハンドル
+    subscribers: Arc<Mutex<Vec<u16>>>,
+    /// 購読者がいるか（`BleHandle` と共有し、購読者がいない間は通知を送らせない）
+    subscribed: Arc<AtomicBool>,
+    /// 接続中のセントラル（NimBLE のコールバックから更新）
+    connections: Arc<Mutex<ConnectionTable>>,
+    /// Battery Service の残量キャラクタリスティック（バッテリー未設定なら None）
+    battery: Option<Arc<Mutex<BLECharacteristic>>>,
+    /// 最後に通知された残量（初期化前に通知された値も保持、最初の測定までは None）
+    battery_level: Option<u8>,
+    battery_low: bool,
+    /// 最後に通知されたバッテリー電圧（Eddystone-TLM 用）
+    battery_mv: u16,
+    /// 起動からボタンを押した回数（アドバタイズの状態データ用）
+    button_presses: u16,
+    /// 最後にアドバタイズに反映した状態
+    advertised_status: Option<AdvStatus>,
+    /// NUS の TX キャラクタリスティック（コンソールの応答）
+    console: Option<Arc<Mutex<BLECharacteristic>>>,
+    /// 現在（または最後）のアドバタイズのモード
+    advertise_mode: AdvertiseMode,
+    /// スキャン中か（スキャンは別スレッドで行い、同時には1つだけ）
+    scanning: Arc<AtomicBool>,
+    /// GATT クライアントのスレッド（最初の要求で起動）
+    gatt: Option<GattWorker>,
+    beacon_mode: BeaconMode,
+    /// 現在アドバタイズしている内容（停止中は None）
+    on_air: Option<OnAir>,
+    /// 現在の内容を送信し始めた時刻
+    on_air_since: Instant,
+    /// 送信内容の切り替えに失敗した時の再試行時刻
+    broadcast_retry_at: Option<Instant>,
+    /// ビーコンを送信した時間の累計（TLM の送信回数の見積もり用）
+    beacon_air_ms: u64,
+    started_at: Instant,
     event_sink: Option<Arc<dyn Fn(BleEvent) + Send + Sync>>,
 }
 
@@ -33,6 +102,29 @@ impl Ble {
             error: Arc::new(AtomicBool::new(false)),
             advertiser: None,
             server: None,
+            response: None,
+            events: None,
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            subscribed: Arc::new(AtomicBool::new(false)),
+            connections: Arc::new(Mutex::new(ConnectionTable::new(
+                BleConfig::CONNECTIONS.max as usize,
+            ))),
+            battery: None,
+            battery_level: None,
+            battery_low: false,
+            battery_mv: 0,
+            button_presses: 0,
+            advertised_status: None,
+            console: None,
+            advertise_mode: AdvertiseMode::OpenPairing,
+            scanning: Arc::new(AtomicBool::new(false)),
+            gatt: None,
+            beacon_mode: BleConfig::BEACON.mode,
+            on_air: None,
+            on_air_since: Instant::now(),
+            broadcast_retry_at: None,
+            beacon_air_ms: 0,
+            started_at: Instant::now(),
             event_sink: None,
         }
     }
@@ -41,6 +133,11 @@ impl Ble {
         self.event_sink = Some(sink);
     }
 
+    /// 購読者の有無を共有するフラグ（初期化前に設定する）
+    pub fn set_subscribed_flag(&mut self, subscribed: Arc<AtomicBool>) {
+        self.subscribed = subscribed;
+    }
+
     /// BLEスタック初期化（1回だけ呼ばれる想定）
     pub fn init(&mut self) -> Result<()> {
         if self.advertiser.is_some() {
@@ -53,23 +150,71 @@ impl Ble {
         let server = device.get_server();
         let advertiser = device.get_advertising();
 
-        // 切断時の自動アドバタイズ再開を無効化
+        // 切断時の自動アドバタイズ再開を無効化（再アドバタイズは BleTask が `ble.json` の `reconnect` に従って行う）
         server.advertise_on_disconnect(false);
 
+        security::configure(device);
+
         // ===== GATT Service（最小）=====
         let service = server.create_service(uuid128!(BleConfig::SERVICE_UUID));
         let chr = service.lock().create_characteristic(
             uuid128!(BleConfig::CHARACTERISTIC_UUID),
-            NimbleProperties::READ,
+            security::secured(NimbleProperties::READ),
         );
 
         // キャラクタリスティックに値を設定
         chr.lock().set_value(b"hello");
 
+        // リモートコマンド（書き込み）と実行結果（読み出し/通知）
+        let command_chr = service.lock().create_characteristic(
+            uuid128!(BleConfig::COMMAND_CHARACTERISTIC_UUID),
+            security::secured(NimbleProperties::WRITE | NimbleProperties::WRITE_NO_RSP),
+        );
+        let response_chr = service.lock().create_characteristic(
+            uuid128!(BleConfig::RESPONSE_CHARACTERISTIC_UUID),
+            security::secured(NimbleProperties::READ | NimbleProperties::NOTIFY),
+        );
+
+        // 状態変化・ボタン・LEDイベントの通知（購読している接続を記録）
+        let event_chr = service.lock().create_characteristic(
+            uuid128!(BleConfig::EVENT_CHARACTERISTIC_UUID),
+            security::secured(
+                NimbleProperties::READ | NimbleProperties::NOTIFY | NimbleProperties::INDICATE,
+            ),
+        );
+        let subscribers = self.subscribers.clone();
+        let subscribed = self.subscribed.clone();
+        event_chr.lock().on_subscribe(move |_, desc, sub| {
+            let conn_handle = desc.conn_handle();
+            let mut subscribers = subscribers.lock();
+            subscribers.retain(|&h| h != conn_handle);
+            if !sub.is_empty() {
+                subscribers.push(conn_handle);
+            }
+            subscribed.store(!subscribers.is_empty(), Ordering::Release);
+            log::info!(
+                "BLE event subscription changed (conn {}: {:?}, subscribers: {})",
+                conn_handle,
+                sub,
+                subscribers.len()
+            );
+        });
+
+        standard_services::create_device_info(server);
+        if BATTERY.is_some() {
+            self.battery = Some(standard_services::create_battery(
+                server,
+                self.battery_level,
+            ));
+        }
+
         log::info!(
-            "GATT service created: {}, characteristic: {}",
+            "GATT service created: {}, characteristic: {}, command: {}, response: {}, event: {}",
             BleConfig::SERVICE_UUID,
-            BleConfig::CHARACTERISTIC_UUID
+            BleConfig::CHARACTERISTIC_UUID,
+            BleConfig::COMMAND_CHARACTERISTIC_UUID,
+            BleConfig::RESPONSE_CHARACTERISTIC_UUID,
+            BleConfig::EVENT_CHARACTERISTIC_UUID
         );
         log::debug!("GATT service and characteristic created");
 
@@ -78,63 +223,281 @@ impl Ble {
             let disconnect_sink = sink.clone();
             let advertiser_on_connect = advertiser;
             let advertising_state = self.advertising.clone();
+            let subscribers_on_disconnect = self.subscribers.clone();
+            let subscribed_on_disconnect = self.subscribed.clone();
+            let connections_on_connect = self.connections.clone();
+            let connections_on_disconnect = self.connections.clone();
+
+            // 上限を超えた接続は切断し、上限に達するまではアドバタイズを続ける
+            // （NimBLE は接続時にアドバタイズを止めるため再開する）
+            server.on_connect(move |server, desc| {
+                let peer = connection_of(desc);
+                let (accepted, count, full) = {
+                    let mut connections = connections_on_connect.lock();
+                    let accepted = connections.insert(peer.clone());
+                    (accepted, connections.len(), connections.is_full())
+                };
+                if !accepted {
+                    log::warn!(
+                        "BLE connection limit reached; rejecting {} (conn {})",
+                        peer.address,
+                        peer.conn_handle
+                    );
+                    if let Err(e) = server.disconnect(peer.conn_handle) {
+                        log::error!("Failed to reject connection: {e:?}");
+                    }
+                    return;
+                }
+                log::info!(
+                    "BLE device connected: {} (conn {}, {}/{})",
+                    peer.address,
+                    peer.conn_handle,
+                    count,
+                    BleConfig::CONNECTIONS.max
+                );
+                security::renew_passkey(&peer.address);
 
-            server.on_connect(move |_, _| {
-                log::info!("BLE device connected - auto-stopping advertising");
-                match advertiser_on_connect.lock().stop() {
-                    Ok(_) => {
+                let mut advertiser = advertiser_on_connect.lock();
+                if BleConfig::CONNECTIONS.keep_advertising
+                    && !full
+                    && advertising_state.load(Ordering::Acquire)
+                {
+                    if let Err(e) = advertiser.start() {
+                        log::error!("Failed to resume advertising on connect: {e:?}");
                         advertising_state.store(false, Ordering::Release);
                     }
-                    Err(e) => {
-                        log::error!("Failed to stop advertising on connect: {e:?}");
+                } else {
+                    log::info!("Stopping advertising on connect");
+                    match advertiser.stop() {
+                        Ok(_) => {
+                            advertising_state.store(false, Ordering::Release);
+                        }
+                        Err(e) => {
+                            log::error!("Failed to stop advertising on connect: {e:?}");
+                        }
                     }
                 }
-                (connect_sink)(BleEvent::Connected);
+                drop(advertiser);
+                (connect_sink)(BleEvent::Connected { peer, count });
             });
 
-            server.on_disconnect(move |_, _| {
-                log::info!("BLE device disconnected");
-                (disconnect_sink)(BleEvent::Disconnected);
+            // ペアリング結果（失敗時は NimBLE 側で切断される）
+            let auth_sink = sink.clone();
+            let connections_on_auth = self.connections.clone();
+            server.on_authentication_complete(move |_, desc, result| match result {
+                Ok(()) => {
+                    log::info!(
+                        "BLE pairing completed (conn {}, bonded: {})",
+                        desc.conn_handle(),
+                        desc.bonded()
+                    );
+                    if let Some(connection) = connections_on_auth.lock().get_mut(desc.conn_handle())
+                    {
+                        // ボンド後は ID アドレスで識別する
+                        connection.address = desc.address().to_string();
+                        connection.bonded = desc.bonded();
+                    }
+                    (auth_sink)(BleEvent::PairingCompleted {
+                        bonded: desc.bonded(),
+                    });
+                }
+                Err(e) => {
+                    log::warn!("BLE pairing failed (conn {}): {e:?}", desc.conn_handle());
+                    (auth_sink)(BleEvent::PairingFailed);
+                }
+            });
+
+            server.on_disconnect(move |desc, _| {
+                let conn_handle = desc.conn_handle();
+                {
+                    let mut subscribers = subscribers_on_disconnect.lock();
+                    subscribers.retain(|&h| h != conn_handle);
+                    subscribed_on_disconnect.store(!subscribers.is_empty(), Ordering::Release);
+                }
+                let (peer, count) = {
+                    let mut connections = connections_on_disconnect.lock();
+                    (connections.remove(conn_handle), connections.len())
+                };
+                // 上限超過で拒否した接続は通知しない
+                let Some(peer) = peer else {
+                    log::debug!("Rejected connection closed (conn {})", conn_handle);
+                    return;
+                };
+                log::info!(
+                    "BLE device disconnected: {} (conn {}, {} remaining)",
+                    peer.address,
+                    conn_handle,
+                    count
+                );
+                (disconnect_sink)(BleEvent::Disconnected { peer, count });
             });
             log::debug!("Connection callbacks registered");
+
+            // 解析できたコマンドはイベントとして転送し、解析エラーはその場で応答する
+            // （`MAGIC` で始まる書き込みはバイナリフレームとして再構成してから解析）
+            let command_sink = sink.clone();
+            let response_on_error = response_chr.clone();
+            let connections_on_write = self.connections.clone();
+            // 分割フレームは接続ごとに再構成する
+            let mut reassemblers: Vec<(u16, Reassembler<{ wire::MAX_MESSAGE_LEN }>)> = Vec::new();
+            command_chr.lock().on_write(move |args| {
+                let conn_handle = args.desc().conn_handle();
+                let mtu = args.desc().mtu();
+                if let Some(connection) = connections_on_write.lock().get_mut(conn_handle) {
+                    connection.mtu = mtu;
+                }
+                let data = args.recv_data();
+
+                if !wire::is_frame(data) {
+                    match command_parser::parse(data) {
+                        Ok(command) => {
+                            log::info!("BLE remote command: {:?}", command);
+                            let reply = ReplyTo {
+                                route: ReplyRoute::Text,
+                                conn_handle,
+                                mtu,
+                            };
+                            (command_sink)(BleEvent::RemoteCommand { command, reply });
+                        }
+                        Err(e) => {
+                            log::warn!("BLE remote command rejected: {e}");
+                            notify_to(
+                                &response_on_error,
+                                conn_handle,
+                                format!("err {e}").as_bytes(),
+                            );
+                        }
+                    }
+                    return;
+                }
+
+                let reassembler = per_connection(
+                    &mut reassemblers,
+                    conn_handle,
+                    &connections_on_write,
+                    Reassembler::new,
+                );
+                let result = match reassembler.push(data) {
+                    // 残りの分割を待つ
+                    Ok(None) => return,
+                    Ok(Some(payload)) => match Message::decode(payload) {
+                        Ok(Message::Command(command)) => wire::to_remote_command(&command)
+                            .map_err(|e| (ErrorCode::InvalidArgument, e)),
+                        Ok(other) => Err((
+                            ErrorCode::InvalidArgument,
+                            format!("not a command: {other:?}"),
+                        )),
+                        Err(e) => Err((ErrorCode::Malformed, e.to_string())),
+                    },
+                    Err(e) => Err((ErrorCode::Malformed, e.to_string())),
+                };
+                // 応答には要求と同じ msg_id を付ける（フレームとして読めなければ 0）
+                let msg_id = reassembler.msg_id().unwrap_or(0);
+
+                match result {
+                    Ok(command) => {
+                        log::info!("BLE remote command (binary): {:?}", command);
+                        let reply = ReplyTo {
+                            route: ReplyRoute::Binary { msg_id },
+                            conn_handle,
+                            mtu,
+                        };
+                        (command_sink)(BleEvent::RemoteCommand { command, reply });
+                    }
+                    Err((code, message)) => {
+                        log::warn!("BLE binary command rejected: {message}");
+                        let message = Message::Error {
+                            code,
+                            message: &message,
+                        };
+                        match wire::encode_frames(&message, msg_id, mtu) {
+                            Ok(frames) => {
+                                for frame in frames {
+                                    notify_to(&response_on_error, conn_handle, &frame);
+                                }
+                            }
+                            Err(e) => log::error!("Failed to encode binary response: {e}"),
+                        }
+                    }
+                }
+            });
+            log::debug!("Command characteristic callback registered");
+
+            if BleConfig::NUS_ENABLED {
+                self.console =
+                    Some(self.init_console(server, sink.clone(), self.connections.clone()));
+            }
         } else {
             log::warn!("Event sink not set, callbacks not registered");
         }
 
         // ===== Advertise データ =====
-        advertiser
-            .lock()
-            .set_data(
-                BLEAdvertisementData::new()
-                    .name(BleConfig::DEVICE_NAME)
-                    .add_service_uuid(uuid128!(BleConfig::SERVICE_UUID)),
-            )
-            .map_err(|e| Error::new_esp(&format!("set adv data failed: {e:?}")))?;
+        let status = self.adv_status();
+        advertising::configure(advertiser, &Self::encode_status(&status))?;
+        self.advertised_status = Some(status);
         log::debug!("Advertisement data configured");
 
         self.server = Some(server);
         self.advertiser = Some(advertiser);
+        self.response = Some(response_chr);
+        self.events = Some(event_chr);
         log::info!("BLE initialization completed");
 
         Ok(())
     }
 
     /// ペアリング(アドバタイズ)を開始
-    pub fn start_pairing(&mut self) -> Result<()> {
-        log::debug!("start_pairing called");
+    ///
+    /// 別のモードでアドバタイズ中なら、停止してから指定のモードでやり直す。
+    pub fn start_pairing(&mut self, mode: AdvertiseMode) -> Result<()> {
+        log::debug!("start_pairing called ({:?})", mode);
         self.init()?;
 
         if self.is_advertising() {
-            log::debug!("Advertising already active, skipping start");
-            return Ok(());
+            if self.advertise_mode == mode {
+                log::debug!("Advertising already active, skipping start");
+                return Ok(());
+            }
+            self.stop_pairing()?;
         }
 
-        if let Some(adv) = &self.advertiser {
-            adv.lock()
-                .start()
+        if let Some(advertiser) = self.advertiser {
+            // ビーコンを送信中なら、接続を受け付ける内容に戻してから開始する
+            if let Some(OnAir::Beacon(_)) = self.on_air {
+                self.take_off_air(advertiser);
+                let status = self.adv_status();
+                advertising::set_connectable(
+                    &mut advertiser.lock(),
+                    &Self::encode_status(&status),
+                )?;
+                self.advertised_status = Some(status);
+            }
+
+            let mut adv = advertiser.lock();
+            match mode {
+                AdvertiseMode::BondedOnly => {
+                    // ボンド済みの相手だけを許可リストに載せ、それ以外からの接続要求は無視する
+                    let bonds = security::bonded_peers()?;
+                    if bonds.is_empty() {
+                        return Err(Error::new_invalid_state(
+                            "no bonded peers; use open pairing first",
+                        ));
+                    }
+                    security::set_accept_list(&bonds)?;
+                    adv.filter_policy(AdvFilterPolicy::Both);
+                    log::info!("Advertising to {} bonded peer(s)", bonds.len());
+                }
+                AdvertiseMode::OpenPairing => {
+                    adv.filter_policy(AdvFilterPolicy::None);
+                }
+            }
+            adv.start()
                 .map_err(|e| Error::new_esp(&format!("adv start failed: {e:?}")))?;
             self.advertising.store(true, Ordering::Release);
-            log::info!("Advertising started");
+            self.advertise_mode = mode;
+            self.on_air = Some(OnAir::Connectable);
+            self.on_air_since = Instant::now();
+            log::info!("Advertising started ({:?})", mode);
         }
         Ok(())
     }
@@ -153,6 +516,8 @@ impl Ble {
                 .stop()
                 .map_err(|e| Error::new_esp(&format!("adv stop failed: {e:?}")))?;
             self.advertising.store(false, Ordering::Release);
+            // 交互送信中のビーコンも止まるため、update_broadcast で送信し直す
+            self.take_off_air_state();
             log::info!("Advertising stopped");
         } else {
             log::warn!("Advertiser not initialized; skipping stop");
@@ -161,18 +526,435 @@ impl Ble {
         Ok(())
     }
 
-    pub fn on_disconnected(&mut self) -> Result<()> {
-        // TODO: 切断後の処理
+    /// Nordic UART Service を作成し、RX に書き込まれた行をコマンドとして転送する
+    fn init_console(
+        &self,
+        server: &mut BLEServer,
+        sink: Arc<dyn Fn(BleEvent) + Send + Sync>,
+        connections: Arc<Mutex<ConnectionTable>>,
+    ) -> Arc<Mutex<BLECharacteristic>> {
+        let service = server.create_service(uuid128!(BleConfig::NUS_SERVICE_UUID));
+        let rx_chr = service.lock().create_characteristic(
+            uuid128!(BleConfig::NUS_RX_CHARACTERISTIC_UUID),
+            security::secured(NimbleProperties::WRITE | NimbleProperties::WRITE_NO_RSP),
+        );
+        let tx_chr = service.lock().create_characteristic(
+            uuid128!(BleConfig::NUS_TX_CHARACTERISTIC_UUID),
+            security::secured(NimbleProperties::NOTIFY),
+        );
+
+        // 解析エラーはその場で応答し、解析できたコマンドはテキストと同じ経路で実行する
+        let tx_on_error = tx_chr.clone();
+        // 行は接続ごとに区切る
+        let mut buffers: Vec<(u16, LineBuffer)> = Vec::new();
+        rx_chr.lock().on_write(move |args| {
+            let conn_handle = args.desc().conn_handle();
+            let mtu = args.desc().mtu();
+            let lines = per_connection(&mut buffers, conn_handle, &connections, LineBuffer::new);
+            for line in lines.push(args.recv_data()) {
+                let result = match line {
+                    Line::Command(line) => command_parser::parse(&line),
+                    Line::TooLong(len) => Err(command_parser::ParseError::TooLong(len)),
+                };
+                match result {
+                    Ok(command) => {
+                        log::info!("BLE console command: {:?}", command);
+                        let reply = ReplyTo {
+                            route: ReplyRoute::Console,
+                            conn_handle,
+                            mtu,
+                        };
+                        (sink)(BleEvent::RemoteCommand { command, reply });
+                    }
+                    Err(e) => {
+                        log::warn!("BLE console command rejected: {e}");
+                        let message = format!("err {e}");
+                        for chunk in console::to_chunks(&message, mtu) {
+                            notify_to(&tx_on_error, conn_handle, &chunk);
+                        }
+                    }
+                }
+            }
+        });
+
+        log::info!("NUS console created: {}", BleConfig::NUS_SERVICE_UUID);
+        tx_chr
+    }
+
+    /// コマンド実行結果を、コマンドを受け付けた接続・経路で通知する
+    ///
+    /// テキストとバイナリフレームはレスポンスキャラクタリスティック、コンソールは NUS の TX に送る。
+    /// 分割は書き込み時の MTU に合わせる。
+    pub fn respond(&self, reply: &ReplyTo, message: &str) -> Result<()> {
+        if reply.route == ReplyRoute::Console {
+            let console = self
+                .console
+                .as_ref()
+                .ok_or_else(|| Error::new_invalid_state("NUS console not initialized"))?;
+            for chunk in console::to_chunks(message, reply.mtu) {
+                notify_to(console, reply.conn_handle, &chunk);
+            }
+            return Ok(());
+        }
+
+        let response = self
+            .response
+            .as_ref()
+            .ok_or_else(|| Error::new_invalid_state("BLE not initialized; cannot respond"))?;
+
+        let msg_id = match reply.route {
+            ReplyRoute::Binary { msg_id } => msg_id,
+            _ => {
+                for chunk in console::to_chunks(message, reply.mtu) {
+                    notify_to(response, reply.conn_handle, &chunk);
+                }
+                return Ok(());
+            }
+        };
+        let frames = wire::encode_frames(&wire::to_response(message), msg_id, reply.mtu)
+            .map_err(|e| Error::new_unexpected(&format!("failed to encode response: {e}")))?;
+        for frame in frames {
+            notify_to(response, reply.conn_handle, &frame);
+        }
+        Ok(())
+    }
+
+    /// バッテリー残量（%）を Battery Service に反映し、購読中のクライアントに通知
+    pub fn set_battery_level(&mut self, percent: u8, mv: u32, low: bool) {
+        self.battery_level = Some(percent);
+        self.battery_mv = mv.min(u16::MAX as u32) as u16;
+        self.battery_low = low;
+        if let Some(battery) = &self.battery {
+            battery.lock().set_value(&[percent]).notify();
+        }
+    }
+
+    /// ボタン操作をアドバタイズの状態データ用に数える
+    pub fn record_button(&mut self, event: &ButtonEvent) {
+        if adv_status::is_press(event) {
+            self.button_presses = self.button_presses.wrapping_add(1);
+        }
+    }
+
+    /// 現在の状態をアドバタイズに反映する（前回から変化が無ければ何もしない）
+    pub fn refresh_adv_status(&mut self) -> Result<()> {
+        let Some(advertiser) = self.advertiser else {
+            return Ok(());
+        };
+        // ビーコンの送信中は、接続を受け付ける内容に戻す時に反映する
+        if let Some(OnAir::Beacon(_)) = self.on_air {
+            return Ok(());
+        }
+        let status = self.adv_status();
+        if self.advertised_status == Some(status) {
+            return Ok(());
+        }
+        advertising::update_status(advertiser, &Self::encode_status(&status))?;
+        log::debug!("Advertised status updated: {:?}", status);
+        self.advertised_status = Some(status);
+        Ok(())
+    }
+
+    fn adv_status(&self) -> AdvStatus {
+        AdvStatus {
+            error: self.has_error(),
+            connected: self.is_connected(),
+            battery_percent: self.battery_level,
+            battery_low: self.battery_low,
+            button_presses: self.button_presses,
+        }
+    }
+
+    /// 設定の Company ID とファームウェアのバージョンを付けて状態データにする
+    fn encode_status(status: &AdvStatus) -> [u8; STATUS_DATA_LEN] {
+        let company_id = BleConfig::ADVERTISING
+            .status
+            .map_or(0xFFFF, |status| status.company_id);
+        status.encode(
+            company_id,
+            adv_status::firmware_version(
+                env!("CARGO_PKG_VERSION_MAJOR"),
+                env!("CARGO_PKG_VERSION_MINOR"),
+                env!("CARGO_PKG_VERSION_PATCH"),
+            ),
+        )
+    }
+
+    /// 周辺デバイスのスキャンを別スレッドで開始する（アドバタイズ・接続は継続）
+    ///
+    /// 結果は終了時に `BleEvent::ScanResults` / `BleEvent::ScanFailed` で通知する。
+    pub fn start_scan(&mut self, duration_ms: u32, filter: ScanFilter) -> Result<()> {
+        self.init()?;
+        let sink = self
+            .event_sink
+            .clone()
+            .ok_or_else(|| Error::new_invalid_state("event sink not set"))?;
+        if self.scanning.swap(true, Ordering::AcqRel) {
+            return Err(Error::new_invalid_state("scan already in progress"));
+        }
+
+        let scanning = self.scanning.clone();
+        let spawned = thread::Builder::new()
+            .name("ble_scan".into())
+            .stack_size(4096)
+            .spawn(move || {
+                let event = match run_scan(duration_ms, filter) {
+                    Ok(devices) => {
+                        log::info!("Scan completed: {} device(s)", devices.len());
+                        BleEvent::ScanResults(devices)
+                    }
+                    Err(e) => {
+                        log::error!("Failed to scan: {e}");
+                        BleEvent::ScanFailed
+                    }
+                };
+                scanning.store(false, Ordering::Release);
+                (sink)(event);
+            });
+        if let Err(e) = spawned {
+            self.scanning.store(false, Ordering::Release);
+            return Err(Error::new_unexpected(&format!(
+                "failed to spawn ble_scan: {e}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// GATT クライアントとしてリモートのペリフェラルを操作する
+    ///
+    /// 要求は GATT クライアントのスレッドで順に処理し、結果は `BleEvent::GattResponse` で通知する。
+    pub fn gatt(&mut self, request: GattRequest) -> Result<()> {
+        self.init()?;
+        if self.gatt.is_none() {
+            let sink = self
+                .event_sink
+                .clone()
+                .ok_or_else(|| Error::new_invalid_state("event sink not set"))?;
+            self.gatt = Some(GattWorker::start(sink)?);
+        }
+        match &self.gatt {
+            Some(gatt) => gatt.request(request),
+            None => Err(Error::new_invalid_state("GATT worker not started")),
+        }
+    }
+
+    /// ビーコンのモードを切り替える（反映は `update_broadcast`）
+    pub fn set_beacon_mode(&mut self, mode: BeaconMode) -> Result<()> {
+        if mode != BeaconMode::Off && beacon::frames(mode, &BleConfig::BEACON).is_empty() {
+            return Err(Error::new_invalid_state(&format!(
+                "beacon mode {mode:?} is not configured"
+            )));
+        }
+        log::info!("Beacon mode: {:?} -> {:?}", self.beacon_mode, mode);
+        self.beacon_mode = mode;
+        self.broadcast_retry_at = None;
+        Ok(())
+    }
+
+    /// ビーコンの送信と、ペアリング中の交互送信を進める（BLE タスクから周期的に呼ぶ）
+    pub fn update_broadcast(&mut self) -> Result<()> {
+        let Some(advertiser) = self.advertiser else {
+            return Ok(());
+        };
+        if self
+            .broadcast_retry_at
+            .is_some_and(|retry_at| Instant::now() < retry_at)
+        {
+            return Ok(());
+        }
+        let config = BleConfig::BEACON;
+
+        // 送信する内容の列（slot_ms ごとに順に切り替える）
+        let mut slots: Vec<OnAir> = beacon::frames(self.beacon_mode, &config)
+            .into_iter()
+            .map(OnAir::Beacon)
+            .collect();
+        if self.is_advertising() {
+            if !config.interleave {
+                slots.clear();
+            }
+            slots.push(OnAir::Connectable);
+        }
+
+        let Some(&first) = slots.first() else {
+            if let Some(OnAir::Beacon(_)) = self.on_air {
+                self.take_off_air(advertiser);
+                log::info!("Beacon stopped");
+            }
+            return Ok(());
+        };
+
+        let current = self
+            .on_air
+            .and_then(|on_air| slots.iter().position(|slot| *slot == on_air));
+        let next = match current {
+            None => first,
+            Some(i) => {
+                let slot_elapsed =
+                    self.on_air_since.elapsed() >= Duration::from_millis(config.slot_ms as u64);
+                // TLM は1つだけでも値を更新するため送り直す
+                let refresh = slots.len() > 1
+                    || slots[i] == OnAir::Beacon(BeaconFrame::Eddystone(EddystoneFrame::Tlm));
+                if !(slot_elapsed && refresh) {
+                    return Ok(());
+                }
+                slots[(i + 1) % slots.len()]
+            }
+        };
+
+        if let Err(e) = self.put_on_air(advertiser, next) {
+            self.broadcast_retry_at =
+                Some(Instant::now() + Duration::from_millis(config.slot_ms as u64));
+            return Err(e);
+        }
+        self.broadcast_retry_at = None;
+        Ok(())
+    }
+
+    /// 送信内容を切り替えて送信を開始する
+    fn put_on_air(&mut self, advertiser: &Mutex<BLEAdvertising>, slot: OnAir) -> Result<()> {
+        self.take_off_air(advertiser);
+        match slot {
+            OnAir::Connectable => {
+                let status = self.adv_status();
+                advertising::set_connectable(
+                    &mut advertiser.lock(),
+                    &Self::encode_status(&status),
+                )?;
+                self.advertised_status = Some(status);
+            }
+            OnAir::Beacon(frame) => {
+                let payload = beacon::payload(frame, &BleConfig::BEACON, &self.telemetry())
+                    .ok_or_else(|| Error::new_invalid_state("beacon frame not configured"))?;
+                advertising::set_beacon(&mut advertiser.lock(), &payload)?;
+            }
+        }
+        advertiser
+            .lock()
+            .start()
+            .map_err(|e| Error::new_esp(&format!("adv start failed: {e:?}")))?;
+        self.on_air = Some(slot);
+        self.on_air_since = Instant::now();
+        log::debug!("On air: {:?}", slot);
+        Ok(())
+    }
+
+    /// 送信を止める（既に止まっていても構わない）
+    fn take_off_air(&mut self, advertiser: &Mutex<BLEAdvertising>) {
+        if let Err(e) = advertiser.lock().stop() {
+            log::debug!("adv stop before switching: {e:?}");
+        }
+        self.take_off_air_state();
+    }
+
+    fn take_off_air_state(&mut self) {
+        if let Some(OnAir::Beacon(_)) = self.on_air {
+            self.beacon_air_ms += self.on_air_since.elapsed().as_millis() as u64;
+        }
+        self.on_air = None;
+    }
+
+    fn telemetry(&self) -> Telemetry {
+        Telemetry {
+            battery_mv: self.battery_mv,
+            adv_count: (self.beacon_air_ms / BleConfig::BEACON.interval_ms as u64) as u32,
+            uptime_ds: (self.started_at.elapsed().as_millis() / 100) as u32,
+        }
+    }
+
+    /// イベント通知の購読者がいるか
+    pub fn has_subscribers(&self) -> bool {
+        self.subscribed.load(Ordering::Acquire)
+    }
+
+    /// イベントを購読者に通知（購読者がいなければ何もしない）
+    pub fn notify(&self, notification: &BleNotification) {
+        let Some(events) = self.events.as_ref() else {
+            return;
+        };
+        if !self.has_subscribers() {
+            return;
+        }
+        let text = notification.to_text();
+        log::debug!("BLE notify: {}", text);
+        events.lock().set_value(text.as_bytes()).notify();
+    }
+
+    /// ボンド済みの相手がいるか（読み出せなければ いない とみなす）
+    pub fn has_bonds(&self) -> bool {
+        match security::bonded_peers() {
+            Ok(bonds) => !bonds.is_empty(),
+            Err(e) => {
+                log::warn!("{e}");
+                false
+            }
+        }
+    }
+
+    /// ペアリング中の相手が表示したパスキーを入力する（`security.mode` が `passkey_entry` の時）
+    pub fn enter_passkey(&mut self, passkey: u32) -> Result<()> {
+        if BleConfig::SECURITY.mode != BleSecurityMode::PasskeyEntry {
+            return Err(Error::new_invalid_state("passkey entry is not enabled"));
+        }
+        let pairing: Vec<u16> = self
+            .connections
+            .lock()
+            .to_vec()
+            .iter()
+            .filter(|c| !c.bonded)
+            .map(|c| c.conn_handle)
+            .collect();
+        // 入力待ちの接続だけが受け付ける
+        match pairing
+            .into_iter()
+            .find(|&conn_handle| security::inject_passkey(conn_handle, passkey).is_ok())
+        {
+            Some(conn_handle) => {
+                log::info!("BLE passkey entered for conn {}", conn_handle);
+                Ok(())
+            }
+            None => Err(Error::new_invalid_state(
+                "no pairing is waiting for a passkey",
+            )),
+        }
+    }
+
+    /// 保存済みのボンド情報をすべて削除（以降の接続では再ペアリングが必要）
+    pub fn clear_bonds(&mut self) -> Result<()> {
+        security::clear_bonds()?;
+        log::info!("BLE bonds cleared");
         Ok(())
     }
 
+    /// GATT クライアントとしての接続をすべて切断する
+    pub fn disconnect_remote_peers(&mut self) {
+        if let Some(gatt) = &self.gatt {
+            gatt.disconnect_all();
+        }
+    }
+
     /// 現在のBLE接続状態を取得
     pub fn is_connected(&self) -> bool {
-        if let Some(server) = &self.server {
-            server.connected_count() > 0
-        } else {
-            false
+        !self.connections.lock().is_empty()
+    }
+
+    /// 接続数が上限に達しているか
+    pub fn is_connection_full(&self) -> bool {
+        self.connections.lock().is_full()
+    }
+
+    /// 接続中のセントラルの一覧（RSSI はこの時点で測定する）
+    pub fn connections(&self) -> Vec<Connection> {
+        let mut connections = self.connections.lock();
+        for connection in connections.iter_mut() {
+            connection.rssi = conn_rssi(connection.conn_handle);
         }
+        connections.to_vec()
+    }
+
+    /// 現在（または最後）のアドバタイズのモード
+    pub fn advertise_mode(&self) -> AdvertiseMode {
+        self.advertise_mode
     }
 
     /// 現在のアドバタイズ状態を取得
@@ -194,8 +976,93 @@ impl Ble {
     pub fn state(&self) -> BleState {
         BleState {
             connected: self.is_connected(),
+            connections: self.connections.lock().len() as u8,
             advertising: self.is_advertising(),
+            // 再接続の状態は BleTask が持つ
+            reconnecting: false,
             error: self.has_error(),
         }
     }
 }
+
+/// 周辺デバイスをスキャンする（終わるまでブロックする、`Ble::start_scan` のスレッドで実行）
+fn run_scan(duration_ms: u32, filter: ScanFilter) -> Result<Vec<ScannedDevice>> {
+    let mut collector = ScanCollector::new(filter);
+    let mut scan = BLEScan::new();
+    // 名前はスキャンレスポンスで届くことが多いため、アクティブスキャンにする
+    scan.active_scan(true).interval(100).window(99);
+    block_on(scan.start(
+        BLEDevice::take(),
+        duration_ms.min(MAX_SCAN_DURATION_MS) as i32,
+        |device, data| {
+            let manufacturer_data = data
+                .manufacture_data()
+                .map(|m| {
+                    let mut bytes = m.company_identifier.to_le_bytes().to_vec();
+                    bytes.extend_from_slice(m.payload);
+                    bytes
+                })
+                .unwrap_or_default();
+            collector.push(ScannedDevice {
+                address: device.addr().to_string(),
+                rssi: device.rssi() as i32,
+                name: data.name().map(|name| name.to_string()),
+                service_uuids: data
+                    .service_uuids()
+                    .map(|uuid| uuid.to_string().to_ascii_lowercase())
+                    .collect(),
+                manufacturer_data,
+            });
+            None::<()>
+        },
+    ))
+    .map_err(|e| Error::new_esp(&format!("scan failed: {e:?}")))?;
+
+    Ok(collector.finish())
+}
+
+/// 接続ディスクリプタから接続情報を作る
+fn connection_of(desc: &BLEConnDesc) -> Connection {
+    Connection {
+        conn_handle: desc.conn_handle(),
+        address: desc.address().to_string(),
+        mtu: desc.mtu(),
+        rssi: None,
+        bonded: desc.bonded(),
+    }
+}
+
+/// 接続の RSSI（dBm）を測定する（切断済みなどで測定できなければ None）
+fn conn_rssi(conn_handle: u16) -> Option<i8> {
+    let mut rssi: i8 = 0;
+    // SAFETY: rssi は呼び出しの間有効なローカル変数で、NimBLE は成功時にだけ書き込む。
+    // 存在しない conn_handle はエラーコードで返り、ホストのロックは関数内で取られる。
+    let rc = unsafe { esp_idf_sys::ble_gap_conn_rssi(conn_handle, &mut rssi) };
+    (rc == 0).then_some(rssi)
+}
+
+/// コマンドを書き込んだ接続にだけ通知する（他の購読者には送らない）
+fn notify_to(chr: &Mutex<BLECharacteristic>, conn_handle: u16, value: &[u8]) {
+    if let Err(e) = chr.lock().notify_with(value, conn_handle) {
+        log::warn!("Failed to notify conn {}: {e:?}", conn_handle);
+    }
+}
+
+/// 接続ごとの受信状態を取り出す（無ければ作り、切断済みの接続の分は捨てる）
+fn per_connection<'a, T>(
+    states: &'a mut Vec<(u16, T)>,
+    conn_handle: u16,
+    connections: &Mutex<ConnectionTable>,
+    new: impl FnOnce() -> T,
+) -> &'a mut T {
+    let index = match states.iter().position(|(handle, _)| *handle == conn_handle) {
+        Some(index) => index,
+        None => {
+            let mut connections = connections.lock();
+            states.retain(|(handle, _)| connections.get_mut(*handle).is_some());
+            states.push((conn_handle, new()));
+            states.len() - 1
+        }
+    };
+    &mut states[index].1
+}
@@ -0,0 +1,99 @@
+use crate::app::ble::ble_state::BleState;
+use crate::app::ble::gatt_client::{self, GattEvent};
+use crate::app::button::event::{ButtonEvent, ButtonGesture, ButtonId, ButtonIdExt, ChordGesture};
+use crate::app::led::led_event::LedEvent;
+use crate::app::led::led_state::LedState;
+
+/// イベントキャラクタリスティックで購読者に通知する内容
+#[derive(Debug, Clone)]
+pub(crate) enum BleNotification {
+    /// 接続/アドバタイズ状態の変化
+    State(BleState),
+    /// ボタンのジェスチャ
+    Button(ButtonEvent),
+    /// LEDの表示の変化
+    Led(LedEvent),
+    /// GATT クライアントとして接続したリモートからの通知・切断
+    Gatt(GattEvent),
+}
+
+impl BleNotification {
+    /// 通知するテキスト（1イベント1行、空白区切り）
+    ///
+    /// - `state connected=<0|1> advertising=<0|1> reconnecting=<0|1> error=<0|1>`
+    /// - `button <name> <gesture>` / `chord <name>+<name> <gesture>`
+    /// - `led <name> <source|none> <state>`
+    /// - `gatt <address> <characteristic> <hex>` / `gatt <address> disconnected`
+    pub fn to_text(&self) -> String {
+        match self {
+            BleNotification::State(state) => format!(
+                "state connected={} advertising={} reconnecting={} error={}",
+                state.connected as u8,
+                state.advertising as u8,
+                state.reconnecting as u8,
+                state.error as u8
+            ),
+            BleNotification::Button(ButtonEvent::Gesture { button, gesture }) => {
+                let gesture = match gesture {
+                    ButtonGesture::ShortPress => "short".to_string(),
+                    ButtonGesture::DoubleClick => "double".to_string(),
+                    ButtonGesture::TripleClick => "triple".to_string(),
+                    ButtonGesture::LongPress => "long".to_string(),
+                    ButtonGesture::VeryLongPress => "very_long".to_string(),
+                    ButtonGesture::Released { held_ms } => format!("released held_ms={held_ms}"),
+                };
+                format!("button {} {gesture}", button.name())
+            }
+            BleNotification::Button(ButtonEvent::Chord { buttons, gesture }) => {
+                let names: Vec<&str> = (0..8)
+                    .filter(|i| buttons.0 & (1 << i) != 0)
+                    .map(|i| ButtonId(i).name())
+                    .collect();
+                let gesture = match gesture {
+                    ChordGesture::Pressed => "pressed".to_string(),
+                    ChordGesture::LongPress => "long".to_string(),
+                    ChordGesture::Released { held_ms } => format!("released held_ms={held_ms}"),
+                };
+                format!("chord {} {gesture}", names.join("+"))
+            }
+            BleNotification::Led(LedEvent::StateChanged { led, source, state }) => {
+                let source = match source {
+                    Some(source) => format!("{source:?}").to_ascii_lowercase(),
+                    None => "none".to_string(),
+                };
+                let state = match state {
+                    LedState::On => "on".to_string(),
+                    LedState::Off => "off".to_string(),
+                    LedState::Brightness(level) => format!("level={level}"),
+                    LedState::Blink {
+                        on_ms,
+                        off_ms,
+                        count,
+                    } => match count {
+                        Some(count) => format!("blink on_ms={on_ms} off_ms={off_ms} count={count}"),
+                        None => format!("blink on_ms={on_ms} off_ms={off_ms}"),
+                    },
+                    LedState::FadeTo { level, duration_ms } => {
+                        format!("fade level={level} duration_ms={duration_ms}")
+                    }
+                    LedState::Breathe { period_ms } => format!("breathe period_ms={period_ms}"),
+                    LedState::Pattern(_) => "pattern".to_string(),
+                };
+                format!("led {} {source} {state}", led.name())
+            }
+            BleNotification::Gatt(GattEvent::Notification {
+                address,
+                characteristic,
+                value,
+            }) => format!(
+                "gatt {address} {characteristic} {}",
+                gatt_client::to_hex(value)
+            ),
+            BleNotification::Gatt(GattEvent::Disconnected { address }) => {
+                format!("gatt {address} disconnected")
+            }
+            // 要求への結果はコマンドの応答として返す
+            BleNotification::Gatt(event) => format!("gatt {}", event.to_response()),
+        }
+    }
+}
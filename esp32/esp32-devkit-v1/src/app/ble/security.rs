@@ -0,0 +1,124 @@
+//! ペアリング・ボンディングと、キャラクタリスティックに要求する暗号化の設定（`ble.json` の `security`）
+
+use esp32_nimble::enums::{AuthReq, PairKeyDist, SecurityIOCap};
+use esp32_nimble::{BLEAddress, BLEDevice, NimbleProperties};
+
+use crate::common::{Error, Result};
+use crate::config::ble::{BleConfig, BleSecurityMode};
+
+/// ペアリング方式を NimBLE に設定する（ボンド情報は `CONFIG_BT_NIMBLE_NVS_PERSIST` で NVS に保存）
+pub(super) fn configure(device: &mut BLEDevice) {
+    let security = BleConfig::SECURITY;
+
+    let mut auth = AuthReq::Sc;
+    if security.bonding {
+        auth |= AuthReq::Bond;
+    }
+    let io_cap = match security.mode {
+        BleSecurityMode::None => {
+            log::warn!("BLE security disabled; characteristics are readable without pairing");
+            return;
+        }
+        BleSecurityMode::JustWorks => SecurityIOCap::NoInputNoOutput,
+        BleSecurityMode::PasskeyDisplay => {
+            auth |= AuthReq::Mitm;
+            SecurityIOCap::DisplayOnly
+        }
+        BleSecurityMode::PasskeyEntry => {
+            auth |= AuthReq::Mitm;
+            SecurityIOCap::KeyboardOnly
+        }
+    };
+
+    // ID キー（IRK）も交換し、RPA を使う相手をボンド情報から解決できるようにする
+    let keys = PairKeyDist::ENC | PairKeyDist::ID;
+    device
+        .security()
+        .set_auth(auth)
+        .set_io_cap(io_cap)
+        .set_security_init_key(keys)
+        .set_security_resp_key(keys);
+    match device.bonded_addresses() {
+        Ok(bonds) => log::info!(
+            "BLE security configured ({:?}, bonding: {}, bonded peers: {})",
+            security.mode,
+            security.bonding,
+            bonds.len()
+        ),
+        Err(e) => log::warn!("Failed to read bonded peers: {e:?}"),
+    }
+}
+
+/// 接続ごとに新しいパスキーを作り、ペアリングで表示する値として設定する（パスキー表示方式のみ）
+///
+/// 表示器が無いため、パスキーはシリアルのログ（info）に出す。
+pub(super) fn renew_passkey(peer: &str) {
+    if BleConfig::SECURITY.mode != BleSecurityMode::PasskeyDisplay {
+        return;
+    }
+    // SAFETY: esp_random は引数を取らず、どのタスクからでも呼べる
+    let passkey = unsafe { esp_idf_sys::esp_random() } % 1_000_000;
+    BLEDevice::take().security().set_passkey(passkey);
+    log::info!("BLE passkey for {}: {:06}", peer, passkey);
+}
+
+/// ペアリング中の接続に、相手が表示したパスキーを入力する（パスキー入力方式）
+pub(super) fn inject_passkey(conn_handle: u16, passkey: u32) -> Result<()> {
+    let mut io = esp_idf_sys::ble_sm_io {
+        action: esp_idf_sys::BLE_SM_IOACT_INPUT as u8,
+        __bindgen_anon_1: esp_idf_sys::ble_sm_io__bindgen_ty_1 { passkey },
+    };
+    // SAFETY: io は呼び出しの間有効なローカル変数で、NimBLE は読み取るだけ。
+    // 入力待ちのペアリングが無い conn_handle はエラーコードで返る。
+    let rc = unsafe { esp_idf_sys::ble_sm_inject_io(conn_handle, &mut io) };
+    if rc != 0 {
+        return Err(Error::new_invalid_state(&format!(
+            "conn {conn_handle} is not waiting for a passkey (rc={rc})"
+        )));
+    }
+    Ok(())
+}
+
+/// 読み書きに暗号化（パスキー方式では MITM 保護された暗号化）を要求する
+pub(super) fn secured(mut properties: NimbleProperties) -> NimbleProperties {
+    let (read, write) = match BleConfig::SECURITY.mode {
+        BleSecurityMode::None => return properties,
+        BleSecurityMode::JustWorks => (NimbleProperties::READ_ENC, NimbleProperties::WRITE_ENC),
+        BleSecurityMode::PasskeyDisplay | BleSecurityMode::PasskeyEntry => (
+            NimbleProperties::READ_ENC | NimbleProperties::READ_AUTHEN,
+            NimbleProperties::WRITE_ENC | NimbleProperties::WRITE_AUTHEN,
+        ),
+    };
+
+    if properties.contains(NimbleProperties::READ) {
+        properties |= read;
+    }
+    if properties.intersects(NimbleProperties::WRITE | NimbleProperties::WRITE_NO_RSP) {
+        properties |= write;
+    }
+    properties
+}
+
+/// ボンド済みの相手のアドレス（ID アドレス）
+pub(super) fn bonded_peers() -> Result<Vec<BLEAddress>> {
+    BLEDevice::take()
+        .bonded_addresses()
+        .map_err(|e| Error::new_esp(&format!("failed to read bonded peers: {e:?}")))
+}
+
+/// 接続を受け付ける相手（フィルタ許可リスト）を設定する
+///
+/// RPA を使う相手は、ペアリングで交換した IRK を使ってホスト側のプライバシー機能
+/// （sdkconfig の `CONFIG_BT_NIMBLE_HOST_BASED_PRIVACY`）で解決されるため、ID アドレスのままでよい。
+pub(super) fn set_accept_list(peers: &[BLEAddress]) -> Result<()> {
+    BLEDevice::take()
+        .set_white_list(peers)
+        .map_err(|e| Error::new_esp(&format!("failed to set accept list: {e:?}")))
+}
+
+/// 保存済みのボンド情報をすべて削除する
+pub(super) fn clear_bonds() -> Result<()> {
+    BLEDevice::take()
+        .delete_all_bonds()
+        .map_err(|e| Error::new_esp(&format!("failed to delete bonds: {e:?}")))
+}
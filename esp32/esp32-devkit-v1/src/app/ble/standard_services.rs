@@ -0,0 +1,68 @@
+//! 汎用ツールが認識する標準サービス（Device Information / Battery）
+//!
+//! 識別情報と残量は機密ではないため、ペアリング前でも読み出せるようにする。
+
+use esp32_nimble::{
+    utilities::mutex::Mutex, BLECharacteristic, BLEServer, BleUuid, NimbleProperties,
+};
+use std::sync::Arc;
+
+use crate::config::ble::BleConfig;
+
+const DEVICE_INFORMATION_SERVICE: u16 = 0x180A;
+const MODEL_NUMBER: u16 = 0x2A24;
+const SERIAL_NUMBER: u16 = 0x2A25;
+const FIRMWARE_REVISION: u16 = 0x2A26;
+const MANUFACTURER_NAME: u16 = 0x2A29;
+
+const BATTERY_SERVICE: u16 = 0x180F;
+const BATTERY_LEVEL: u16 = 0x2A19;
+
+/// Device Information Service（ファームウェアのバージョンは Cargo.toml の `version`）
+pub(super) fn create_device_info(server: &mut BLEServer) {
+    let service = server.create_service(BleUuid::from_uuid16(DEVICE_INFORMATION_SERVICE));
+    let serial = serial_number();
+    let values: [(u16, &str); 4] = [
+        (MANUFACTURER_NAME, BleConfig::MANUFACTURER),
+        (MODEL_NUMBER, BleConfig::MODEL),
+        (SERIAL_NUMBER, &serial),
+        (FIRMWARE_REVISION, env!("CARGO_PKG_VERSION")),
+    ];
+    for (uuid, value) in values {
+        service
+            .lock()
+            .create_characteristic(BleUuid::from_uuid16(uuid), NimbleProperties::READ)
+            .lock()
+            .set_value(value.as_bytes());
+    }
+    log::info!(
+        "Device Information Service created (serial: {}, firmware: {})",
+        serial,
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+/// Battery Service（残量 0-100% を1バイトで読み出し/通知、最初の測定までは値を設定しない）
+pub(super) fn create_battery(
+    server: &mut BLEServer,
+    level: Option<u8>,
+) -> Arc<Mutex<BLECharacteristic>> {
+    let service = server.create_service(BleUuid::from_uuid16(BATTERY_SERVICE));
+    let chr = service.lock().create_characteristic(
+        BleUuid::from_uuid16(BATTERY_LEVEL),
+        NimbleProperties::READ | NimbleProperties::NOTIFY,
+    );
+    if let Some(level) = level {
+        chr.lock().set_value(&[level]);
+    }
+    log::info!("Battery Service created");
+    chr
+}
+
+/// eFuse に書き込まれた工場出荷時の MAC アドレス（16進12桁）
+fn serial_number() -> String {
+    let mut mac = [0u8; 6];
+    // 失敗するのは引数が NULL の場合のみ
+    unsafe { esp_idf_sys::esp_efuse_mac_get_default(mac.as_mut_ptr()) };
+    mac.iter().map(|b| format!("{b:02X}")).collect()
+}
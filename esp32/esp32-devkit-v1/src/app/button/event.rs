@@ -1,9 +1,43 @@
-/// ボタンから発行されるイベント
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ButtonEvent {
-    /// 短押し（未使用の場合は将来用）
-    #[allow(dead_code)]
-    ShortPress,
-    /// 長押し（3秒以上）
-    LongPress,
+pub use devkit_core::button::event::{
+    ButtonEvent, ButtonGesture, ButtonId, ButtonMask, ChordGesture,
+};
+
+use crate::config::pins::BUTTONS;
+
+/// `pins.json` で付けたボタン名との対応
+pub trait ButtonIdExt: Sized {
+    /// `pins.json` で付けた名前
+    fn name(&self) -> &'static str;
+
+    /// 名前からボタンを引く
+    fn from_name(name: &str) -> Option<Self>;
+}
+
+impl ButtonIdExt for ButtonId {
+    fn name(&self) -> &'static str {
+        BUTTONS
+            .get(self.0 as usize)
+            .map(|b| b.name)
+            .unwrap_or("unknown")
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        BUTTONS
+            .iter()
+            .position(|b| b.name == name)
+            .map(|i| Self(i as u8))
+    }
+}
+
+pub trait ButtonMaskExt: Sized {
+    /// 名前の組からマスクを作る（未定義の名前が含まれていれば `None`）
+    fn from_names(names: &[&str]) -> Option<Self>;
+}
+
+impl ButtonMaskExt for ButtonMask {
+    fn from_names(names: &[&str]) -> Option<Self> {
+        names.iter().try_fold(Self::default(), |mask, name| {
+            ButtonId::from_name(name).map(|id| mask.with(id))
+        })
+    }
 }
@@ -1,24 +1,95 @@
 pub mod event;
 pub mod task;
 
+pub use devkit_core::button::{chord, debounce, gesture};
+
+use crate::app::button::debounce::{BounceStats, Debouncer};
 use crate::common::{Error, Result};
+use crate::config::pins::ButtonPinConfig;
 
-use esp_idf_hal::gpio::{Gpio14, Input, PinDriver, Pull};
+use esp_idf_hal::gpio::{AnyIOPin, Input, InterruptType, PinDriver};
 
-/// ボタン（Gpio14 / Active-Low：押すとLOW）
+/// ボタン（ピン・極性・プル設定は `pins.json` に従う）
+///
+/// 生のピンレベルは `Debouncer` を通して判定する。
 pub struct Button {
-    pin: PinDriver<'static, Gpio14, Input>,
+    pin: PinDriver<'static, AnyIOPin, Input>,
+    active_low: bool,
+    debouncer: Debouncer,
 }
 
 impl Button {
-    pub fn new(mut pin: PinDriver<'static, Gpio14, Input>) -> Result<Self> {
-        pin.set_pull(Pull::Up)
-            .map_err(|e| Error::new_invalid_state(&format!("failed to set pull-up: {e}")))?;
+    pub fn new(
+        mut pin: PinDriver<'static, AnyIOPin, Input>,
+        config: &ButtonPinConfig,
+    ) -> Result<Self> {
+        pin.set_pull(config.pull).map_err(|e| {
+            Error::new_invalid_state(&format!("failed to set pull {:?}: {e}", config.pull))
+        })?;
+
+        let initial = pin.is_low() == config.active_low;
+        Ok(Self {
+            pin,
+            active_low: config.active_low,
+            debouncer: Debouncer::new(config.debounce_ms, initial),
+        })
+    }
 
-        Ok(Self { pin })
+    /// 生のピンレベルでの押下状態（デバウンスなし）
+    pub fn is_pressed_raw(&self) -> bool {
+        // Active-Low なら LOW、Active-High なら HIGH が押下
+        self.pin.is_low() == self.active_low
     }
 
+    /// デバウンス後の押下状態（最後にサンプリングした時点、起動直後は初期化時の読み取り結果）
     pub fn is_pressed(&self) -> bool {
-        self.pin.is_low()
+        self.debouncer.level()
+    }
+
+    /// ピンをサンプリングし、デバウンス後の押下状態を返す
+    pub fn update(&mut self, now_ms: u32) -> bool {
+        let raw = self.is_pressed_raw();
+        self.debouncer.update(raw, now_ms)
+    }
+
+    /// デバウンス後の押下状態が変化し始めた時刻（ms）
+    pub fn changed_at(&self) -> u32 {
+        self.debouncer.changed_at()
+    }
+
+    /// デバウンス判定待ちの残り時間（ms）
+    pub fn debounce_pending_ms(&self, now_ms: u32) -> Option<u32> {
+        self.debouncer.pending_ms(now_ms)
+    }
+
+    /// チャタリング統計
+    pub fn bounce_stats(&self) -> BounceStats {
+        self.debouncer.stats()
+    }
+
+    /// 両エッジ割り込みを購読する（`callback` はISRコンテキストで呼ばれる）
+    pub fn subscribe<F>(&mut self, callback: F) -> Result<()>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        self.pin
+            .set_interrupt_type(InterruptType::AnyEdge)
+            .map_err(|e| Error::new_esp(&format!("failed to set interrupt type: {e}")))?;
+
+        // SAFETY: callback は 'static かつ ISR 内で安全な処理（アトミック操作と通知）のみ行う
+        unsafe {
+            self.pin
+                .subscribe(callback)
+                .map_err(|e| Error::new_esp(&format!("failed to subscribe button: {e}")))?;
+        }
+
+        self.enable_interrupt()
+    }
+
+    /// 割り込みを再度有効化する（割り込み発生ごとに自動で無効化されるため）
+    pub fn enable_interrupt(&mut self) -> Result<()> {
+        self.pin
+            .enable_interrupt()
+            .map_err(|e| Error::new_esp(&format!("failed to enable button interrupt: {e}")))
     }
 }
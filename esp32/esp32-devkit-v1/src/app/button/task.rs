@@ -1,52 +1,63 @@
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 
-use esp_idf_hal::delay::FreeRtos;
+use esp_idf_hal::delay::{FreeRtos, TickType, BLOCK};
+use esp_idf_hal::task::notification::Notification;
 
-use super::{event::ButtonEvent, Button};
+use super::{
+    chord::ChordDetector,
+    event::{ButtonEvent, ButtonId, ButtonIdExt, ButtonMask},
+    gesture::{GestureConfig, GestureRecognizer},
+    Button,
+};
 use crate::app::tasks::Tasks;
 use crate::common::{Error, Result};
+use crate::config::pins::ButtonInputMode;
+
+/// ポーリング周期
+const POLL_MS: u32 = 20;
 
 pub struct ButtonTask {
     _handle: JoinHandle<()>,
 }
 
 impl ButtonTask {
-    pub fn start(tasks: Arc<Tasks>, button: Button) -> Result<Self> {
+    pub fn start(
+        tasks: Arc<Tasks>,
+        buttons: Vec<Button>,
+        config: GestureConfig,
+        mode: ButtonInputMode,
+    ) -> Result<Self> {
+        // ButtonMask / 通知ビットで表現できる範囲に制限
+        if buttons.is_empty() || buttons.len() > 8 {
+            return Err(Error::new_invalid_state(&format!(
+                "unsupported number of buttons: {}",
+                buttons.len()
+            )));
+        }
+
+        let alive = tasks.register_task("button_task");
         let h = thread::Builder::new()
             .name("button_task".into())
             .stack_size(4096)
             .spawn(move || {
-                // 設定値
-                const POLL_MS: u32 = 20;
-                const LONG_PRESS_MS: u32 = 3000;
-
-                // 状態
-                let mut pressed_ms: u32 = 0;
-                let mut fired: bool = false;
-
-                loop {
-                    let pressed = button.is_pressed();
-
-                    if pressed {
-                        // 押下継続
-                        if pressed_ms < LONG_PRESS_MS {
-                            pressed_ms = pressed_ms.saturating_add(POLL_MS);
-                        }
+                let _alive = alive;
+                log::info!(
+                    "Button task started ({:?}, {} buttons)",
+                    mode,
+                    buttons.len()
+                );
+                let set = ButtonSet::new(buttons, config);
 
-                        // 3秒到達で1回だけ発火
-                        if !fired && pressed_ms >= LONG_PRESS_MS {
-                            // ボタンイベントを発行
-                            tasks.send_button_event(ButtonEvent::LongPress);
-                            fired = true;
+                match mode {
+                    ButtonInputMode::Interrupt => {
+                        if let Err(e) = run_interrupt(&tasks, set) {
+                            log::error!("button interrupt mode failed: {e}");
                         }
-                    } else {
-                        // 離したらリセット
-                        pressed_ms = 0;
-                        fired = false;
                     }
-
-                    FreeRtos::delay_ms(POLL_MS);
+                    ButtonInputMode::Polling => run_polling(&tasks, set),
                 }
             })
             .map_err(|e| Error::new_unexpected(&format!("failed to spawn button_task: {e}")))?;
@@ -54,3 +65,192 @@ impl ButtonTask {
         Ok(Self { _handle: h })
     }
 }
+
+/// 起動からの経過時間(ms)。ISRからも呼び出し可能
+fn now_ms() -> u32 {
+    // esp_timer は µs 単位の64bitカウンタ。u32(ms) への切り捨てはラップアラウンド前提
+    (unsafe { esp_idf_sys::esp_timer_get_time() } / 1000) as u32
+}
+
+/// ボタン1つ分の判定状態
+struct ButtonSlot {
+    id: ButtonId,
+    button: Button,
+    recognizer: GestureRecognizer,
+    stable: bool,
+    /// 起動時に押されていたボタンは、一度離されるまで押下として扱わない
+    armed: bool,
+}
+
+/// 全ボタンの判定状態（単体ジェスチャ + 同時押し）
+struct ButtonSet {
+    slots: Vec<ButtonSlot>,
+    chord: ChordDetector,
+}
+
+impl ButtonSet {
+    fn new(buttons: Vec<Button>, config: GestureConfig) -> Self {
+        let slots = buttons
+            .into_iter()
+            .enumerate()
+            .map(|(i, button)| {
+                let id = ButtonId(i as u8);
+                // デバウンスの初期レベルと同じ読み取り結果で判定する
+                let held = button.is_pressed();
+                if held {
+                    log::info!(
+                        "Button '{}' is held at startup; ignored until released",
+                        id.name()
+                    );
+                }
+                ButtonSlot {
+                    id,
+                    button,
+                    recognizer: GestureRecognizer::new(config),
+                    stable: false,
+                    armed: !held,
+                }
+            })
+            .collect();
+
+        Self {
+            slots,
+            chord: ChordDetector::new(config.long_press_ms),
+        }
+    }
+
+    /// 次に `process` を呼ぶ必要がある時刻までの残り時間（ms）
+    fn next_deadline_ms(&self, now_ms: u32) -> Option<u32> {
+        self.slots
+            .iter()
+            .flat_map(|slot| {
+                [
+                    slot.button.debounce_pending_ms(now_ms),
+                    slot.recognizer.next_deadline_ms(now_ms),
+                ]
+            })
+            .chain([self.chord.next_deadline_ms(now_ms)])
+            .flatten()
+            .min()
+    }
+
+    /// 各ボタンをサンプリングし、確定したイベントを発行する
+    ///
+    /// `sample_at(i)` はボタン i のサンプル時刻（割り込み時はエッジの時刻）。
+    fn process(&mut self, tasks: &Tasks, sample_at: impl Fn(usize) -> u32, now_ms: u32) {
+        // デバウンス後の押下状態
+        let mut pressed = ButtonMask::default();
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            let is_pressed = slot.button.update(sample_at(i));
+            if !slot.armed {
+                slot.armed = !is_pressed;
+            } else if is_pressed {
+                pressed = pressed.with(slot.id);
+            }
+        }
+
+        // 同時押し判定（同時押し中は単体ジェスチャを抑止）
+        let in_chord = self.chord.update(pressed, now_ms, &mut |buttons, gesture| {
+            tasks.send_button_event(ButtonEvent::Chord { buttons, gesture });
+        });
+
+        for slot in self.slots.iter_mut() {
+            let is_pressed = pressed.0 & (1 << slot.id.0) != 0;
+            let id = slot.id;
+            let mut emit = |gesture| {
+                if !in_chord {
+                    tasks.send_button_event(ButtonEvent::Gesture {
+                        button: id,
+                        gesture,
+                    });
+                }
+            };
+
+            // 押下状態が変化した場合は、最初のエッジの時刻で遷移を確定させてから現在時刻で期限判定
+            if is_pressed != slot.stable {
+                slot.stable = is_pressed;
+                slot.recognizer
+                    .update(is_pressed, slot.button.changed_at(), &mut emit);
+                if !is_pressed {
+                    log::debug!(
+                        "Button '{}' bounce stats: {:?}",
+                        id.name(),
+                        slot.button.bounce_stats()
+                    );
+                }
+            }
+            slot.recognizer.update(is_pressed, now_ms, &mut emit);
+
+            // 同時押しに取り込まれた押下はクリック/長押しとして数えない
+            if in_chord {
+                slot.recognizer.cancel();
+            }
+        }
+    }
+}
+
+/// 従来のポーリング方式
+fn run_polling(tasks: &Tasks, mut set: ButtonSet) {
+    loop {
+        let now = now_ms();
+        set.process(tasks, |_| now, now);
+
+        FreeRtos::delay_ms(POLL_MS);
+    }
+}
+
+/// GPIOエッジ割り込み方式
+///
+/// 割り込みが無い間はタスクをブロックし、デバウンスやジェスチャ判定の期限がある場合のみタイムアウトで起床する。
+fn run_interrupt(tasks: &Tasks, mut set: ButtonSet) -> Result<()> {
+    // 通知は待ち受けるタスク自身で生成する必要がある
+    let notification = Notification::new();
+    let notifier = notification.notifier();
+    let edge_at: Arc<Vec<AtomicU32>> =
+        Arc::new((0..set.slots.len()).map(|_| AtomicU32::new(0)).collect());
+
+    for (i, slot) in set.slots.iter_mut().enumerate() {
+        let notifier = notifier.clone();
+        let edge_at = edge_at.clone();
+        // 通知値のビットでどのボタンのエッジかを識別
+        let bit = NonZeroU32::new(1 << i).unwrap_or(NonZeroU32::MIN);
+
+        slot.button.subscribe(move || {
+            // エッジ発生時刻を記録してタスクを起床
+            edge_at[i].store(now_ms(), Ordering::Release);
+            unsafe {
+                notifier.notify_and_yield(bit);
+            }
+        })?;
+    }
+
+    loop {
+        let timeout = match set.next_deadline_ms(now_ms()) {
+            Some(ms) => TickType::new_millis(ms.max(1) as u64).ticks(),
+            None => BLOCK,
+        };
+
+        let edges = notification.wait(timeout).map_or(0, NonZeroU32::get);
+        let now = now_ms();
+
+        // 割り込みは発生ごとに無効化されるため、チャタリング中のエッジも拾えるよう即座に再有効化
+        for (i, slot) in set.slots.iter_mut().enumerate() {
+            if edges & (1 << i) != 0 {
+                slot.button.enable_interrupt()?;
+            }
+        }
+
+        // ソフトウェアデバウンス後の状態でジェスチャ判定
+        set.process(
+            tasks,
+            |i| {
+                if edges & (1 << i) != 0 {
+                    edge_at[i].load(Ordering::Acquire)
+                } else {
+                    now
+                }
+            },
+            now,
+        );
+    }
+}
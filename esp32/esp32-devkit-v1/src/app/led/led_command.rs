@@ -1,10 +1,56 @@
-#[derive(PartialEq, Clone, Copy, Debug)]
+use crate::app::led::arbiter::LedSource;
+use crate::app::led::color::Rgb;
+use crate::app::led::led_id::LedId;
+use crate::app::led::led_state::LedState;
+
+/// LEDタスクへのコマンド（`Shutdown` 以外は `led` で対象のLEDを指定）
+#[derive(PartialEq, Clone, Debug)]
 pub enum LedCommand {
-    Blink {
-        interval_ms: u32,
+    /// 要求元 `source` として表示を要求（`ttl_ms` 経過で自動解除、`None` なら解除まで有効）
+    ///
+    /// 複数の要求がある場合は優先度の最も高い要求元の表示になる。`color` は RGB LED のみ反映される。
+    Request {
+        led: LedId,
+        source: LedSource,
+        state: LedState,
+        color: Rgb,
+        ttl_ms: Option<u32>,
+    },
+    /// 要求元 `source` の表示要求を解除
+    Release { led: LedId, source: LedSource },
+    /// 現在の表示の上に `times` 回点滅を重ね、終了後は元の表示に戻る
+    ///
+    /// 元の表示は裏で進み続けるため、点滅中に受けた他のコマンドも終了後に反映される。
+    Flash {
+        led: LedId,
+        times: u16,
+        on_ms: u32,
+        off_ms: u32,
+        color: Rgb,
     },
-    On,
-    Off,
     #[allow(dead_code)]
     Shutdown,
 }
+
+impl LedCommand {
+    /// 対象のLED（`Shutdown` は `None`）
+    pub fn led(&self) -> Option<LedId> {
+        match self {
+            LedCommand::Request { led, .. }
+            | LedCommand::Release { led, .. }
+            | LedCommand::Flash { led, .. } => Some(*led),
+            LedCommand::Shutdown => None,
+        }
+    }
+
+    /// 対象のLEDを `id` に差し替えたコマンド
+    pub fn with_led(mut self, id: LedId) -> Self {
+        match &mut self {
+            LedCommand::Request { led, .. }
+            | LedCommand::Release { led, .. }
+            | LedCommand::Flash { led, .. } => *led = id,
+            LedCommand::Shutdown => {}
+        }
+        self
+    }
+}
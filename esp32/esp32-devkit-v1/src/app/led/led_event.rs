@@ -0,0 +1,14 @@
+use crate::app::led::arbiter::LedSource;
+use crate::app::led::led_id::LedId;
+use crate::app::led::led_state::LedState;
+
+/// LEDタスクから発行される表示変化イベント
+#[derive(Debug, Clone, PartialEq)]
+pub enum LedEvent {
+    /// 表示する要求が切り替わった（`source` が `None` なら要求が無く消灯）
+    StateChanged {
+        led: LedId,
+        source: Option<LedSource>,
+        state: LedState,
+    },
+}
@@ -0,0 +1,21 @@
+use crate::config::pins::LEDS;
+
+/// LED識別子（`pins.json` の `leds` の並び順）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LedId(pub u8);
+
+impl LedId {
+    /// `pins.json` で付けた名前
+    pub fn name(&self) -> &'static str {
+        LEDS.get(self.0 as usize)
+            .map(|l| l.name)
+            .unwrap_or("unknown")
+    }
+
+    /// 名前からLEDを引く
+    pub fn from_name(name: &str) -> Option<Self> {
+        LEDS.iter()
+            .position(|l| l.name == name)
+            .map(|i| Self(i as u8))
+    }
+}
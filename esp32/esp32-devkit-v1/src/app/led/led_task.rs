@@ -1,10 +1,19 @@
 use esp_idf_hal::delay::FreeRtos;
 
+use crate::app::led::animation::Animation;
+use crate::app::led::arbiter::LedArbiter;
+use crate::app::led::color::Rgb;
+use crate::app::led::led_event::LedEvent;
 use crate::app::led::led_handle::LedHandle;
+use crate::app::led::led_id::LedId;
+use crate::app::led::led_state::LedState;
+use crate::app::led::pattern::{Clock, LedPattern, Sequencer};
 use crate::app::led::{Led, LedCommand};
+use crate::app::tasks::Tasks;
 use crate::common::{Error, Result};
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc};
 use std::thread::{self, JoinHandle};
+use std::time::Instant;
 
 /// LEDタスク本体（スレッド寿命を保持）
 pub struct LedTask {
@@ -12,57 +21,156 @@ pub struct LedTask {
     handle: JoinHandle<()>,
 }
 
+/// タスク起動時刻からの経過時間を返す時刻源
+struct MonotonicClock {
+    origin: Instant,
+}
+
+impl Clock for MonotonicClock {
+    fn now_ms(&self) -> u32 {
+        self.origin.elapsed().as_millis() as u32
+    }
+}
+
+/// LED1つ分の点灯制御状態
+///
+/// arbiter: 要求元ごとの表示要求 / base: 採用中の要求の表示 / overlay: 一時的な点滅
+struct LedChannel {
+    led: Led,
+    arbiter: LedArbiter,
+    active: Option<u32>,
+    base: Animation,
+    base_color: Rgb,
+    overlay: Option<(Sequencer, Rgb)>,
+    output: Option<(Rgb, u8)>,
+}
+
+impl LedChannel {
+    fn new(led: Led) -> Self {
+        Self {
+            led,
+            arbiter: LedArbiter::default(),
+            active: None,
+            base: Animation::Static(0),
+            base_color: Rgb::WHITE,
+            overlay: None,
+            output: None,
+        }
+    }
+
+    /// 最優先の要求に表示を合わせ、変化があった時だけ書き込む
+    fn update(&mut self, id: LedId, clock: &impl Clock, tasks: &Tasks) {
+        // 最優先の要求が変わったら表示を切り替え（要求が無ければ消灯）
+        let winner = self.arbiter.winner(clock.now_ms());
+        let seq = winner.map(|request| request.seq);
+        if seq != self.active {
+            let source = winner.map(|request| request.source);
+            let (state, color) = match winner {
+                Some(request) => {
+                    log::debug!(
+                        "LED '{}' state from {:?}: {:?} {:?}",
+                        id.name(),
+                        request.source,
+                        request.state,
+                        request.color
+                    );
+                    (request.state.clone(), request.color)
+                }
+                None => (LedState::Off, self.base_color),
+            };
+            tasks.send_led_event(LedEvent::StateChanged {
+                led: id,
+                source,
+                state: state.clone(),
+            });
+            self.base = Animation::start(state, self.base.level(clock), clock);
+            self.base_color = color;
+            self.active = seq;
+        }
+
+        // 出力輝度の決定（overlay 優先、終了したら base に戻す）
+        let base_output = (self.base_color, self.base.level(clock));
+        let next = match self
+            .overlay
+            .as_ref()
+            .map(|(seq, color)| (seq.level(clock), *color))
+        {
+            Some((Some(level), color)) => (color, level),
+            Some((None, _)) => {
+                self.overlay = None;
+                base_output
+            }
+            None => base_output,
+        };
+
+        if self.output != Some(next) {
+            let _ = self.led.set(next.0, next.1);
+            self.output = Some(next);
+        }
+    }
+}
+
 impl LedTask {
-    pub fn start(mut led: Led) -> Result<(Self, LedHandle)> {
+    /// `leds` は `LEDS` と同じ並び順（`LedId` の添字）
+    pub fn start(tasks: Arc<Tasks>, leds: Vec<Led>) -> Result<(Self, LedHandle)> {
         let (tx, rx) = mpsc::channel::<LedCommand>();
 
+        let alive = tasks.register_task("led_task");
         let handle = thread::Builder::new()
             .name("led_task".into())
             .stack_size(4096)
             .spawn(move || {
-                // 点滅制御用の状態
-                let mut blink_interval: Option<u32> = None;
-                let mut phase_on = false;
-                let mut elapsed_ms: u32 = 0;
+                let _alive = alive;
+                let clock = MonotonicClock {
+                    origin: Instant::now(),
+                };
+                let mut channels: Vec<_> = leds.into_iter().map(LedChannel::new).collect();
 
                 loop {
                     // コマンド処理（キューが空になるまで）
                     while let Ok(cmd) = rx.try_recv() {
+                        let led = match &cmd {
+                            LedCommand::Request { led, .. }
+                            | LedCommand::Release { led, .. }
+                            | LedCommand::Flash { led, .. } => *led,
+                            LedCommand::Shutdown => return,
+                        };
+                        let Some(channel) = channels.get_mut(led.0 as usize) else {
+                            log::warn!("LED command for unknown LED {:?}; dropping", led);
+                            continue;
+                        };
+
                         match cmd {
-                            LedCommand::On => {
-                                blink_interval = None;
-                                phase_on = true;
-                                let _ = led.on();
-                            }
-                            LedCommand::Off => {
-                                blink_interval = None;
-                                phase_on = false;
-                                let _ = led.off();
-                            }
-                            LedCommand::Blink { interval_ms } => {
-                                // Constrain interval to [20ms, 65535ms] to prevent overflow
-                                // and ensure reasonable blink rates
-                                blink_interval = Some(interval_ms.max(20).min(65535));
-                                phase_on = false;
-                                elapsed_ms = 0;
+                            LedCommand::Request {
+                                source,
+                                state,
+                                color,
+                                ttl_ms,
+                                ..
+                            } => channel.arbiter.request(
+                                source,
+                                state,
+                                color,
+                                ttl_ms,
+                                clock.now_ms(),
+                            ),
+                            LedCommand::Release { source, .. } => channel.arbiter.release(source),
+                            LedCommand::Flash {
+                                times,
+                                on_ms,
+                                off_ms,
+                                color,
+                                ..
+                            } => {
+                                let pattern = LedPattern::blink(on_ms, off_ms, Some(times.max(1)));
+                                channel.overlay = Some((Sequencer::start(pattern, &clock), color));
                             }
                             LedCommand::Shutdown => return,
                         }
                     }
 
-                    // 点滅処理
-                    // Note: Timing assumes task loop execution is negligible vs 20ms delay
-                    if let Some(interval) = blink_interval {
-                        elapsed_ms = elapsed_ms.saturating_add(20);
-                        if elapsed_ms >= interval {
-                            if phase_on {
-                                let _ = led.off();
-                            } else {
-                                let _ = led.on();
-                            }
-                            phase_on = !phase_on;
-                            elapsed_ms = 0;
-                        }
+                    for (i, channel) in channels.iter_mut().enumerate() {
+                        channel.update(LedId(i as u8), &clock, &tasks);
                     }
 
                     FreeRtos::delay_ms(20);
@@ -1,33 +1,57 @@
 pub mod led_command;
+pub mod led_event;
 pub mod led_handle;
+pub mod led_id;
 pub mod led_task;
+pub mod ws2812;
 
-use esp_idf_hal::gpio::{Output, PinDriver};
+pub use devkit_core::led::{animation, arbiter, color, led_state, pattern};
 
+use crate::app::led::color::Rgb;
 use crate::app::led::led_command::LedCommand;
 use crate::common::{Error, Result};
-use crate::config::pins::LedPinType;
+use crate::config::pins::LedPin;
+
+/// GPIO駆動時に点灯とみなす輝度の下限
+const GPIO_ON_THRESHOLD: u8 = 128;
 
 pub struct Led {
-    pin: PinDriver<'static, LedPinType, Output>,
+    pin: LedPin,
 }
 
 impl Led {
-    pub fn new(pin: PinDriver<'static, LedPinType, Output>) -> Self {
+    pub fn new(pin: LedPin) -> Self {
         Self { pin }
     }
 
-    pub fn on(&mut self) -> Result<()> {
-        self.pin
-            .set_high()
-            .map_err(|e| Error::new_invalid_state(&format!("failed to set LED HIGH: {e}")))?;
-        Ok(())
-    }
-
-    pub fn off(&mut self) -> Result<()> {
-        self.pin
-            .set_low()
-            .map_err(|e| Error::new_invalid_state(&format!("failed to set LED LOW: {e}")))?;
+    /// 色と輝度を設定する（輝度 0 = 消灯, 255 = 最大）
+    ///
+    /// 色は WS2812 駆動のみ反映される。PWM/WS2812 駆動ではガンマ補正した値を出力し、
+    /// GPIO駆動では 50% 以上を点灯とみなす。
+    pub fn set(&mut self, color: Rgb, level: u8) -> Result<()> {
+        match &mut self.pin {
+            LedPin::Gpio(pin) => {
+                if level >= GPIO_ON_THRESHOLD {
+                    pin.set_high().map_err(|e| {
+                        Error::new_invalid_state(&format!("failed to set LED HIGH: {e}"))
+                    })?;
+                } else {
+                    pin.set_low().map_err(|e| {
+                        Error::new_invalid_state(&format!("failed to set LED LOW: {e}"))
+                    })?;
+                }
+            }
+            LedPin::Pwm(driver) => {
+                // 知覚輝度が線形に近くなるよう二乗で補正
+                let max = driver.get_max_duty();
+                let level = level as u32;
+                let duty = max * level * level / (255 * 255);
+                driver.set_duty(duty).map_err(|e| {
+                    Error::new_invalid_state(&format!("failed to set LED duty {duty}: {e}"))
+                })?;
+            }
+            LedPin::Ws2812(ws2812) => ws2812.write(color.scale(level))?,
+        }
         Ok(())
     }
 }
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use esp_idf_hal::rmt::{FixedLengthSignal, PinState, Pulse, TxRmtDriver};
+
+use crate::app::led::color::Rgb;
+use crate::common::{Error, Result};
+
+/// 1ピクセル分のビット数（GRB各8bit）
+const BITS: usize = 24;
+
+/// RMT で WS2812（NeoPixel）1 個を駆動する
+pub struct Ws2812 {
+    tx: TxRmtDriver<'static>,
+    /// 0 ビットのパルス（High, Low）
+    zero: (Pulse, Pulse),
+    /// 1 ビットのパルス（High, Low）
+    one: (Pulse, Pulse),
+}
+
+impl Ws2812 {
+    pub fn new(tx: TxRmtDriver<'static>) -> Result<Self> {
+        let ticks_hz = tx
+            .counter_clock()
+            .map_err(|e| Error::new_esp(&format!("failed to get RMT clock: {e}")))?;
+
+        // データシートのタイミング（T0H 350ns / T0L 800ns / T1H 700ns / T1L 600ns）
+        let pulse = |state, ns| {
+            Pulse::new_with_duration(ticks_hz, state, &Duration::from_nanos(ns))
+                .map_err(|e| Error::new_esp(&format!("failed to build RMT pulse: {e}")))
+        };
+
+        Ok(Self {
+            zero: (pulse(PinState::High, 350)?, pulse(PinState::Low, 800)?),
+            one: (pulse(PinState::High, 700)?, pulse(PinState::Low, 600)?),
+            tx,
+        })
+    }
+
+    /// 色を送信する（送信完了までブロック）
+    pub fn write(&mut self, color: Rgb) -> Result<()> {
+        // WS2812 は G, R, B の順に MSB から送る
+        let grb = (color.g as u32) << 16 | (color.r as u32) << 8 | color.b as u32;
+
+        let mut signal = FixedLengthSignal::<BITS>::new();
+        for i in 0..BITS {
+            let bit = grb & (1 << (BITS - 1 - i)) != 0;
+            let pulses = if bit { &self.one } else { &self.zero };
+            signal
+                .set(i, pulses)
+                .map_err(|e| Error::new_esp(&format!("failed to build RMT signal: {e}")))?;
+        }
+
+        self.tx
+            .start_blocking(&signal)
+            .map_err(|e| Error::new_invalid_state(&format!("failed to send WS2812 data: {e}")))
+    }
+}
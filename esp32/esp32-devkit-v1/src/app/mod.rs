@@ -1,5 +1,6 @@
 pub mod tasks;
 
+mod battery;
 mod ble;
 mod button;
 mod led;
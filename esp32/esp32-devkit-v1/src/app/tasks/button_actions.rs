@@ -0,0 +1,60 @@
+use crate::app::ble::ble_command::AdvertiseMode;
+use crate::app::button::event::{
+    ButtonEvent, ButtonGesture, ButtonId, ButtonIdExt, ButtonMask, ButtonMaskExt, ChordGesture,
+};
+
+/// ボタン操作に割り当てるシステム動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonAction {
+    /// BLEアドバタイズ開始（接続を受け付ける相手を指定）
+    StartAdvertise(AdvertiseMode),
+    /// BLEアドバタイズ停止
+    StopAdvertise,
+    /// ファクトリーリセット
+    FactoryReset,
+}
+
+/// 単体ボタンのジェスチャ割り当て（ボタン名は `pins.json` の `buttons[].name`）
+const GESTURE_ACTIONS: &[(&str, ButtonGesture, ButtonAction)] = &[
+    (
+        "main",
+        ButtonGesture::ShortPress,
+        ButtonAction::StartAdvertise(AdvertiseMode::BondedOnly),
+    ),
+    (
+        "main",
+        ButtonGesture::LongPress,
+        ButtonAction::StartAdvertise(AdvertiseMode::OpenPairing),
+    ),
+    (
+        "main",
+        ButtonGesture::VeryLongPress,
+        ButtonAction::FactoryReset,
+    ),
+    (
+        "sub",
+        ButtonGesture::DoubleClick,
+        ButtonAction::StopAdvertise,
+    ),
+];
+
+/// 同時押しの割り当て（押されたボタンの組が完全一致した場合のみ）
+const CHORD_ACTIONS: &[(&[&str], ChordGesture, ButtonAction)] = &[(
+    &["main", "sub"],
+    ChordGesture::LongPress,
+    ButtonAction::FactoryReset,
+)];
+
+/// ボタンイベントに割り当てられた動作を引く
+pub fn resolve(event: &ButtonEvent) -> Option<ButtonAction> {
+    match *event {
+        ButtonEvent::Gesture { button, gesture } => GESTURE_ACTIONS
+            .iter()
+            .find(|(name, g, _)| ButtonId::from_name(name) == Some(button) && *g == gesture)
+            .map(|(_, _, action)| *action),
+        ButtonEvent::Chord { buttons, gesture } => CHORD_ACTIONS
+            .iter()
+            .find(|(names, g, _)| ButtonMask::from_names(names) == Some(buttons) && *g == gesture)
+            .map(|(_, _, action)| *action),
+    }
+}
@@ -3,11 +3,30 @@ use std::thread::{self, JoinHandle};
 
 use esp_idf_hal::delay::FreeRtos;
 
-use super::Tasks;
-use crate::app::ble::{ble_command::BleCommand, ble_event::BleEvent};
+use super::{
+    button_actions::{self, ButtonAction},
+    Tasks,
+};
+use crate::app::battery::battery_event::BatteryEvent;
+use crate::app::ble::{
+    beacon,
+    ble_command::{AdvertiseMode, BleCommand},
+    ble_event::{BleEvent, ReplyTo},
+    command_parser::{self, RemoteCommand, RemoteLedMode},
+    connections::Connection,
+    notification::BleNotification,
+    scan::{self, ScannedDevice},
+};
 use crate::app::button::event::ButtonEvent;
+use crate::app::led::arbiter::LedSource;
+use crate::app::led::color::Rgb;
 use crate::app::led::led_command::LedCommand;
+use crate::app::led::led_event::LedEvent;
+use crate::app::led::led_id::LedId;
+use crate::app::led::led_state::LedState;
 use crate::common::{Error, Result};
+use crate::config::ble::{BeaconMode, BleConfig};
+use crate::config::led::{self, STATUS_COLORS};
 
 /// イベント集約・制御タスク
 /// 各タスクからのイベントを受信し、システム全体を調整する
@@ -18,89 +37,382 @@ pub struct EventCoordinator {
 impl EventCoordinator {
     pub fn start(
         tasks: Arc<Tasks>,
-    ) -> Result<(Self, mpsc::Sender<ButtonEvent>, mpsc::Sender<BleEvent>)> {
+    ) -> Result<(
+        Self,
+        mpsc::Sender<ButtonEvent>,
+        mpsc::Sender<BleEvent>,
+        mpsc::Sender<LedEvent>,
+        mpsc::Sender<BatteryEvent>,
+    )> {
         let (button_tx, button_rx) = mpsc::channel::<ButtonEvent>();
         let (ble_tx, ble_rx) = mpsc::channel::<BleEvent>();
+        let (led_tx, led_rx) = mpsc::channel::<LedEvent>();
+        let (battery_tx, battery_rx) = mpsc::channel::<BatteryEvent>();
 
+        let alive = tasks.register_task("event_coordinator");
         let h = thread::Builder::new()
             .name("event_coordinator".into())
             .stack_size(4096)
             .spawn(move || {
+                let _alive = alive;
                 log::info!("Event coordinator started");
+                // リモートからの状態問い合わせの応答先（次の StateResponse を全員に返す）
+                let mut state_queries: Vec<ReplyTo> = Vec::new();
+                let mut connections_queries: Vec<ReplyTo> = Vec::new();
+                let mut scan_pending: Option<ReplyTo> = None;
+                // 応答待ちの GATT 要求の相手と応答先（1件ずつ処理する）
+                let mut gatt_pending: Option<(String, ReplyTo)> = None;
+                // バッテリー低下を表示中か
+                let mut battery_low = false;
 
                 loop {
-                    // ボタンイベント処理
+                    // ボタンイベント処理（ボタン名・ジェスチャごとの割り当て表に従う）
                     while let Ok(event) = button_rx.try_recv() {
                         log::debug!("Button event received: {:?}", event);
-                        match event {
-                            ButtonEvent::LongPress => {
-                                // 長押し：BLEアドバタイズ開始
-                                log::info!("Button: Long press detected, starting BLE advertising");
+                        // 押した回数はアドバタイズの状態データにも載せるため、その設定があれば常に送る
+                        if BleConfig::ADVERTISING.status.is_some() {
+                            tasks.send_ble_command(BleCommand::Notify(BleNotification::Button(
+                                event,
+                            )));
+                        } else {
+                            notify_subscribers(&tasks, BleNotification::Button(event));
+                        }
+                        let Some(action) = button_actions::resolve(&event) else {
+                            continue;
+                        };
+
+                        // 操作を受け付けたことを短い点滅で通知（元のLED状態は維持）
+                        send_indicator_command(&tasks, LedCommand::Flash {
+                            led: indicator(STATUS_LED),
+                            times: 2,
+                            on_ms: 60,
+                            off_ms: 60,
+                            color: STATUS_COLORS.feedback,
+                        });
+
+                        match action {
+                            ButtonAction::StartAdvertise(mode) => {
+                                log::info!(
+                                    "Button: {:?} -> starting BLE advertising ({:?})",
+                                    event,
+                                    mode
+                                );
                                 tasks.send_ble_command(BleCommand::StartAdvertise {
-                                    timeout_ms: 60000,
+                                    timeout_ms: advertise_timeout_ms(mode),
+                                    mode,
                                 });
                             }
-                            ButtonEvent::ShortPress => {
-                                // 短押し：将来の拡張用
-                                log::debug!("Button: Short press detected (not yet implemented)");
+                            ButtonAction::StopAdvertise => {
+                                log::info!("Button: {:?} -> stopping BLE advertising", event);
+                                tasks.send_ble_command(BleCommand::StopAdvertise);
+                            }
+                            ButtonAction::FactoryReset => {
+                                // ファクトリーリセット：ボンド情報を削除（受け付けたことを他の表示より優先して通知）
+                                send_indicator_command(&tasks, LedCommand::Request {
+                                    led: indicator(STATUS_LED),
+                                    source: LedSource::UserFeedback,
+                                    state: LedState::Blink {
+                                        on_ms: 50,
+                                        off_ms: 50,
+                                        count: None,
+                                    },
+                                    color: STATUS_COLORS.feedback,
+                                    ttl_ms: Some(2000),
+                                });
+                                log::warn!(
+                                    "Button: {:?} -> factory reset (clearing BLE bonds)",
+                                    event
+                                );
+                                tasks.send_ble_command(BleCommand::ClearBonds);
                             }
                         }
                     }
 
-                    // BLEイベント処理
+                    // BLEイベント処理（BLE状態とエラーは別の要求元としてLEDを要求）
                     while let Ok(event) = ble_rx.try_recv() {
                         log::debug!("BLE event received: {:?}", event);
                         match event {
                             BleEvent::AdvertisingStarted => {
                                 log::info!("BLE: Advertising started");
-                                let cmd = LedCommand::Blink { interval_ms: 500 };
-                                log::debug!("Sending LED command: {:?}", cmd);
-                                tasks.send_led_command(cmd);
+                                request_ble_led(
+                                    &tasks,
+                                    advertising_led_state(),
+                                    STATUS_COLORS.advertising,
+                                );
                             }
                             BleEvent::AdvertisingStopped => {
                                 log::debug!("BLE: Advertising stopped");
-                                let cmd = LedCommand::Off;
-                                log::debug!("Sending LED command: {:?}", cmd);
-                                tasks.send_led_command(cmd);
-                            }
-                            BleEvent::Connected => {
-                                log::info!("BLE: Connected");
-                                let cmd = LedCommand::On;
-                                log::debug!("Sending LED command: {:?}", cmd);
-                                tasks.send_led_command(cmd);
-                            }
-                            BleEvent::Disconnected => {
-                                log::info!("BLE: Disconnected");
-                                let cmd = LedCommand::Off;
-                                log::debug!("Sending LED command: {:?}", cmd);
-                                tasks.send_led_command(cmd);
+                                request_ble_led(&tasks, LedState::Off, Rgb::WHITE);
+                            }
+                            BleEvent::ReconnectStarted { window_ms } => {
+                                log::info!("BLE: Re-advertising for {}ms", window_ms);
+                                request_ble_led(
+                                    &tasks,
+                                    reconnect_led_state(),
+                                    STATUS_COLORS.advertising,
+                                );
+                            }
+                            BleEvent::ReconnectWaiting { retry_in_ms } => {
+                                log::info!("BLE: Re-advertising again in {}ms", retry_in_ms);
+                                request_ble_led(
+                                    &tasks,
+                                    reconnect_waiting_led_state(),
+                                    STATUS_COLORS.advertising,
+                                );
+                            }
+                            BleEvent::Connected { peer, count } => {
+                                log::info!(
+                                    "BLE: Connected {} (conn {}, {} connected)",
+                                    peer.address,
+                                    peer.conn_handle,
+                                    count
+                                );
+                                request_ble_led(&tasks, LedState::On, STATUS_COLORS.connected);
+                            }
+                            BleEvent::Disconnected { peer, count } => {
+                                log::info!(
+                                    "BLE: Disconnected {} (conn {}, {} remaining)",
+                                    peer.address,
+                                    peer.conn_handle,
+                                    count
+                                );
+                                // 最後の接続が切れたら、アドバタイズ継続中かを確認してLEDに反映
+                                if count == 0 {
+                                    tasks.send_ble_command(BleCommand::GetState);
+                                }
                             }
                             BleEvent::Error => {
                                 log::warn!("BLE: Error detected");
-                                let cmd = LedCommand::Blink { interval_ms: 100 };
-                                log::debug!("Sending LED command: {:?}", cmd);
-                                tasks.send_led_command(cmd);
+                                request_error_led(&tasks);
+                            }
+                            BleEvent::PairingCompleted { bonded } => {
+                                log::info!("BLE: Pairing completed (bonded: {})", bonded);
+                            }
+                            BleEvent::PairingFailed => {
+                                log::warn!("BLE: Pairing failed");
+                                request_error_led(&tasks);
+                            }
+                            BleEvent::BondsCleared => {
+                                log::info!("BLE: Bonds cleared");
+                                send_indicator_command(&tasks, LedCommand::Flash {
+                                    led: indicator(STATUS_LED),
+                                    times: 3,
+                                    on_ms: 150,
+                                    off_ms: 150,
+                                    color: STATUS_COLORS.feedback,
+                                });
                             }
                             BleEvent::StateResponse(state) => {
-                                let cmd = if state.error {
-                                    log::warn!("BLE: Error state, LED blinking (100ms)");
-                                    LedCommand::Blink { interval_ms: 100 }
-                                } else if state.connected {
+                                let (ble_state, color) = if state.connected {
                                     log::info!("BLE: Connected, LED ON");
-                                    LedCommand::On
+                                    (LedState::On, STATUS_COLORS.connected)
+                                } else if state.reconnecting && state.advertising {
+                                    log::info!("BLE: Re-advertising, LED reconnect pattern");
+                                    (reconnect_led_state(), STATUS_COLORS.advertising)
+                                } else if state.reconnecting {
+                                    log::info!("BLE: Waiting to re-advertise, LED breathing");
+                                    (reconnect_waiting_led_state(), STATUS_COLORS.advertising)
                                 } else if state.advertising {
                                     log::info!("BLE: Advertising, LED blinking (500ms)");
-                                    LedCommand::Blink { interval_ms: 500 }
+                                    (advertising_led_state(), STATUS_COLORS.advertising)
                                 } else {
                                     log::info!("BLE: Not connected, LED off");
-                                    LedCommand::Off
+                                    (LedState::Off, Rgb::WHITE)
                                 };
-                                log::debug!("Sending LED command: {:?}", cmd);
-                                tasks.send_led_command(cmd);
+                                request_ble_led(&tasks, ble_state, color);
+
+                                for reply in state_queries.drain(..) {
+                                    respond(
+                                        &tasks,
+                                        reply,
+                                        format!(
+                                            "ok connected={} connections={} advertising={} reconnecting={} error={}",
+                                            state.connected as u8,
+                                            state.connections,
+                                            state.advertising as u8,
+                                            state.reconnecting as u8,
+                                            state.error as u8
+                                        ),
+                                    );
+                                }
+
+                                if state.error {
+                                    log::warn!("BLE: Error state, LED error pattern");
+                                    request_error_led(&tasks);
+                                } else {
+                                    send_indicator_command(&tasks, LedCommand::Release {
+                                        led: indicator(ERROR_LED),
+                                        source: LedSource::Error,
+                                    });
+                                }
+                            }
+                            BleEvent::ConnectionsResponse(connections) => {
+                                for reply in connections_queries.drain(..) {
+                                    respond(
+                                        &tasks,
+                                        reply,
+                                        format!("ok {}", connections_report(&connections)),
+                                    );
+                                }
+                            }
+                            BleEvent::ScanResults(devices) => {
+                                log::info!("BLE: Scan found {} device(s)", devices.len());
+                                if let Some(reply) = scan_pending.take() {
+                                    respond(&tasks, reply, format!("ok {}", scan_report(&devices)));
+                                }
+                            }
+                            BleEvent::ScanFailed => {
+                                log::warn!("BLE: Scan failed");
+                                if let Some(reply) = scan_pending.take() {
+                                    respond(&tasks, reply, "err scan failed".to_string());
+                                }
+                            }
+                            BleEvent::GattResponse(event) => {
+                                log::info!("BLE: GATT response {:?}", event);
+                                // 応答待ちの要求と同じ相手への結果だけを応答にする
+                                match gatt_pending.take() {
+                                    Some((address, reply)) if address == event.address() => {
+                                        respond(&tasks, reply, event.to_response());
+                                    }
+                                    pending => {
+                                        log::warn!("BLE: Unexpected GATT response {:?}", event);
+                                        gatt_pending = pending;
+                                    }
+                                }
+                            }
+                            BleEvent::Gatt(event) => {
+                                log::info!("BLE: GATT {:?}", event);
+                                notify_subscribers(&tasks, BleNotification::Gatt(event));
+                            }
+                            BleEvent::RemoteCommand { command, reply } => {
+                                log::info!("BLE: Remote command {:?}", command);
+                                match command {
+                                    RemoteCommand::SetLed { led, mode, color } => {
+                                        match remote_led_command(&led, mode, color) {
+                                            Ok(cmd) => {
+                                                tasks.send_led_command(cmd);
+                                                respond(&tasks, reply, "ok".to_string());
+                                            }
+                                            Err(e) => respond(&tasks, reply, format!("err {}", e.message)),
+                                        }
+                                    }
+                                    RemoteCommand::StopAdvertise => {
+                                        tasks.send_ble_command(BleCommand::StopAdvertise);
+                                        respond(&tasks, reply, "ok".to_string());
+                                    }
+                                    RemoteCommand::QueryState => {
+                                        state_queries.push(reply);
+                                        tasks.send_ble_command(BleCommand::GetState);
+                                    }
+                                    RemoteCommand::QueryConnections => {
+                                        connections_queries.push(reply);
+                                        tasks.send_ble_command(BleCommand::GetConnections);
+                                    }
+                                    RemoteCommand::Reboot => {
+                                        respond(&tasks, reply, "ok".to_string());
+                                        // 応答の通知が送信されるまで待ってから再起動
+                                        log::warn!("Rebooting by remote command");
+                                        FreeRtos::delay_ms(500);
+                                        esp_idf_hal::reset::restart();
+                                    }
+                                    RemoteCommand::SetLogLevel(level) => {
+                                        set_log_level(level);
+                                        respond(&tasks, reply, format!("ok log={level}"));
+                                    }
+                                    RemoteCommand::QueryTasks => {
+                                        respond(&tasks, reply, format!("ok {}", task_report(&tasks)));
+                                    }
+                                    RemoteCommand::Scan {
+                                        duration_ms,
+                                        filter,
+                                    } => {
+                                        if scan_pending.is_some() {
+                                            respond(&tasks, reply, "err scan in progress".to_string());
+                                        } else {
+                                            scan_pending = Some(reply);
+                                            tasks.send_ble_command(BleCommand::Scan {
+                                                duration_ms,
+                                                filter,
+                                            });
+                                        }
+                                    }
+                                    RemoteCommand::Gatt(request) => {
+                                        if gatt_pending.is_some() {
+                                            respond(
+                                                &tasks,
+                                                reply,
+                                                "err gatt request in progress".to_string(),
+                                            );
+                                        } else {
+                                            gatt_pending = Some((request.address().to_string(), reply));
+                                            tasks.send_ble_command(BleCommand::Gatt(request));
+                                        }
+                                    }
+                                    RemoteCommand::SetBeacon(mode) => {
+                                        if mode != BeaconMode::Off
+                                            && beacon::frames(mode, &BleConfig::BEACON).is_empty()
+                                        {
+                                            respond(
+                                                &tasks,
+                                                reply,
+                                                format!("err beacon {mode:?} is not configured"),
+                                            );
+                                        } else {
+                                            tasks.send_ble_command(BleCommand::SetBeacon(mode));
+                                            respond(&tasks, reply, "ok".to_string());
+                                        }
+                                    }
+                                    RemoteCommand::EnterPasskey(passkey) => {
+                                        // 結果は BLE タスクから応答する
+                                        tasks.send_ble_command(BleCommand::EnterPasskey {
+                                            passkey,
+                                            reply,
+                                        });
+                                    }
+                                    RemoteCommand::Help => {
+                                        respond(&tasks, reply, format!("ok {}", command_parser::HELP));
+                                    }
+                                }
                             }
                         }
                     }
 
+                    // LEDイベント処理（表示の変化を購読者に通知）
+                    while let Ok(event) = led_rx.try_recv() {
+                        log::debug!("LED event received: {:?}", event);
+                        notify_subscribers(&tasks, BleNotification::Led(event));
+                    }
+
+                    // バッテリーイベント処理（残量を Battery Service に反映し、低下中は LED で通知）
+                    while let Ok(event) = battery_rx.try_recv() {
+                        log::debug!("Battery event received: {:?}", event);
+                        let BatteryEvent::Level { percent, mv, low } = event;
+                        tasks.send_ble_command(BleCommand::SetBatteryLevel { percent, mv, low });
+                        if low == battery_low {
+                            continue;
+                        }
+                        battery_low = low;
+                        if low {
+                            log::warn!("Battery low ({}%)", percent);
+                            send_indicator_command(&tasks, LedCommand::Request {
+                                led: indicator(STATUS_LED),
+                                source: LedSource::LowBattery,
+                                state: LedState::Blink {
+                                    on_ms: 100,
+                                    off_ms: 1900,
+                                    count: None,
+                                },
+                                color: STATUS_COLORS.low_battery,
+                                ttl_ms: None,
+                            });
+                        } else {
+                            send_indicator_command(&tasks, LedCommand::Release {
+                                led: indicator(STATUS_LED),
+                                source: LedSource::LowBattery,
+                            });
+                        }
+                    }
+
                     FreeRtos::delay_ms(20);
                 }
             })
@@ -108,6 +420,222 @@ impl EventCoordinator {
                 Error::new_unexpected(&format!("failed to spawn event_coordinator: {e}"))
             })?;
 
-        Ok((Self { _handle: h }, button_tx, ble_tx))
+        Ok((Self { _handle: h }, button_tx, ble_tx, led_tx, battery_tx))
+    }
+}
+
+/// 表示用途ごとのLED名（`pins.json` の `leds`）
+const STATUS_LED: &str = "status";
+const CONNECTION_LED: &str = "connection";
+const ERROR_LED: &str = "error";
+/// 各用途の表示を色付きでまとめて表示する RGB LED（無ければ使わない）
+const RGB_LED: &str = "rgb";
+
+/// 用途に対応するLED（`pins.json` に無ければ先頭のLEDで代用し、優先度で表示を切り替える）
+fn indicator(name: &str) -> LedId {
+    LedId::from_name(name).unwrap_or_default()
+}
+
+/// 用途ごとのLEDへのコマンドを送り、`rgb` LED があれば同じコマンドを重ねて送る
+fn send_indicator_command(tasks: &Tasks, cmd: LedCommand) {
+    if let Some(rgb) = LedId::from_name(RGB_LED) {
+        if cmd.led() != Some(rgb) {
+            tasks.send_led_command(cmd.clone().with_led(rgb));
+        }
     }
+    tasks.send_led_command(cmd);
+}
+
+/// アドバタイズの継続時間（新規ペアリングの受付は短く、ボンド済みの再接続待ちは長く）
+fn advertise_timeout_ms(mode: AdvertiseMode) -> u32 {
+    match mode {
+        AdvertiseMode::OpenPairing => 60_000,
+        AdvertiseMode::BondedOnly => 180_000,
+    }
+}
+
+/// エラー表示の継続時間（再度エラーが通知されれば延長）
+const ERROR_LED_TTL_MS: u32 = 10_000;
+
+/// BLE状態の表示を要求（エラー表示中は裏で保持され、エラー解除後に表示される）
+fn request_ble_led(tasks: &Tasks, state: LedState, color: Rgb) {
+    let cmd = LedCommand::Request {
+        led: indicator(CONNECTION_LED),
+        source: LedSource::Ble,
+        state,
+        color,
+        ttl_ms: None,
+    };
+    log::debug!("Sending LED command: {:?}", cmd);
+    send_indicator_command(tasks, cmd);
+}
+
+/// エラー表示を一定時間要求（プリセット "error" が無ければ高速点滅）
+fn request_error_led(tasks: &Tasks) {
+    let state = led::preset("error")
+        .map(LedState::Pattern)
+        .unwrap_or(LedState::Blink {
+            on_ms: 100,
+            off_ms: 100,
+            count: None,
+        });
+    let cmd = LedCommand::Request {
+        led: indicator(ERROR_LED),
+        source: LedSource::Error,
+        state,
+        color: STATUS_COLORS.error,
+        ttl_ms: Some(ERROR_LED_TTL_MS),
+    };
+    log::debug!("Sending LED command: {:?}", cmd);
+    send_indicator_command(tasks, cmd);
+}
+
+/// イベント通知の購読者がいる時だけ BLE タスクに通知を送る（購読者がいない間はチャネルを使わない）
+fn notify_subscribers(tasks: &Tasks, notification: BleNotification) {
+    if tasks.has_ble_subscribers() {
+        tasks.send_ble_command(BleCommand::Notify(notification));
+    }
+}
+
+/// リモートコマンドの実行結果を、コマンドを受け付けた接続・経路で通知
+fn respond(tasks: &Tasks, reply: ReplyTo, message: String) {
+    tasks.send_ble_command(BleCommand::Respond { reply, message });
+}
+
+/// ログ出力レベルを変更（`log` クレートと ESP-IDF のコンポーネントの両方）
+///
+/// sdkconfig の `CONFIG_LOG_MAXIMUM_LEVEL` より詳細なレベルは出力されない。
+fn set_log_level(level: log::LevelFilter) {
+    log::set_max_level(level);
+    let esp_level = match level {
+        log::LevelFilter::Off => esp_idf_sys::esp_log_level_t_ESP_LOG_NONE,
+        log::LevelFilter::Error => esp_idf_sys::esp_log_level_t_ESP_LOG_ERROR,
+        log::LevelFilter::Warn => esp_idf_sys::esp_log_level_t_ESP_LOG_WARN,
+        log::LevelFilter::Info => esp_idf_sys::esp_log_level_t_ESP_LOG_INFO,
+        log::LevelFilter::Debug => esp_idf_sys::esp_log_level_t_ESP_LOG_DEBUG,
+        log::LevelFilter::Trace => esp_idf_sys::esp_log_level_t_ESP_LOG_VERBOSE,
+    };
+    unsafe { esp_idf_sys::esp_log_level_set(c"*".as_ptr(), esp_level) };
+    log::warn!("Log level changed to {level}");
+}
+
+/// 登録済みタスクの稼働状況とヒープ残量（1行）
+fn task_report(tasks: &Tasks) -> String {
+    let (uptime_us, heap_free, heap_min, freertos_tasks) = unsafe {
+        (
+            esp_idf_sys::esp_timer_get_time(),
+            esp_idf_sys::esp_get_free_heap_size(),
+            esp_idf_sys::esp_get_minimum_free_heap_size(),
+            esp_idf_sys::uxTaskGetNumberOfTasks(),
+        )
+    };
+    let mut report = format!(
+        "uptime_s={} heap_free={heap_free} heap_min={heap_min} freertos_tasks={freertos_tasks}",
+        uptime_us / 1_000_000
+    );
+    for (name, running) in tasks.task_status() {
+        report.push_str(&format!(
+            " {name}={}",
+            if running { "running" } else { "stopped" }
+        ));
+    }
+    report
+}
+
+/// スキャン結果の応答（`devices=<件数> <address> rssi=<dBm> [name="<名前>"] ...`）
+fn scan_report(devices: &[ScannedDevice]) -> String {
+    let mut report = format!("devices={}", devices.len());
+    for device in devices {
+        report.push_str(&format!(" {} rssi={}", device.address, device.rssi));
+        if let Some(name) = &device.name {
+            report.push_str(&format!(" name={}", scan::quote_name(name)));
+        }
+    }
+    report
+}
+
+fn connections_report(connections: &[Connection]) -> String {
+    let mut report = format!(
+        "connections={}/{}",
+        connections.len(),
+        BleConfig::CONNECTIONS.max
+    );
+    for connection in connections {
+        report.push_str(&format!(
+            " {} conn={} mtu={} bonded={}",
+            connection.address, connection.conn_handle, connection.mtu, connection.bonded as u8
+        ));
+        if let Some(rssi) = connection.rssi {
+            report.push_str(&format!(" rssi={rssi}"));
+        }
+    }
+    report
+}
+
+/// リモートからのLED操作を `LedCommand` に変換
+fn remote_led_command(
+    led_name: &str,
+    mode: RemoteLedMode,
+    color: Option<Rgb>,
+) -> Result<LedCommand> {
+    let id = LedId::from_name(led_name)
+        .ok_or_else(|| Error::new_invalid_state(&format!("unknown LED: {led_name}")))?;
+    let state = match mode {
+        RemoteLedMode::On => LedState::On,
+        RemoteLedMode::Off => LedState::Off,
+        RemoteLedMode::Level(level) => LedState::Brightness(level),
+        RemoteLedMode::Blink {
+            on_ms,
+            off_ms,
+            count,
+        } => LedState::Blink {
+            on_ms,
+            off_ms,
+            count,
+        },
+        RemoteLedMode::Fade { level, duration_ms } => LedState::FadeTo { level, duration_ms },
+        RemoteLedMode::Breathe { period_ms } => LedState::Breathe { period_ms },
+        RemoteLedMode::Preset(name) => led::preset(&name)
+            .map(LedState::Pattern)
+            .ok_or_else(|| Error::new_invalid_state(&format!("unknown pattern: {name}")))?,
+        RemoteLedMode::Release => {
+            return Ok(LedCommand::Release {
+                led: id,
+                source: LedSource::Remote,
+            })
+        }
+    };
+
+    Ok(LedCommand::Request {
+        led: id,
+        source: LedSource::Remote,
+        state,
+        color: color.unwrap_or(Rgb::WHITE),
+        ttl_ms: None,
+    })
+}
+
+/// アドバタイズ中の表示
+fn advertising_led_state() -> LedState {
+    LedState::Blink {
+        on_ms: 500,
+        off_ms: 500,
+        count: None,
+    }
+}
+
+/// 切断後の再アドバタイズ中の表示（プリセット "reconnecting" が無ければ短い点滅）
+fn reconnect_led_state() -> LedState {
+    led::preset("reconnecting")
+        .map(LedState::Pattern)
+        .unwrap_or(LedState::Blink {
+            on_ms: 100,
+            off_ms: 400,
+            count: None,
+        })
+}
+
+/// 次の再アドバタイズを待っている間の表示
+fn reconnect_waiting_led_state() -> LedState {
+    LedState::Breathe { period_ms: 3000 }
 }
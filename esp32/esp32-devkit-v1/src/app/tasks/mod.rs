@@ -1,11 +1,13 @@
+pub mod button_actions;
 pub mod event_coordinator;
 pub mod task_manager;
 
-use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::sync::{mpsc, Arc, Mutex, PoisonError, Weak};
 
+use crate::app::battery::battery_event::BatteryEvent;
 use crate::app::ble::{ble_event::BleEvent, ble_handle::BleHandle};
 use crate::app::button::event::ButtonEvent;
-use crate::app::led::led_handle::LedHandle;
+use crate::app::led::{led_event::LedEvent, led_handle::LedHandle};
 
 pub use task_manager::TaskManager;
 
@@ -15,6 +17,10 @@ pub struct Tasks {
     ble_handle: Mutex<Option<BleHandle>>,
     button_event_tx: Mutex<Option<mpsc::Sender<ButtonEvent>>>,
     ble_event_tx: Mutex<Option<mpsc::Sender<BleEvent>>>,
+    led_event_tx: Mutex<Option<mpsc::Sender<LedEvent>>>,
+    battery_event_tx: Mutex<Option<mpsc::Sender<BatteryEvent>>>,
+    /// 起動したタスクの名前と生存確認用の参照（スレッドが終了すると参照先が破棄される）
+    registry: Mutex<Vec<(&'static str, Weak<()>)>>,
 }
 
 impl Tasks {
@@ -24,6 +30,9 @@ impl Tasks {
             ble_handle: Mutex::new(None),
             button_event_tx: Mutex::new(None),
             ble_event_tx: Mutex::new(None),
+            led_event_tx: Mutex::new(None),
+            battery_event_tx: Mutex::new(None),
+            registry: Mutex::new(Vec::new()),
         })
     }
 
@@ -77,6 +86,12 @@ impl Tasks {
         }
     }
 
+    /// BLE のイベント通知の購読者がいるか
+    pub fn has_ble_subscribers(&self) -> bool {
+        Self::lock_or_log(&self.ble_handle, "ble_handle")
+            .is_some_and(|guard| guard.as_ref().is_some_and(BleHandle::has_subscribers))
+    }
+
     pub fn set_button_event_tx(&self, tx: mpsc::Sender<ButtonEvent>) {
         if let Some(mut guard) = Self::lock_or_log(&self.button_event_tx, "button_event_tx") {
             *guard = Some(tx);
@@ -112,4 +127,61 @@ impl Tasks {
             }
         }
     }
+
+    pub fn set_led_event_tx(&self, tx: mpsc::Sender<LedEvent>) {
+        if let Some(mut guard) = Self::lock_or_log(&self.led_event_tx, "led_event_tx") {
+            *guard = Some(tx);
+        }
+    }
+
+    pub fn send_led_event(&self, event: LedEvent) {
+        if let Some(guard) = Self::lock_or_log(&self.led_event_tx, "led_event_tx") {
+            if let Some(tx) = guard.as_ref() {
+                if let Err(e) = tx.send(event) {
+                    log::error!("failed to send led event: {e}");
+                }
+            } else {
+                log::warn!("led event channel not set; dropping event");
+            }
+        }
+    }
+
+    pub fn set_battery_event_tx(&self, tx: mpsc::Sender<BatteryEvent>) {
+        if let Some(mut guard) = Self::lock_or_log(&self.battery_event_tx, "battery_event_tx") {
+            *guard = Some(tx);
+        }
+    }
+
+    pub fn send_battery_event(&self, event: BatteryEvent) {
+        if let Some(guard) = Self::lock_or_log(&self.battery_event_tx, "battery_event_tx") {
+            if let Some(tx) = guard.as_ref() {
+                if let Err(e) = tx.send(event) {
+                    log::error!("failed to send battery event: {e}");
+                }
+            } else {
+                log::warn!("battery event channel not set; dropping event");
+            }
+        }
+    }
+
+    /// タスクを登録し、生存確認用の値を返す（タスクのスレッドが終了まで保持する）
+    pub fn register_task(&self, name: &'static str) -> Arc<()> {
+        let alive = Arc::new(());
+        if let Some(mut guard) = Self::lock_or_log(&self.registry, "registry") {
+            guard.push((name, Arc::downgrade(&alive)));
+        }
+        alive
+    }
+
+    /// 登録済みタスクの名前と稼働中か
+    pub fn task_status(&self) -> Vec<(&'static str, bool)> {
+        Self::lock_or_log(&self.registry, "registry")
+            .map(|guard| {
+                guard
+                    .iter()
+                    .map(|(name, alive)| (*name, alive.strong_count() > 0))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
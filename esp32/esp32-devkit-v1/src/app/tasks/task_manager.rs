@@ -1,13 +1,14 @@
 use std::sync::Arc;
 
 use crate::app::{
+    battery::{battery_task::BatteryTask, Battery},
     ble::ble_task::BleTask,
     button::{task::ButtonTask, Button},
     led::{led_task::LedTask, Led},
     tasks::{event_coordinator, Tasks},
 };
 use crate::common::Result;
-use crate::config::pins::Pins;
+use crate::config::pins::{Pins, BUTTONS, BUTTON_INPUT_MODE, GESTURES};
 
 /// タスク起動の入口
 pub struct TaskManager {
@@ -16,6 +17,7 @@ pub struct TaskManager {
     led_task: Option<LedTask>,
     button_task: Option<ButtonTask>,
     ble_task: Option<BleTask>,
+    battery_task: Option<BatteryTask>,
     event_coordinator: Option<event_coordinator::EventCoordinator>,
 }
 
@@ -26,33 +28,40 @@ impl TaskManager {
             led_task: None,
             button_task: None,
             ble_task: None,
+            battery_task: None,
             event_coordinator: None,
         }
     }
 
     pub fn start(&mut self) -> Result<()> {
         let pins = Pins::take()?;
-        let led = Led::new(pins.led);
-        let button = Button::new(pins.button)?;
+        let leds = pins.leds.into_iter().map(Led::new).collect();
+        let buttons = pins
+            .buttons
+            .into_iter()
+            .zip(BUTTONS.iter())
+            .map(|(pin, config)| Button::new(pin, config))
+            .collect::<Result<Vec<_>>>()?;
 
         self.start_event_coordinator()?;
-        self.start_led_task(led)?;
-        self.start_button_task(button)?;
+        self.start_led_task(leds)?;
+        self.start_button_task(buttons)?;
         self.start_ble_task()?;
+        self.start_battery_task(pins.battery)?;
 
         Ok(())
     }
 
-    fn start_led_task(&mut self, led: Led) -> Result<()> {
-        let (led_task, led_handle) = LedTask::start(led)?;
+    fn start_led_task(&mut self, leds: Vec<Led>) -> Result<()> {
+        let (led_task, led_handle) = LedTask::start(self.tasks.clone(), leds)?;
         self.led_task = Some(led_task);
         self.tasks.set_led_handle(led_handle);
 
         Ok(())
     }
 
-    fn start_button_task(&mut self, button: Button) -> Result<()> {
-        let t = ButtonTask::start(self.tasks.clone(), button)?;
+    fn start_button_task(&mut self, buttons: Vec<Button>) -> Result<()> {
+        let t = ButtonTask::start(self.tasks.clone(), buttons, GESTURES, BUTTON_INPUT_MODE)?;
         self.button_task = Some(t);
         Ok(())
     }
@@ -64,11 +73,24 @@ impl TaskManager {
         Ok(())
     }
 
+    /// `pins.json` に `battery` が無ければ起動しない
+    fn start_battery_task(&mut self, battery: Option<Battery>) -> Result<()> {
+        let Some(battery) = battery else {
+            log::info!("Battery not configured; battery task not started");
+            return Ok(());
+        };
+        let t = BatteryTask::start(self.tasks.clone(), battery)?;
+        self.battery_task = Some(t);
+        Ok(())
+    }
+
     fn start_event_coordinator(&mut self) -> Result<()> {
-        let (coordinator, button_event_tx, ble_event_tx) =
+        let (coordinator, button_event_tx, ble_event_tx, led_event_tx, battery_event_tx) =
             event_coordinator::EventCoordinator::start(self.tasks.clone())?;
         self.tasks.set_button_event_tx(button_event_tx);
         self.tasks.set_ble_event_tx(ble_event_tx);
+        self.tasks.set_led_event_tx(led_event_tx);
+        self.tasks.set_battery_event_tx(battery_event_tx);
         self.event_coordinator = Some(coordinator);
         Ok(())
     }
@@ -1,3 +1,75 @@
+/// ペアリング方式（`ble.json` の `security.mode` で選択）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BleSecurityMode {
+    /// 暗号化なし（誰でも接続・読み書きできる）
+    None,
+    /// LE Secure Connections（MITM 保護なし）
+    JustWorks,
+    /// デバイスがパスキー（接続ごとに生成）を表示し、相手が入力する
+    PasskeyDisplay,
+    /// 相手が表示したパスキーを `passkey` コマンドでデバイスに入力する
+    PasskeyEntry,
+}
+
+/// BLE のセキュリティ設定（`ble.json` の `security` から生成）
+#[derive(Debug, Clone, Copy)]
+pub struct BleSecurity {
+    pub mode: BleSecurityMode,
+    /// ペアリング情報を NVS に保存するか
+    pub bonding: bool,
+}
+
+/// アドバタイズパケット・スキャンレスポンスに載せる項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvField {
+    Name,
+    ServiceUuid,
+    Appearance,
+    ManufacturerData,
+    /// 現在の状態（`advertising.status`、`devkit_core::ble::adv_status` の形式）
+    Status,
+}
+
+/// アドバタイズに載せる状態データの設定
+#[derive(Debug, Clone, Copy)]
+pub struct AdvStatusConfig {
+    pub company_id: u16,
+    /// 状態を反映する周期
+    pub interval_ms: u32,
+}
+
+/// アドバタイズ設定（`ble.json` の `advertising` から生成、サイズはビルド時に検証済み）
+#[derive(Debug, Clone, Copy)]
+pub struct AdvertisingConfig {
+    pub interval_min_ms: u32,
+    pub interval_max_ms: u32,
+    pub tx_power: esp32_nimble::enums::PowerLevel,
+    /// false なら接続を受け付けないアドバタイズにする
+    pub connectable: bool,
+    pub appearance: u16,
+    /// Company ID（リトルエンディアン）+ データ
+    pub manufacturer_data: &'static [u8],
+    pub status: Option<AdvStatusConfig>,
+    pub data: &'static [AdvField],
+    /// 空ならスキャンレスポンスを返さない
+    pub scan_response: &'static [AdvField],
+}
+
+pub use devkit_core::ble::beacon::{
+    BeaconConfig, BeaconMode, EddystoneConfig, EddystoneFrame, IBeaconConfig,
+};
+
+/// 同時接続の設定（`ble.json` の `connections` から生成）
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionsConfig {
+    /// 同時に接続できるセントラルの数
+    pub max: u8,
+    /// 上限に達するまで接続中もアドバタイズを続けるか
+    pub keep_advertising: bool,
+}
+
+pub use devkit_core::ble::reconnect::{ReconnectConfig, ReconnectPolicy};
+
 // build.rs で生成される BLE 設定
 include!(concat!(env!("OUT_DIR"), "/ble_gen.rs"));
 
@@ -7,5 +79,27 @@ pub struct BleConfig;
 impl BleConfig {
     pub const SERVICE_UUID: &'static str = BLE_SERVICE_UUID;
     pub const CHARACTERISTIC_UUID: &'static str = BLE_CHARACTERISTIC_UUID;
+    /// リモートコマンドの書き込み先
+    pub const COMMAND_CHARACTERISTIC_UUID: &'static str = BLE_COMMAND_CHARACTERISTIC_UUID;
+    /// コマンド実行結果の通知元
+    pub const RESPONSE_CHARACTERISTIC_UUID: &'static str = BLE_RESPONSE_CHARACTERISTIC_UUID;
+    /// 状態変化・ボタン・LEDイベントの通知元
+    pub const EVENT_CHARACTERISTIC_UUID: &'static str = BLE_EVENT_CHARACTERISTIC_UUID;
     pub const DEVICE_NAME: &'static str = BLE_DEVICE_NAME;
+    /// Device Information Service の製造者名・モデル番号
+    pub const MANUFACTURER: &'static str = BLE_MANUFACTURER;
+    pub const MODEL: &'static str = BLE_MODEL;
+    pub const SECURITY: BleSecurity = BLE_SECURITY;
+    pub const ADVERTISING: AdvertisingConfig = BLE_ADVERTISING;
+    pub const BEACON: BeaconConfig = BLE_BEACON;
+    pub const CONNECTIONS: ConnectionsConfig = BLE_CONNECTIONS;
+    pub const RECONNECT: ReconnectConfig = BLE_RECONNECT;
+    /// Nordic UART Service のコンソールを有効にするか
+    pub const NUS_ENABLED: bool = BLE_NUS_ENABLED;
+    /// NUS の UUID（端末アプリが認識する固定値）
+    pub const NUS_SERVICE_UUID: &'static str = "6e400001-b5a3-f393-e0a9-e50e24dcca9e";
+    /// 端末 → デバイス
+    pub const NUS_RX_CHARACTERISTIC_UUID: &'static str = "6e400002-b5a3-f393-e0a9-e50e24dcca9e";
+    /// デバイス → 端末
+    pub const NUS_TX_CHARACTERISTIC_UUID: &'static str = "6e400003-b5a3-f393-e0a9-e50e24dcca9e";
 }
@@ -0,0 +1,33 @@
+use std::borrow::Cow;
+
+use crate::app::led::color::Rgb;
+use crate::app::led::pattern::{LedPattern, PatternRepeat, PatternStep};
+
+/// 状態ごとの表示色（`config/led_colors.json` から生成、RGB LED のみ反映）
+#[derive(Debug, Clone, Copy)]
+pub struct StatusColors {
+    /// BLEアドバタイズ中
+    pub advertising: Rgb,
+    /// BLE接続中
+    pub connected: Rgb,
+    /// エラー表示
+    pub error: Rgb,
+    /// ボタン操作へのフィードバック
+    pub feedback: Rgb,
+    /// バッテリー残量低下
+    pub low_battery: Rgb,
+}
+
+// build.rs で生成される LED パターンのプリセット
+include!(concat!(env!("OUT_DIR"), "/led_patterns_gen.rs"));
+
+// build.rs で生成される状態ごとの表示色
+include!(concat!(env!("OUT_DIR"), "/led_colors_gen.rs"));
+
+/// `config/led_patterns.json` で定義したプリセットを名前で取得
+pub fn preset(name: &str) -> Option<LedPattern> {
+    LED_PATTERN_PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, pattern)| pattern.clone())
+}
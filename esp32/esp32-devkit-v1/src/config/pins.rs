@@ -1,13 +1,73 @@
+use crate::app::battery::Battery;
+use crate::app::led::ws2812::Ws2812;
 use crate::common::{Error, Result};
-use esp_idf_hal::gpio::{Input, Output, PinDriver, Pull};
+use esp_idf_hal::gpio::{AnyIOPin, AnyOutputPin, Input, Output, PinDriver, Pull};
+use esp_idf_hal::ledc::{config::TimerConfig, LedcDriver, LedcTimerDriver, Resolution};
 use esp_idf_hal::peripherals::Peripherals;
+use esp_idf_hal::rmt::{config::TransmitConfig, TxRmtDriver};
+use esp_idf_hal::units::FromValueType;
+
+/// LEDの駆動方式（`pins.json` の `leds[].driver` で選択）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedDriverKind {
+    /// GPIO のオン/オフのみ
+    Gpio,
+    /// LEDC PWM による輝度制御
+    Pwm,
+    /// RMT で駆動する WS2812（NeoPixel）RGB LED
+    Ws2812,
+}
+
+/// LED用に初期化済みの出力
+pub enum LedPin {
+    Gpio(PinDriver<'static, AnyOutputPin, Output>),
+    Pwm(LedcDriver<'static>),
+    Ws2812(Ws2812),
+}
+
+/// LEDの名前と駆動方式（`pins.json` の `leds` から生成）
+#[derive(Debug, Clone, Copy)]
+pub struct LedPinConfig {
+    /// コマンドの宛先指定に使う名前
+    pub name: &'static str,
+    pub driver: LedDriverKind,
+}
+
+/// ボタン入力の検出方式（`pins.json` の `button_mode` で選択）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonInputMode {
+    /// GPIOエッジ割り込み + タスク側でソフトウェアデバウンス
+    Interrupt,
+    /// 一定周期でのポーリング（従来方式）
+    Polling,
+}
+
+/// ボタンの電気的特性とデバウンス設定（`pins.json` の `buttons` から生成）
+#[derive(Debug, Clone, Copy)]
+pub struct ButtonPinConfig {
+    /// イベントのルーティングに使う名前
+    pub name: &'static str,
+    /// 押下時に LOW になる配線か
+    pub active_low: bool,
+    /// 内蔵プルアップ/プルダウン設定
+    pub pull: Pull,
+    /// デバウンス窓（ms）
+    pub debounce_ms: u32,
+}
+
+pub use devkit_core::battery::level::BatteryPinConfig;
+pub use devkit_core::button::gesture::GestureConfig;
 
 // build.rs で生成されるピン設定
 include!(concat!(env!("OUT_DIR"), "/pins_gen.rs"));
 
 pub struct Pins {
-    pub led: PinDriver<'static, LedPinType, Output>,
-    pub button: PinDriver<'static, ButtonPinType, Input>,
+    /// `LEDS` と同じ並び順
+    pub leds: Vec<LedPin>,
+    /// `BUTTONS` と同じ並び順
+    pub buttons: Vec<PinDriver<'static, AnyIOPin, Input>>,
+    /// `BATTERY` が設定されている場合のみ
+    pub battery: Option<Battery>,
 }
 
 impl Pins {
@@ -16,17 +76,118 @@ impl Pins {
             .map_err(|e| Error::new_esp(&format!("failed to take peripherals: {e}")))?;
 
         // build.rs で生成した関数で、必要なピンだけを取り出す
-        let (led_raw, button_raw) = split_pins(peripherals);
+        let (leds_raw, buttons_raw, battery) = split_pins(peripherals.pins, peripherals.adc1);
+        let battery = battery?;
+
+        let ledc = peripherals.ledc;
+        let rmt = peripherals.rmt;
+
+        // PWM LED は同じタイマーを共有する（8bit分解能で輝度 0-255 をそのままデューティに対応させる）
+        let pwm_timer = if LEDS.iter().any(|l| l.driver == LedDriverKind::Pwm) {
+            Some(
+                LedcTimerDriver::new(
+                    ledc.timer0,
+                    &TimerConfig::default()
+                        .frequency(5.kHz().into())
+                        .resolution(Resolution::Bits8),
+                )
+                .map_err(|e| Error::new_esp(&format!("failed to init LEDC timer: {e}")))?,
+            )
+        } else {
+            None
+        };
+
+        // LEDC / RMT チャネルは `LEDS` の並び順に先頭から割り当てる（数は build.rs で検証済み）
+        let mut pwm_channels = (
+            Some(ledc.channel0),
+            Some(ledc.channel1),
+            Some(ledc.channel2),
+            Some(ledc.channel3),
+        );
+        let mut rmt_channels = (
+            Some(rmt.channel0),
+            Some(rmt.channel1),
+            Some(rmt.channel2),
+            Some(rmt.channel3),
+        );
+
+        let mut leds = Vec::with_capacity(LEDS.len());
+        for (pin, config) in leds_raw.into_iter().zip(LEDS.iter()) {
+            let led = match config.driver {
+                LedDriverKind::Gpio => LedPin::Gpio(PinDriver::output(pin).map_err(|e| {
+                    Error::new_esp(&format!("failed to init LED pin '{}': {e}", config.name))
+                })?),
+                LedDriverKind::Pwm => {
+                    let timer = pwm_timer
+                        .as_ref()
+                        .ok_or_else(|| Error::new_invalid_state("LEDC timer is not initialized"))?;
+                    let driver = if let Some(channel) = pwm_channels.0.take() {
+                        LedcDriver::new(channel, timer, pin)
+                    } else if let Some(channel) = pwm_channels.1.take() {
+                        LedcDriver::new(channel, timer, pin)
+                    } else if let Some(channel) = pwm_channels.2.take() {
+                        LedcDriver::new(channel, timer, pin)
+                    } else if let Some(channel) = pwm_channels.3.take() {
+                        LedcDriver::new(channel, timer, pin)
+                    } else {
+                        return Err(Error::new_invalid_state(&format!(
+                            "no LEDC channel left for LED '{}'",
+                            config.name
+                        )));
+                    };
+
+                    LedPin::Pwm(driver.map_err(|e| {
+                        Error::new_esp(&format!(
+                            "failed to init LEDC channel for LED '{}': {e}",
+                            config.name
+                        ))
+                    })?)
+                }
+                LedDriverKind::Ws2812 => {
+                    // 1クロック = 12.5ns（80MHz）でパルス幅を表現
+                    let rmt_config = TransmitConfig::new().clock_divider(1);
+                    let tx = if let Some(channel) = rmt_channels.0.take() {
+                        TxRmtDriver::new(channel, pin, &rmt_config)
+                    } else if let Some(channel) = rmt_channels.1.take() {
+                        TxRmtDriver::new(channel, pin, &rmt_config)
+                    } else if let Some(channel) = rmt_channels.2.take() {
+                        TxRmtDriver::new(channel, pin, &rmt_config)
+                    } else if let Some(channel) = rmt_channels.3.take() {
+                        TxRmtDriver::new(channel, pin, &rmt_config)
+                    } else {
+                        return Err(Error::new_invalid_state(&format!(
+                            "no RMT channel left for LED '{}'",
+                            config.name
+                        )));
+                    };
 
-        let led = PinDriver::output(led_raw)
-            .map_err(|e| Error::new_esp(&format!("failed to output: {e}")))?;
+                    let tx = tx.map_err(|e| {
+                        Error::new_esp(&format!(
+                            "failed to init RMT channel for LED '{}': {e}",
+                            config.name
+                        ))
+                    })?;
+                    LedPin::Ws2812(Ws2812::new(tx)?)
+                }
+            };
+            leds.push(led);
+        }
 
-        let mut button = PinDriver::input(button_raw)
-            .map_err(|e| Error::new_esp(&format!("failed to init button pin: {e}")))?;
-        button
-            .set_pull(Pull::Up)
-            .map_err(|e| Error::new_esp(&format!("failed to set button pullup: {e}")))?;
+        // プル設定は Button 側で pins.json の内容に従って行う
+        let buttons = buttons_raw
+            .into_iter()
+            .zip(BUTTONS.iter())
+            .map(|(pin, config)| {
+                PinDriver::input(pin).map_err(|e| {
+                    Error::new_esp(&format!("failed to init button pin '{}': {e}", config.name))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        Ok(Self { led, button })
+        Ok(Self {
+            leds,
+            buttons,
+            battery,
+        })
     }
 }